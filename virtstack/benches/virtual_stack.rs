@@ -0,0 +1,116 @@
+//! Building and rendering a 5-deep error chain, once with virtual stack traces and once
+//! capturing a `std::backtrace::Backtrace` at every level.
+
+use std::backtrace::Backtrace;
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+/// Number of errors in each benchmarked chain, including the root cause.
+const DEPTH: usize = 5;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum VirtualError {
+    #[snafu(display("Layer {depth} failed"))]
+    Layer {
+        depth: usize,
+        #[snafu(source(from(VirtualError, Box::new)))]
+        source: Box<VirtualError>,
+    },
+
+    #[snafu(display("Root cause"))]
+    Root,
+}
+
+fn virtual_chain() -> VirtualError {
+    let mut error = RootSnafu.build();
+    for depth in 1..DEPTH {
+        error = Err::<(), _>(error)
+            .context(LayerSnafu { depth })
+            .unwrap_err();
+    }
+    error
+}
+
+/// Error capturing a full system backtrace wherever it is created.
+#[derive(Debug)]
+struct BacktraceError {
+    depth: usize,
+    backtrace: Backtrace,
+    source: Option<Box<BacktraceError>>,
+}
+
+impl BacktraceError {
+    fn new(depth: usize, source: Option<BacktraceError>) -> Self {
+        Self {
+            depth,
+            backtrace: Backtrace::force_capture(),
+            source: source.map(Box::new),
+        }
+    }
+
+    /// Renders each error of the chain followed by its backtrace.
+    fn render(&self) -> String {
+        let mut rendered = String::new();
+        let mut next = Some(self);
+        while let Some(error) = next {
+            rendered.push_str(&format!("{error}\n{}\n", error.backtrace));
+            next = error.source.as_deref();
+        }
+        rendered
+    }
+}
+
+impl std::fmt::Display for BacktraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.depth {
+            0 => write!(f, "Root cause"),
+            depth => write!(f, "Layer {depth} failed"),
+        }
+    }
+}
+
+impl std::error::Error for BacktraceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
+
+fn backtrace_chain() -> BacktraceError {
+    let mut error = BacktraceError::new(0, None);
+    for depth in 1..DEPTH {
+        error = BacktraceError::new(depth, Some(error));
+    }
+    error
+}
+
+fn virtual_stack(c: &mut Criterion) {
+    let mut group = c.benchmark_group("virtual_stack");
+    group.bench_function("build", |b| b.iter(|| black_box(virtual_chain())));
+    group.bench_function("build_and_collect", |b| {
+        b.iter(|| black_box(virtual_chain().virtual_stack()))
+    });
+    group.bench_function("build_and_format", |b| {
+        b.iter(|| black_box(format!("{:?}", virtual_chain())))
+    });
+    group.finish();
+}
+
+fn backtrace(c: &mut Criterion) {
+    let mut group = c.benchmark_group("backtrace");
+    // Resolving symbols takes milliseconds, so fewer samples keep the run short
+    group.sample_size(10);
+    group.bench_function("build", |b| b.iter(|| black_box(backtrace_chain())));
+    group.bench_function("build_and_format", |b| {
+        b.iter(|| black_box(backtrace_chain().render()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, virtual_stack, backtrace);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+//! Memoization of computed virtual stacks.
+
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "std")]
+use crate::StackFrame;
+use crate::StackIter;
+
+/// Lazily filled storage for the frames of an error, injected as an implicit field by
+/// `#[stack_trace_debug(cache)]`.
+///
+/// The first call to [`virtual_stack`](crate::VirtualStackTrace::virtual_stack), its
+/// iterator or the generated [`Debug`](core::fmt::Debug) output walks the source chain once;
+/// every later call reuses the stored frames instead of formatting each message again.
+/// Without the `std` feature nothing is stored and the stack is recomputed every time.
+#[derive(Debug, Clone, Default)]
+pub struct StackCache {
+    #[cfg(feature = "std")]
+    frames: OnceLock<alloc::vec::Vec<StackFrame>>,
+}
+
+impl StackCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Iterates over the cached frames, filling the cache from `build` on first use.
+    pub fn stack<'a>(&'a self, build: impl FnOnce() -> StackIter<'a>) -> StackIter<'a> {
+        #[cfg(feature = "std")]
+        {
+            StackIter::from_cached(self.frames.get_or_init(|| build().collect()))
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            build()
+        }
+    }
+}
+
+impl snafu::GenerateImplicitData for StackCache {
+    fn generate() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,56 @@
+//! ANSI coloring of rendered frames, enabled by the `color` feature.
+
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+pub(crate) const RESET: &str = "\x1b[0m";
+pub(crate) const INDEX: &str = "\x1b[33m";
+pub(crate) const MESSAGE: &str = "\x1b[1m";
+pub(crate) const LOCATION: &str = "\x1b[36m";
+pub(crate) const WARN: &str = "\x1b[33m";
+pub(crate) const FATAL: &str = "\x1b[31m";
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Returns whether output written to `stream` should be colored.
+///
+/// The conventional environment variables are checked first, then whether `stream` is a
+/// terminal:
+///
+/// - `NO_COLOR` set to any non-empty value disables colors.
+/// - `CLICOLOR_FORCE` set to anything but `0` enables them, even when piped.
+/// - `CLICOLOR=0` disables them.
+/// - Otherwise colors are used when `stream` is a terminal.
+///
+/// The environment is read on every call. The generated [`Debug`](core::fmt::Debug)
+/// output asks once per process for stderr, custom formatters can call this to color
+/// consistently with it.
+///
+/// # Example
+///
+/// ```rust
+/// let colored = snafu_virtstack::should_colorize(&std::io::stdout());
+/// let (bold, reset) = if colored { ("\x1b[1m", "\x1b[0m") } else { ("", "") };
+/// println!("{bold}Virtual Stack Trace{reset}");
+/// ```
+pub fn should_colorize(stream: &impl IsTerminal) -> bool {
+    if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        false
+    } else if env_flag("CLICOLOR_FORCE") {
+        true
+    } else if std::env::var_os("CLICOLOR").is_some_and(|value| value == "0") {
+        false
+    } else {
+        stream.is_terminal()
+    }
+}
+
+/// Returns whether frames of the generated `Debug` output should be colored, decided once
+/// per process by [`should_colorize`] for stderr.
+pub(crate) fn enabled() -> bool {
+    *ENABLED.get_or_init(|| should_colorize(&std::io::stderr()))
+}
+
+fn env_flag(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|value| !value.is_empty() && value != "0")
+}
@@ -0,0 +1,544 @@
+//! Process-wide switches controlling how virtual stack traces are rendered.
+
+use alloc::borrow::Cow;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use std::sync::{OnceLock, RwLock};
+
+use crate::{PathStyle, StackFrame};
+
+/// Environment variable that disables stack trace formatting when set to anything but `0`.
+pub const DISABLE_ENV_VAR: &str = "VIRTSTACK_DISABLE";
+
+/// Separator written between the message and the location of a frame unless
+/// [`set_location_separator`] replaces it.
+pub const DEFAULT_LOCATION_SEPARATOR: &str = " at ";
+
+const UNSET: u8 = 0;
+const ENABLED: u8 = 1;
+const DISABLED: u8 = 2;
+
+static OVERRIDE: AtomicU8 = AtomicU8::new(UNSET);
+static PATH_STYLE: AtomicU8 = AtomicU8::new(PathStyle::Full as u8);
+static MAX_MESSAGE_LEN: AtomicUsize = AtomicUsize::new(usize::MAX);
+static MAX_FRAMES: AtomicUsize = AtomicUsize::new(usize::MAX);
+static ROOT_FIRST: AtomicBool = AtomicBool::new(false);
+static CLEAN_MESSAGES: AtomicBool = AtomicBool::new(false);
+static COMPACT_LEAF: AtomicBool = AtomicBool::new(false);
+#[cfg(feature = "std")]
+static ENV_DISABLED: OnceLock<bool> = OnceLock::new();
+#[cfg(feature = "std")]
+static OBSERVER: OnceLock<Observer> = OnceLock::new();
+#[cfg(feature = "std")]
+static PATH_TRIMMER: OnceLock<PathTrimmer> = OnceLock::new();
+#[cfg(feature = "std")]
+static SKIP_FILES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+#[cfg(feature = "std")]
+static LOCATION_SEPARATOR: RwLock<&str> = RwLock::new(DEFAULT_LOCATION_SEPARATOR);
+
+#[cfg(feature = "std")]
+type Observer = Box<dyn Fn(&[StackFrame]) + Send + Sync>;
+#[cfg(feature = "std")]
+type PathTrimmer = Box<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Overrides whether the generated [`Debug`] implementations render the virtual stack trace.
+///
+/// When disabled, `{:?}` only writes the single line `Error: {error}` and skips walking
+/// the source chain entirely. This takes precedence over the [`VIRTSTACK_DISABLE`]
+/// environment variable and can be changed at any time.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::stack_trace_debug;
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Something went wrong"))]
+///     SomethingWrong,
+/// }
+///
+/// snafu_virtstack::set_enabled(false);
+/// assert_eq!(format!("{:?}", SomethingWrongSnafu.build()), "Error: Something went wrong");
+/// snafu_virtstack::set_enabled(true);
+/// ```
+///
+/// [`VIRTSTACK_DISABLE`]: DISABLE_ENV_VAR
+pub fn set_enabled(enabled: bool) {
+    let value = if enabled { ENABLED } else { DISABLED };
+    OVERRIDE.store(value, Ordering::Relaxed);
+}
+
+/// Returns whether virtual stack traces are currently rendered by [`Debug`].
+///
+/// A value set through [`set_enabled`] wins; otherwise the [`VIRTSTACK_DISABLE`]
+/// environment variable is consulted. The environment is only read once per process, and
+/// not at all without the `std` feature.
+///
+/// [`VIRTSTACK_DISABLE`]: DISABLE_ENV_VAR
+pub fn is_enabled() -> bool {
+    match OVERRIDE.load(Ordering::Relaxed) {
+        ENABLED => true,
+        DISABLED => false,
+        _ => !env_disabled(),
+    }
+}
+
+/// Sets how file paths are shown whenever a [`Location`](crate::Location) is displayed,
+/// including in the generated [`Debug`] output. Defaults to [`PathStyle::Full`].
+///
+/// # Example
+///
+/// ```rust
+/// use snafu_virtstack::{Location, PathStyle};
+///
+/// let location = Location::new("app/src/config.rs", 42, 15);
+///
+/// snafu_virtstack::set_path_style(PathStyle::FileName);
+/// assert_eq!(location.to_string(), "config.rs:42:15");
+/// snafu_virtstack::set_path_style(PathStyle::Full);
+/// assert_eq!(location.to_string(), "app/src/config.rs:42:15");
+/// ```
+pub fn set_path_style(style: PathStyle) {
+    PATH_STYLE.store(style as u8, Ordering::Relaxed);
+}
+
+/// Returns the [`PathStyle`] selected through [`set_path_style`].
+pub fn path_style() -> PathStyle {
+    match PATH_STYLE.load(Ordering::Relaxed) {
+        value if value == PathStyle::Relative as u8 => PathStyle::Relative,
+        value if value == PathStyle::FileName as u8 => PathStyle::FileName,
+        _ => PathStyle::Full,
+    }
+}
+
+/// Installs a function rewriting the file path of every displayed
+/// [`Location`](crate::Location), such as one stripping everything before `src/`.
+///
+/// The function runs before the [`path_style`] is applied, wherever a location is rendered:
+/// its [`Display`](core::fmt::Display) output, and therefore the generated [`Debug`] output
+/// and the built-in formatters, as well as [`to_logfmt`] and similar renderers. Captured
+/// locations and serialized frames keep the original path. While no function is installed,
+/// paths are rendered unchanged.
+///
+/// The function can only be installed once per process, so that all errors are rendered
+/// alike; this returns `false` and drops `trimmer` if one already is. It may be called
+/// from any thread, concurrently, so it must be `Send + Sync`, and it shouldn't panic or
+/// render locations itself.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu_virtstack::Location;
+///
+/// let installed = snafu_virtstack::set_path_trimmer(|file| match file.find("src/") {
+///     Some(start) => file[start..].to_string(),
+///     None => file.to_string(),
+/// });
+/// assert!(installed);
+///
+/// let location = Location::new("/ci/build/app/src/config.rs", 42, 15);
+/// assert_eq!(location.to_string(), "src/config.rs:42:15");
+/// assert_eq!(location.file(), "/ci/build/app/src/config.rs");
+///
+/// // Later calls are ignored
+/// assert!(!snafu_virtstack::set_path_trimmer(|_| String::new()));
+/// assert_eq!(location.to_string(), "src/config.rs:42:15");
+/// ```
+///
+/// [`to_logfmt`]: crate::VirtualStackTrace::to_logfmt
+#[cfg(feature = "std")]
+pub fn set_path_trimmer(trimmer: impl Fn(&str) -> String + Send + Sync + 'static) -> bool {
+    PATH_TRIMMER.set(Box::new(trimmer)).is_ok()
+}
+
+/// Returns `file` as it is rendered, rewritten by the [`set_path_trimmer`] function, if
+/// any, and shortened according to the [`path_style`].
+pub(crate) fn rendered_file(file: &str) -> Cow<'_, str> {
+    #[cfg(feature = "std")]
+    if let Some(trimmer) = PATH_TRIMMER.get() {
+        let trimmed = trimmer(file);
+        return Cow::Owned(path_style().trim(&trimmed).to_string());
+    }
+    Cow::Borrowed(path_style().trim(file))
+}
+
+/// Limits how many characters of each frame message are rendered, with longer messages
+/// cut off and ended with `…`. `None`, the default, renders messages in full.
+///
+/// This applies to the generated [`Debug`] output, [`StackDisplay`](crate::StackDisplay)
+/// and the built-in [`FrameFormatter`](crate::FrameFormatter)s, unless a type sets its own
+/// limit with `#[stack_trace_debug(max_message_len = N)]`. The frames themselves, as
+/// returned by [`virtual_stack`](crate::VirtualStackTrace::virtual_stack), keep their full
+/// messages.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu_virtstack::{Location, StackFrame};
+///
+/// let frame = StackFrame::new(Location::new("src/lib.rs", 1, 1), "expected value at line 1");
+///
+/// snafu_virtstack::set_max_message_len(Some(14));
+/// assert_eq!(frame.to_string(), "expected value… at src/lib.rs:1:1");
+/// snafu_virtstack::set_max_message_len(None);
+/// assert_eq!(frame.to_string(), "expected value at line 1 at src/lib.rs:1:1");
+/// ```
+pub fn set_max_message_len(max_len: Option<usize>) {
+    MAX_MESSAGE_LEN.store(max_len.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// Returns the limit selected through [`set_max_message_len`].
+pub fn max_message_len() -> Option<usize> {
+    match MAX_MESSAGE_LEN.load(Ordering::Relaxed) {
+        usize::MAX => None,
+        max_len => Some(max_len),
+    }
+}
+
+/// Limits how many frames of a trace are rendered, with the rest summarized in a single
+/// `  ... (K more frames)` line. `None`, the default, renders every frame.
+///
+/// This applies to the generated [`Debug`] output, [`StackDisplay`](crate::StackDisplay)
+/// and [`render_with`](crate::VirtualStackTrace::render_with), unless a type sets its own
+/// limit with `#[stack_trace_debug(max_frames = N)]`. Unlike `max_depth`, which limits how
+/// many frames are captured, it only shortens the display: the stack returned by
+/// [`virtual_stack`](crate::VirtualStackTrace::virtual_stack) keeps every frame.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// #[snafu(display("Query failed"))]
+/// struct QueryError {
+///     source: std::io::Error,
+/// }
+///
+/// let io = std::io::Error::other("connection reset");
+/// let error = Err::<(), _>(io).context(QuerySnafu).unwrap_err();
+///
+/// snafu_virtstack::set_max_frames(Some(1));
+/// let rendered = format!("{error:?}");
+/// snafu_virtstack::set_max_frames(None);
+///
+/// assert!(rendered.ends_with("\n  ... (1 more frame)\n"));
+/// assert!(!rendered.contains("connection reset"));
+/// assert_eq!(error.virtual_stack().len(), 2);
+/// ```
+pub fn set_max_frames(max_frames: Option<usize>) {
+    MAX_FRAMES.store(max_frames.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// Returns the limit selected through [`set_max_frames`].
+pub fn max_frames() -> Option<usize> {
+    match MAX_FRAMES.load(Ordering::Relaxed) {
+        usize::MAX => None,
+        max_frames => Some(max_frames),
+    }
+}
+
+/// Sets whether rendered traces list the root cause first, like Python tracebacks, instead
+/// of the outermost error. Defaults to `false`.
+///
+/// When set, the frames of the generated [`Debug`] output,
+/// [`StackDisplay`](crate::StackDisplay) and
+/// [`render_with`](crate::VirtualStackTrace::render_with) are written in reverse and
+/// numbered from the root cause, while the `Error:` header keeps showing the outermost
+/// error. A type can opt in on its own with `#[stack_trace_debug(root_first)]`. The order
+/// of [`virtual_stack`](crate::VirtualStackTrace::virtual_stack) never changes.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Failed to read config"))]
+///     ReadConfig { source: std::io::Error },
+/// }
+///
+/// let io = std::io::Error::other("disk on fire");
+/// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+///
+/// snafu_virtstack::set_root_first(true);
+/// let trace = error.stack_display().to_string();
+/// snafu_virtstack::set_root_first(false);
+///
+/// assert!(trace.starts_with("  0: disk on fire at "));
+/// assert!(trace.contains("\n  1: [ReadConfig] Failed to read config at "));
+/// ```
+pub fn set_root_first(root_first: bool) {
+    ROOT_FIRST.store(root_first, Ordering::Relaxed);
+}
+
+/// Returns the order selected through [`set_root_first`].
+pub fn root_first() -> bool {
+    ROOT_FIRST.load(Ordering::Relaxed)
+}
+
+/// Sets whether rendered traces strip the message of each frame's source from its own
+/// message. Defaults to `false`.
+///
+/// SNAFU errors often embed their source in their message with `display("...: {source}")`,
+/// so every frame repeats the text of all frames below it. When set, a message ending with
+/// `": "` followed by the next frame's message loses that suffix in the generated [`Debug`]
+/// output, [`StackDisplay`](crate::StackDisplay) and
+/// [`render_with`](crate::VirtualStackTrace::render_with), leaving only the text each layer
+/// adds. Messages ending differently are kept as they are, and the `Error:` header keeps
+/// showing the full message. A type can opt in on its own with
+/// `#[stack_trace_debug(clean_messages)]`. The messages of
+/// [`virtual_stack`](crate::VirtualStackTrace::virtual_stack) never change.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Failed to read config: {source}"))]
+///     ReadConfig { source: std::io::Error },
+/// }
+///
+/// let io = std::io::Error::other("disk on fire");
+/// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+///
+/// snafu_virtstack::set_clean_messages(true);
+/// let trace = error.stack_display().to_string();
+/// snafu_virtstack::set_clean_messages(false);
+///
+/// assert!(trace.starts_with("  0: [ReadConfig] Failed to read config at "));
+/// assert!(trace.contains("\n  1: disk on fire at "));
+/// ```
+pub fn set_clean_messages(clean_messages: bool) {
+    CLEAN_MESSAGES.store(clean_messages, Ordering::Relaxed);
+}
+
+/// Returns whether [`set_clean_messages`] is enabled.
+pub fn clean_messages() -> bool {
+    CLEAN_MESSAGES.load(Ordering::Relaxed)
+}
+
+/// Sets whether the generated [`Debug`] output of an error without sources fits on one
+/// line. Defaults to `false`.
+///
+/// A leaf error otherwise takes three lines, a header, the `Virtual Stack Trace:` label and
+/// its single frame. When set, it is written as `Error: {message} at {location}` instead,
+/// while errors with sources and the alternate `{:#?}` form keep the full layout. A type
+/// can opt in on its own with `#[stack_trace_debug(compact_leaf)]`.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::stack_trace_debug;
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Config not found"))]
+///     NotFound,
+/// }
+///
+/// let error = NotFoundSnafu.build();
+///
+/// snafu_virtstack::set_compact_leaf(true);
+/// let compact = format!("{error:?}");
+/// let full = format!("{error:#?}");
+/// snafu_virtstack::set_compact_leaf(false);
+///
+/// assert!(compact.starts_with("Error: Config not found at "));
+/// assert!(!compact.contains('\n'));
+/// assert!(full.contains("\nVirtual Stack Trace:\n"));
+/// ```
+///
+/// [`Debug`]: core::fmt::Debug
+pub fn set_compact_leaf(compact_leaf: bool) {
+    COMPACT_LEAF.store(compact_leaf, Ordering::Relaxed);
+}
+
+/// Returns whether [`set_compact_leaf`] is enabled.
+pub fn compact_leaf() -> bool {
+    COMPACT_LEAF.load(Ordering::Relaxed)
+}
+
+/// Replaces the `" at "` written between the message and the location of each frame, for
+/// log parsers expecting another delimiter. Defaults to [`DEFAULT_LOCATION_SEPARATOR`].
+///
+/// This applies to the [`Display`](core::fmt::Display) output of frames, the generated
+/// [`Debug`] output and [`StackDisplay`](crate::StackDisplay), unless a type sets its own
+/// separator with `#[stack_trace_debug(location_separator = " | ")]`. Without the `std`
+/// feature the separator can't be changed process-wide, only per type.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu_virtstack::{Location, StackFrame};
+///
+/// let frame = StackFrame::new(Location::new("src/lib.rs", 7, 5), "connection reset");
+///
+/// snafu_virtstack::set_location_separator(" | ");
+/// let rendered = frame.to_string();
+/// snafu_virtstack::set_location_separator(snafu_virtstack::DEFAULT_LOCATION_SEPARATOR);
+///
+/// assert_eq!(rendered, "connection reset | src/lib.rs:7:5");
+/// ```
+///
+/// [`Debug`]: core::fmt::Debug
+#[cfg(feature = "std")]
+pub fn set_location_separator(separator: &'static str) {
+    *LOCATION_SEPARATOR
+        .write()
+        .unwrap_or_else(|err| err.into_inner()) = separator;
+}
+
+/// Returns the separator set with [`set_location_separator`], which is always
+/// [`DEFAULT_LOCATION_SEPARATOR`] without the `std` feature.
+pub fn location_separator() -> &'static str {
+    #[cfg(feature = "std")]
+    return *LOCATION_SEPARATOR
+        .read()
+        .unwrap_or_else(|err| err.into_inner());
+    #[cfg(not(feature = "std"))]
+    DEFAULT_LOCATION_SEPARATOR
+}
+
+/// Hides frames located in files whose path starts with any of `prefixes` from rendered
+/// traces. Defaults to no prefixes.
+///
+/// A prefix also matches right after a path separator, so relative prefixes such as
+/// `.cargo/registry` match the absolute paths of dependencies.
+///
+/// Frames of third-party code, e.g. under `.cargo/registry` or `/rustc/`, can clutter
+/// traces. Hidden frames are left out of the generated [`Debug`] output,
+/// [`StackDisplay`](crate::StackDisplay) and
+/// [`render_with`](crate::VirtualStackTrace::render_with), with each run of consecutive
+/// hidden frames replaced by a single `(N frames hidden)` line. The remaining frames keep
+/// their numbers, and [`virtual_stack`](crate::VirtualStackTrace::virtual_stack) still
+/// returns every frame. Prefixes are compared with the file as captured, before
+/// [`set_path_style`] shortens it.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Failed to read config"))]
+///     ReadConfig { source: std::io::Error },
+/// }
+///
+/// let io = std::io::Error::other("disk on fire");
+/// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+///
+/// snafu_virtstack::set_skip_files([file!()]);
+/// let trace = error.stack_display().to_string();
+/// snafu_virtstack::set_skip_files(Vec::<String>::new());
+///
+/// assert_eq!(trace, "  (2 frames hidden)\n");
+/// assert_eq!(error.virtual_stack().len(), 2);
+/// ```
+#[cfg(feature = "std")]
+pub fn set_skip_files(prefixes: impl IntoIterator<Item = impl Into<String>>) {
+    let prefixes = prefixes.into_iter().map(Into::into).collect();
+    *SKIP_FILES.write().unwrap_or_else(|err| err.into_inner()) = prefixes;
+}
+
+/// Returns the prefixes set with [`set_skip_files`].
+#[cfg(feature = "std")]
+pub fn skip_files() -> Vec<String> {
+    SKIP_FILES
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+/// Registers a function called with the frames of every stack that gets materialized.
+///
+/// The observer runs whenever a generated [`virtual_stack`] is collected or a generated
+/// [`Debug`] implementation renders a trace, which makes it a single place to count errors,
+/// e.g. per [`variant`] of the outermost frame. Only one observer can be registered per
+/// process; this returns `false` and drops `observer` if one already is. While no observer
+/// is registered, stacks are rendered without collecting them first.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static OBSERVED: AtomicUsize = AtomicUsize::new(0);
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Something went wrong"))]
+///     SomethingWrong,
+/// }
+///
+/// snafu_virtstack::set_error_observer(|frames| {
+///     assert_eq!(frames[0].variant, Some("SomethingWrong"));
+///     OBSERVED.fetch_add(1, Ordering::Relaxed);
+/// });
+///
+/// let error = SomethingWrongSnafu.build();
+/// error.virtual_stack();
+/// format!("{error:?}");
+/// assert_eq!(OBSERVED.load(Ordering::Relaxed), 2);
+/// ```
+///
+/// [`virtual_stack`]: crate::VirtualStackTrace::virtual_stack
+/// [`variant`]: StackFrame::variant
+#[cfg(feature = "std")]
+pub fn set_error_observer(observer: impl Fn(&[StackFrame]) + Send + Sync + 'static) -> bool {
+    OBSERVER.set(Box::new(observer)).is_ok()
+}
+
+/// Returns whether an observer was registered with [`set_error_observer`].
+pub(crate) fn has_observer() -> bool {
+    #[cfg(feature = "std")]
+    {
+        OBSERVER.get().is_some()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        false
+    }
+}
+
+/// Hands `frames` to the registered observer, if any.
+pub(crate) fn observe(frames: &[StackFrame]) {
+    #[cfg(feature = "std")]
+    if let Some(observer) = OBSERVER.get() {
+        observer(frames);
+    }
+    #[cfg(not(feature = "std"))]
+    let _ = frames;
+}
+
+#[cfg(feature = "std")]
+fn env_disabled() -> bool {
+    *ENV_DISABLED.get_or_init(|| {
+        std::env::var_os(DISABLE_ENV_VAR).is_some_and(|value| !value.is_empty() && value != "0")
+    })
+}
+
+#[cfg(not(feature = "std"))]
+fn env_disabled() -> bool {
+    false
+}
@@ -0,0 +1,83 @@
+//! Surfacing virtual stack traces in `miette` diagnostics.
+
+use crate::{StackDisplay, VirtualStackTrace};
+
+/// Wrapper implementing [`miette::Diagnostic`] for an error, with its virtual stack trace as
+/// the diagnostic's `help`.
+///
+/// The wrapper displays as the error itself and keeps its `source()` chain, so `miette`
+/// renders the usual message and causes, followed by the numbered frames and their
+/// locations. Convert it into a [`miette::Report`] with `?` to adopt the crate in code that
+/// already returns [`miette::Result`].
+///
+/// # Example
+///
+/// ```rust
+/// use miette::Diagnostic;
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, StackDiagnostic};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Something went wrong"))]
+///     SomethingWrong,
+/// }
+///
+/// fn run() -> miette::Result<()> {
+///     Err(StackDiagnostic::new(SomethingWrongSnafu.build()))?
+/// }
+///
+/// let diagnostic = StackDiagnostic::new(SomethingWrongSnafu.build());
+/// let help = diagnostic.help().unwrap().to_string();
+/// assert!(help.starts_with("  0: [SomethingWrong] Something went wrong at "));
+/// assert!(run().is_err());
+/// ```
+pub struct StackDiagnostic<E> {
+    error: E,
+}
+
+impl<E> StackDiagnostic<E> {
+    /// Wraps `error`.
+    pub fn new(error: E) -> Self {
+        Self { error }
+    }
+
+    /// Returns the wrapped error.
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+}
+
+impl<E> From<E> for StackDiagnostic<E> {
+    fn from(error: E) -> Self {
+        Self::new(error)
+    }
+}
+
+impl<E: core::fmt::Debug> core::fmt::Debug for StackDiagnostic<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for StackDiagnostic<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for StackDiagnostic<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl<E> miette::Diagnostic for StackDiagnostic<E>
+where
+    E: VirtualStackTrace + core::error::Error,
+{
+    fn help<'a>(&'a self) -> Option<Box<dyn core::fmt::Display + 'a>> {
+        Some(Box::new(StackDisplay(&self.error)))
+    }
+}
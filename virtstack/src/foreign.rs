@@ -0,0 +1,55 @@
+//! Virtual stacks of standard library errors.
+
+use crate::{DEFAULT_MAX_DEPTH, Location, Stack, StackIter, VirtualStackTrace};
+
+/// Implements [`VirtualStackTrace`] for errors that don't capture a location.
+///
+/// Their stack is the error itself followed by its `source()` chain, like the part of a
+/// trace contributed by a foreign source, with every frame attributed to the caller. As
+/// the source of a `#[stack_trace_debug]` error they're still attributed to the place
+/// where they were wrapped.
+macro_rules! impl_std_errors {
+    ($($(#[$attr:meta])* $ty:ty),* $(,)?) => {$(
+        $(#[$attr])*
+        impl VirtualStackTrace for $ty {
+            #[track_caller]
+            fn virtual_stack(&self) -> Stack {
+                self.virtual_stack_iter().collect()
+            }
+
+            #[track_caller]
+            fn virtual_stack_iter(&self) -> StackIter<'_> {
+                StackIter::new(self, Location::caller(), DEFAULT_MAX_DEPTH)
+            }
+
+            fn as_error(&self) -> Option<&(dyn core::error::Error + 'static)> {
+                Some(self)
+            }
+        }
+
+        $(#[$attr])*
+        impl crate::__private::StdSource for $ty {}
+    )*};
+}
+
+impl_std_errors!(
+    core::array::TryFromSliceError,
+    core::char::CharTryFromError,
+    core::char::ParseCharError,
+    core::fmt::Error,
+    core::num::ParseFloatError,
+    core::num::ParseIntError,
+    core::num::TryFromIntError,
+    core::str::ParseBoolError,
+    core::str::Utf8Error,
+    alloc::string::FromUtf8Error,
+    alloc::string::FromUtf16Error,
+    #[cfg(feature = "std")]
+    std::env::VarError,
+    #[cfg(feature = "std")]
+    std::io::Error,
+    #[cfg(feature = "std")]
+    std::net::AddrParseError,
+    #[cfg(feature = "std")]
+    std::time::SystemTimeError,
+);
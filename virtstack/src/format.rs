@@ -0,0 +1,411 @@
+//! Pluggable layouts for rendering individual [`StackFrame`]s.
+
+use crate::{StackFrame, VirtualStackTrace};
+
+/// Renders a single [`StackFrame`].
+///
+/// Implement this trait to change how frames are laid out, e.g. to put the location
+/// first for a log parser. [`CompactFormatter`] reproduces the [`Display`] output of
+/// [`StackFrame`] and [`VerboseFormatter`] spreads each frame over several lines.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, FrameFormatter, StackFrame, VirtualStackTrace};
+/// use std::fmt;
+///
+/// struct LocationFirst;
+///
+/// impl FrameFormatter for LocationFirst {
+///     fn format_frame(&self, f: &mut fmt::Formatter<'_>, frame: &StackFrame) -> fmt::Result {
+///         write!(f, "{} | {}", frame.location, frame.message)
+///     }
+/// }
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Something went wrong"))]
+///     SomethingWrong,
+/// }
+///
+/// let rendered = SomethingWrongSnafu.build().render_with(&LocationFirst);
+/// assert!(rendered.ends_with(" | Something went wrong\n"));
+/// ```
+///
+/// [`Display`]: std::fmt::Display
+pub trait FrameFormatter {
+    /// Writes `frame` to `f`, without a trailing newline.
+    fn format_frame(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+        frame: &StackFrame,
+    ) -> core::fmt::Result;
+}
+
+/// Single-line `[{variant}] {message} at {file}:{line}:{column}` layout.
+///
+/// This is the default layout, used by the [`Display`](std::fmt::Display) implementation
+/// of [`StackFrame`].
+///
+/// ```text
+/// [FileRead] Failed to read configuration file at src/config.rs:42:15
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactFormatter;
+
+impl FrameFormatter for CompactFormatter {
+    fn format_frame(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+        frame: &StackFrame,
+    ) -> core::fmt::Result {
+        write_compact(f, frame.variant, &frame.message, frame.location)
+    }
+}
+
+/// Multi-line layout putting the message and each piece of frame information on
+/// its own line, including the module of the error type when known and, with the `thread`
+/// and `timestamp` features, the thread and time it was created on. Any
+/// [metadata](StackFrame::metadata) follows as `key=value` pairs.
+///
+/// ```text
+/// [FileRead] Failed to read configuration file
+///       at src/config.rs:42:15
+///       in my_app::config
+///       on thread main
+///       captured 2024-05-17T09:41:07.123Z
+///       with request_id=f3a9 attempt=2
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VerboseFormatter;
+
+impl FrameFormatter for VerboseFormatter {
+    fn format_frame(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+        frame: &StackFrame,
+    ) -> core::fmt::Result {
+        write_variant(f, frame.variant)?;
+        let message = Truncated::global(&frame.message);
+        write!(f, "{message}\n      at {}", frame.location)?;
+        if let Some(module) = frame.module {
+            write!(f, "\n      in {module}")?;
+        }
+        #[cfg(feature = "thread")]
+        if let Some(thread) = &frame.thread {
+            write!(f, "\n      on thread {thread}")?;
+        }
+        #[cfg(feature = "timestamp")]
+        if let Some(time) = frame.timestamp {
+            write!(f, "\n      captured {}", crate::timestamp::Rfc3339(time))?;
+        }
+        if let Some(((key, value), rest)) = frame.metadata.split_first() {
+            write!(f, "\n      with {key}={value}")?;
+            for (key, value) in rest {
+                write!(f, " {key}={value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Displays a logfmt value, quoted and escaped if it isn't a plain word.
+pub(crate) struct LogfmtValue<'a>(pub(crate) &'a str);
+
+impl core::fmt::Display for LogfmtValue<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use core::fmt::Write;
+
+        let plain = !self.0.is_empty()
+            && !self
+                .0
+                .chars()
+                .any(|c| c == ' ' || c == '=' || c == '"' || c == '\\' || c.is_control());
+        if plain {
+            return f.write_str(self.0);
+        }
+
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_control() => write!(f, "\\u{{{:04x}}}", u32::from(c))?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+/// Displays a message cut off after `max_len` characters, ending with `…` if it was.
+pub(crate) struct Truncated<'a> {
+    pub(crate) message: &'a str,
+    pub(crate) max_len: Option<usize>,
+}
+
+impl<'a> Truncated<'a> {
+    /// Truncates `message` according to [`max_message_len`](crate::max_message_len).
+    pub(crate) fn global(message: &'a str) -> Self {
+        Self {
+            message,
+            max_len: crate::max_message_len(),
+        }
+    }
+}
+
+impl core::fmt::Display for Truncated<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Counting characters rather than bytes always cuts on a char boundary
+        match self
+            .max_len
+            .and_then(|max_len| self.message.char_indices().nth(max_len))
+        {
+            Some((end, _)) => write!(f, "{}…", &self.message[..end]),
+            None => f.write_str(self.message),
+        }
+    }
+}
+
+/// Displays the error label of the generated `Debug` output, colored after the level of
+/// the error when the `color` feature is enabled.
+pub(crate) struct Label<'a> {
+    label: &'a str,
+    #[cfg(feature = "color")]
+    level: crate::Level,
+}
+
+impl<'a> Label<'a> {
+    pub(crate) fn new(options: &crate::__private::DebugOptions<'a>) -> Self {
+        Self {
+            label: options.error_label,
+            #[cfg(feature = "color")]
+            level: options.level,
+        }
+    }
+}
+
+impl core::fmt::Display for Label<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        #[cfg(feature = "color")]
+        if crate::color::enabled() {
+            use crate::color::{FATAL, RESET, WARN};
+
+            match self.level {
+                crate::Level::Warn => return write!(f, "{WARN}{}{RESET}", self.label),
+                crate::Level::Fatal => return write!(f, "{FATAL}{}{RESET}", self.label),
+                _ => {}
+            }
+        }
+        f.write_str(self.label)
+    }
+}
+
+/// Leaves frames of the files set with [`set_skip_files`](crate::set_skip_files) out of a
+/// rendered trace, summarizing each run of them in a single line.
+pub(crate) struct HiddenFrames {
+    #[cfg(feature = "std")]
+    prefixes: alloc::vec::Vec<alloc::string::String>,
+    hidden: usize,
+}
+
+impl HiddenFrames {
+    /// Snapshots the prefixes to hide, so they stay the same for the whole trace.
+    pub(crate) fn new() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            prefixes: crate::skip_files(),
+            hidden: 0,
+        }
+    }
+
+    /// Returns true if `frame` is hidden, in which case it must not be written.
+    pub(crate) fn hide(&mut self, frame: &StackFrame) -> bool {
+        #[cfg(feature = "std")]
+        let hidden = {
+            let file = frame.location.file();
+            self.prefixes.iter().any(|prefix| skips(file, prefix))
+        };
+        #[cfg(not(feature = "std"))]
+        let hidden = {
+            let _ = frame;
+            false
+        };
+        self.hidden += usize::from(hidden);
+        hidden
+    }
+
+    /// Writes the summary of the frames hidden since the last visible one, if any.
+    ///
+    /// Call before each visible frame and once after the last frame.
+    pub(crate) fn flush(&mut self, f: &mut dyn core::fmt::Write) -> core::fmt::Result {
+        match core::mem::take(&mut self.hidden) {
+            0 => Ok(()),
+            1 => writeln!(f, "  (1 frame hidden)"),
+            hidden => writeln!(f, "  ({hidden} frames hidden)"),
+        }
+    }
+}
+
+/// Writes the line summarizing the `more` frames left out past the
+/// [`max_frames`](crate::max_frames) limit.
+pub(crate) fn write_more_frames(f: &mut dyn core::fmt::Write, more: usize) -> core::fmt::Result {
+    match more {
+        1 => writeln!(f, "  ... (1 more frame)"),
+        more => writeln!(f, "  ... ({more} more frames)"),
+    }
+}
+
+/// Returns true if `file` starts with `prefix`, or contains it right after a path separator.
+#[cfg(feature = "std")]
+fn skips(file: &str, prefix: &str) -> bool {
+    file.match_indices(prefix)
+        .any(|(start, _)| start == 0 || file[..start].ends_with(['/', '\\']))
+}
+
+/// Writes the `[Variant] ` prefix of frames that know their variant.
+fn write_variant(f: &mut core::fmt::Formatter<'_>, variant: Option<&str>) -> core::fmt::Result {
+    match variant {
+        Some(variant) => write!(f, "[{variant}] "),
+        None => Ok(()),
+    }
+}
+
+/// Writes a frame in the layout of [`CompactFormatter`], shared with the `Display` of
+/// [`OwnedFrame`](crate::OwnedFrame) so both render identically.
+pub(crate) fn write_compact(
+    f: &mut core::fmt::Formatter<'_>,
+    variant: Option<&str>,
+    message: &str,
+    location: impl core::fmt::Display,
+) -> core::fmt::Result {
+    write_variant(f, variant)?;
+    write!(
+        f,
+        "{}{}{}",
+        Truncated::global(message),
+        crate::location_separator(),
+        location
+    )
+}
+
+/// How much of a source file path is shown when a [`Location`](crate::Location) is displayed.
+///
+/// Select a style for the whole process with [`set_path_style`](crate::set_path_style).
+/// Only the rendered text is affected; [`Location::file`](crate::Location::file) always
+/// returns the path as captured by the compiler.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu_virtstack::PathStyle;
+///
+/// let file = "/ci/build/app/config/src/config.rs";
+/// assert_eq!(PathStyle::Full.trim(file), file);
+/// assert_eq!(PathStyle::Relative.trim(file), "src/config.rs");
+/// assert_eq!(PathStyle::FileName.trim(file), "config.rs");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum PathStyle {
+    /// The path exactly as captured, e.g. `/ci/build/app/config/src/config.rs`.
+    #[default]
+    Full,
+    /// The path relative to its crate root, e.g. `src/config.rs`.
+    ///
+    /// Everything before the last `src`, `tests`, `examples` or `benches` directory is
+    /// dropped. Paths without such a directory are shown in full.
+    Relative,
+    /// Just the file name, e.g. `config.rs`.
+    FileName,
+}
+
+impl PathStyle {
+    const CRATE_DIRS: [&'static str; 4] = ["src", "tests", "examples", "benches"];
+
+    /// Returns the part of `file` shown in this style.
+    pub fn trim(self, file: &str) -> &str {
+        let is_separator = |c: char| c == '/' || c == '\\';
+        match self {
+            PathStyle::Full => file,
+            PathStyle::Relative => {
+                let mut start = 0;
+                let mut relative = None;
+                for component in file.split(is_separator) {
+                    if Self::CRATE_DIRS.contains(&component) {
+                        relative = Some(start);
+                    }
+                    start += component.len() + 1;
+                }
+                relative.map_or(file, |start| &file[start..])
+            }
+            PathStyle::FileName => file.rsplit(is_separator).next().unwrap_or(file),
+        }
+    }
+}
+
+/// Renders the numbered frames of a virtual stack, without the `Error:` header of the
+/// generated [`Debug`](std::fmt::Debug) output.
+///
+/// Each frame is written on its own line exactly as in the `Debug` output, including the
+/// tree layout for `{:#}` and colors when the `color` feature is enabled. Use it to embed
+/// the trace in your own reports.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, StackDisplay};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Something went wrong"))]
+///     SomethingWrong,
+/// }
+///
+/// let error = SomethingWrongSnafu.build();
+/// let report = format!("request failed:\n{}", StackDisplay(&error));
+/// assert!(report.starts_with("request failed:\n  0: [SomethingWrong] Something went wrong at "));
+/// ```
+#[derive(Clone, Copy)]
+pub struct StackDisplay<'a>(pub &'a dyn VirtualStackTrace);
+
+impl core::fmt::Display for StackDisplay<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let options = crate::__private::DebugOptions::global();
+        if options.root_first {
+            let mut frames = self.0.virtual_stack();
+            if options.clean_messages {
+                crate::iter::clean_stack(&mut frames);
+            }
+            let frames = frames.iter().rev();
+            return crate::__private::write_visible_frames(f, frames, &options);
+        }
+
+        let frames = self.0.virtual_stack_iter();
+        let frames = if options.clean_messages {
+            frames.clean_messages()
+        } else {
+            frames
+        };
+        crate::__private::write_visible_frames(f, frames, &options)
+    }
+}
+
+/// Adapts a [`FrameFormatter`] to [`Display`](std::fmt::Display) for a single frame.
+pub(crate) struct Formatted<'a> {
+    pub(crate) formatter: &'a dyn FrameFormatter,
+    pub(crate) frame: &'a StackFrame,
+}
+
+impl core::fmt::Display for Formatted<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.formatter.format_frame(f, self.frame)
+    }
+}
@@ -0,0 +1,38 @@
+//! Conversion of errors into gRPC statuses for `tonic` services.
+
+use crate::StatusCode;
+
+/// Binary metadata key under which
+/// [`ErrorExt::to_tonic_status_with_stack`](crate::ErrorExt::to_tonic_status_with_stack)
+/// attaches the rendered virtual stack.
+pub const TONIC_STACK_METADATA_KEY: &str = "x-virtual-stack-bin";
+
+impl From<StatusCode> for tonic::Code {
+    /// Maps each status code onto the closest gRPC code.
+    fn from(code: StatusCode) -> Self {
+        match code {
+            StatusCode::Internal => tonic::Code::Internal,
+            StatusCode::Unknown => tonic::Code::Unknown,
+            StatusCode::Unsupported => tonic::Code::Unimplemented,
+            StatusCode::InvalidArguments => tonic::Code::InvalidArgument,
+            StatusCode::NotFound => tonic::Code::NotFound,
+            StatusCode::AlreadyExists => tonic::Code::AlreadyExists,
+            StatusCode::Unauthorized => tonic::Code::Unauthenticated,
+            StatusCode::PermissionDenied => tonic::Code::PermissionDenied,
+            StatusCode::Cancelled => tonic::Code::Cancelled,
+            StatusCode::Timeout => tonic::Code::DeadlineExceeded,
+            StatusCode::RateLimited => tonic::Code::ResourceExhausted,
+            StatusCode::Unavailable => tonic::Code::Unavailable,
+        }
+    }
+}
+
+/// Builds a status whose metadata carries `stack` under [`TONIC_STACK_METADATA_KEY`].
+pub(crate) fn status_with_stack(code: StatusCode, message: String, stack: &str) -> tonic::Status {
+    let mut metadata = tonic::metadata::MetadataMap::new();
+    metadata.insert_bin(
+        TONIC_STACK_METADATA_KEY,
+        tonic::metadata::MetadataValue::from_bytes(stack.as_bytes()),
+    );
+    tonic::Status::with_metadata(code.into(), message, metadata)
+}
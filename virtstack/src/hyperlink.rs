@@ -0,0 +1,63 @@
+//! OSC 8 terminal hyperlinks for rendered locations, enabled by the `hyperlinks` feature.
+
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::Location;
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Returns whether locations should be hyperlinked, decided once per process.
+///
+/// `FORCE_HYPERLINK` set to anything but `0` enables links even when piped, and `0`
+/// disables them. Otherwise links are only emitted when stderr is a terminal.
+pub(crate) fn enabled() -> bool {
+    *ENABLED.get_or_init(|| match std::env::var_os("FORCE_HYPERLINK") {
+        Some(value) if !value.is_empty() => value != "0",
+        _ => std::io::stderr().is_terminal(),
+    })
+}
+
+/// Displays a location, wrapped in an OSC 8 hyperlink to its file when enabled.
+pub(crate) struct Hyperlink<'a>(pub(crate) &'a Location);
+
+impl core::fmt::Display for Hyperlink<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if !enabled() {
+            return self.0.fmt(f);
+        }
+
+        let absolute = absolute(Path::new(self.0.file()));
+        let path = absolute
+            .to_string_lossy()
+            .replace('\\', "/")
+            .replace(' ', "%20");
+        let slash = if path.starts_with('/') { "" } else { "/" };
+
+        write!(
+            f,
+            "\x1b]8;;file://{slash}{path}\x1b\\{}\x1b]8;;\x1b\\",
+            self.0
+        )
+    }
+}
+
+/// Best-effort absolute path of a file captured by `file!()`.
+///
+/// Relative paths are relative to the workspace root the crate was compiled in, which is
+/// usually the working directory or one of its ancestors, e.g. when running the tests of a
+/// workspace member.
+fn absolute(file: &Path) -> PathBuf {
+    let Ok(dir) = std::env::current_dir() else {
+        return file.to_path_buf();
+    };
+    if file.is_absolute() {
+        return file.to_path_buf();
+    }
+
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(file))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| dir.join(file))
+}
@@ -0,0 +1,724 @@
+//! Lazy iteration over virtual stack frames.
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Location, StackFrame, TRUNCATED_MESSAGE};
+
+/// Iterator over the frames of a virtual stack, returned by
+/// [`VirtualStackTrace::virtual_stack_iter`](crate::VirtualStackTrace::virtual_stack_iter).
+///
+/// Frames are built on demand, so each error message is only formatted when its frame
+/// is reached. Taking just the first frame never touches the rest of the source chain.
+pub struct StackIter<'a> {
+    state: State<'a>,
+    remaining: usize,
+}
+
+enum State<'a> {
+    Chain {
+        chain: Chain<'a, 'a>,
+        location: Location,
+        root: Root,
+    },
+    Nested {
+        root: Option<&'a (dyn core::error::Error + 'a)>,
+        location: Location,
+        details: Root,
+        nested: Box<StackIter<'a>>,
+    },
+    Frames(alloc::vec::IntoIter<StackFrame>),
+    Cached(core::slice::Iter<'a, StackFrame>),
+    Dedup {
+        inner: Box<StackIter<'a>>,
+        pending: Option<StackFrame>,
+    },
+    Clean {
+        inner: Box<StackIter<'a>>,
+        pending: Option<StackFrame>,
+    },
+    OsErrors {
+        inner: Box<StackIter<'a>>,
+        parent: Option<Cow<'static, str>>,
+    },
+    Done,
+}
+
+impl<'a> StackIter<'a> {
+    /// Walks `error` and its `source()` chain, yielding at most `max_depth` frames.
+    ///
+    /// Sources don't carry a location of their own, so every frame is attributed to
+    /// `location`. If the chain is longer than `max_depth` the iterator ends with a
+    /// [`TRUNCATED_MESSAGE`] frame, which guarantees termination for cyclic chains.
+    pub fn new(
+        error: &'a (dyn core::error::Error + 'a),
+        location: Location,
+        max_depth: usize,
+    ) -> Self {
+        Self {
+            state: State::Chain {
+                chain: Chain { next: Some(error) },
+                location,
+                root: Root::default(),
+            },
+            remaining: max_depth,
+        }
+    }
+
+    /// Yields the frame of `error` at `location`, followed by the frames of `nested`.
+    ///
+    /// This is used when the source of `error` has a virtual stack of its own, so that its
+    /// frames keep their real locations instead of being flattened into the parent's.
+    /// The combined stack is limited to `max_depth` frames like [`StackIter::new`].
+    pub fn with_nested(
+        error: &'a (dyn core::error::Error + 'a),
+        location: Location,
+        nested: StackIter<'a>,
+        max_depth: usize,
+    ) -> Self {
+        Self {
+            state: State::Nested {
+                root: Some(error),
+                location,
+                details: Root::default(),
+                nested: Box::new(nested),
+            },
+            remaining: max_depth,
+        }
+    }
+
+    /// Yields only the frame of `error` at `location`, without visiting its sources.
+    pub fn single(error: &'a (dyn core::error::Error + 'a), location: Location) -> Self {
+        let done = Self {
+            state: State::Done,
+            remaining: 0,
+        };
+        Self::with_nested(error, location, done, 1)
+    }
+
+    /// Iterates over already materialized frames.
+    pub fn from_frames(frames: impl IntoIterator<Item = StackFrame>) -> Self {
+        // Collecting a `Vec` back into a `Vec` reuses its allocation
+        let frames: Vec<StackFrame> = frames.into_iter().collect();
+        Self {
+            state: State::Frames(frames.into_iter()),
+            remaining: usize::MAX,
+        }
+    }
+
+    /// Attributes the first frame, the one of the error itself, to the enum variant
+    /// named `variant`.
+    ///
+    /// This only has an effect on iterators created with [`StackIter::new`] or
+    /// [`StackIter::with_nested`] before any frame has been yielded.
+    pub fn with_variant(mut self, name: &'static str) -> Self {
+        if let Some(root) = self.root_mut() {
+            root.variant = Some(name);
+        }
+        self
+    }
+
+    /// Uses `message` for the first frame instead of formatting the error, for errors
+    /// whose `Display` output is known to be constant.
+    ///
+    /// The frame then borrows `message` rather than allocating. Like
+    /// [`with_variant`](StackIter::with_variant), this only has an effect on iterators
+    /// created with [`StackIter::new`] or [`StackIter::with_nested`] before any frame has
+    /// been yielded.
+    pub fn with_static_message(mut self, message: &'static str) -> Self {
+        if let Some(root) = self.root_mut() {
+            root.message = Some(message);
+        }
+        self
+    }
+
+    /// Attributes the first frame to an error type defined in `module`.
+    ///
+    /// Like [`with_variant`](StackIter::with_variant), this only has an effect on iterators
+    /// created with [`StackIter::new`] or [`StackIter::with_nested`] before any frame has
+    /// been yielded.
+    pub fn with_module(mut self, module: &'static str) -> Self {
+        if let Some(root) = self.root_mut() {
+            root.module = Some(module);
+        }
+        self
+    }
+
+    /// Attributes the first frame to an error created on `thread`.
+    ///
+    /// Like [`with_variant`](StackIter::with_variant), this only has an effect on iterators
+    /// created with [`StackIter::new`] or [`StackIter::with_nested`] before any frame has
+    /// been yielded.
+    #[cfg(feature = "thread")]
+    pub fn with_thread(mut self, thread: &crate::CapturedThread) -> Self {
+        if let Some(root) = self.root_mut() {
+            root.thread = Some(thread.clone());
+        }
+        self
+    }
+
+    /// Attributes the first frame to an error created at `time`.
+    ///
+    /// Like [`with_variant`](StackIter::with_variant), this only has an effect on iterators
+    /// created with [`StackIter::new`] or [`StackIter::with_nested`] before any frame has
+    /// been yielded.
+    #[cfg(feature = "timestamp")]
+    pub fn with_timestamp(mut self, time: &crate::CapturedTime) -> Self {
+        if let Some(root) = self.root_mut() {
+            root.timestamp = Some(time.time());
+        }
+        self
+    }
+
+    /// Attaches a `key=value` pair to the [metadata](StackFrame::metadata) of the first frame.
+    ///
+    /// Like [`with_variant`](StackIter::with_variant), this only has an effect on iterators
+    /// created with [`StackIter::new`] or [`StackIter::with_nested`] before any frame has
+    /// been yielded.
+    pub fn with_meta(mut self, key: impl Into<String>, value: impl core::fmt::Display) -> Self {
+        if let Some(root) = self.root_mut() {
+            root.metadata.push((key.into(), value.to_string()));
+        }
+        self
+    }
+
+    fn root_mut(&mut self) -> Option<&mut Root> {
+        match &mut self.state {
+            State::Chain { root, .. } | State::Nested { details: root, .. } => Some(root),
+            _ => None,
+        }
+    }
+
+    /// Iterates over copies of frames stored elsewhere, e.g. in a
+    /// [`StackCache`](crate::StackCache).
+    pub fn from_cached(frames: &'a [StackFrame]) -> Self {
+        Self {
+            state: State::Cached(frames.iter()),
+            remaining: usize::MAX,
+        }
+    }
+
+    /// Collapses consecutive frames whose message repeats the end of the previous one.
+    ///
+    /// SNAFU errors often embed their source in their own message (`"...: {source}"`),
+    /// which shows the same text twice in adjacent frames. A frame whose message is equal
+    /// to, or a suffix of, the preceding frame's message is merged into it: the merged frame
+    /// keeps the fuller outer message and the inner location, which is at least as precise
+    /// since foreign sources inherit the location of their wrapper.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu_virtstack::{Location, StackFrame, StackIter};
+    ///
+    /// let frames = vec![
+    ///     StackFrame::new(Location::new("src/lib.rs", 1, 1), "Failed to read: missing".to_string()),
+    ///     StackFrame::new(Location::new("src/io.rs", 2, 2), "missing".to_string()),
+    /// ];
+    ///
+    /// let stack: Vec<_> = StackIter::from_frames(frames).dedup().collect();
+    /// assert_eq!(stack.len(), 1);
+    /// assert_eq!(stack[0].message, "Failed to read: missing");
+    /// assert_eq!(stack[0].location.file(), "src/io.rs");
+    /// ```
+    pub fn dedup(self) -> Self {
+        Self {
+            state: State::Dedup {
+                inner: Box::new(self),
+                pending: None,
+            },
+            remaining: usize::MAX,
+        }
+    }
+
+    /// Strips the message of each frame from the end of the preceding frame's message.
+    ///
+    /// Where [`dedup`](StackIter::dedup) merges such frames, this keeps them all and leaves
+    /// each with only the text its own layer adds: a message ending with `": "` followed by
+    /// the next frame's message loses that suffix. Messages that don't end this way are kept
+    /// as they are.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu_virtstack::{Location, StackFrame, StackIter};
+    ///
+    /// let location = Location::new("src/lib.rs", 1, 1);
+    /// let frames = vec![
+    ///     StackFrame::new(location, "Failed to load: Failed to read: missing".to_string()),
+    ///     StackFrame::new(location, "Failed to read: missing".to_string()),
+    ///     StackFrame::new(location, "missing".to_string()),
+    /// ];
+    ///
+    /// let messages: Vec<_> = StackIter::from_frames(frames)
+    ///     .clean_messages()
+    ///     .map(|frame| frame.message)
+    ///     .collect();
+    /// assert_eq!(messages, ["Failed to load", "Failed to read", "missing"]);
+    /// ```
+    pub fn clean_messages(self) -> Self {
+        Self {
+            state: State::Clean {
+                inner: Box::new(self),
+                pending: None,
+            },
+            remaining: usize::MAX,
+        }
+    }
+
+    /// Strips a trailing `(os error N)` from each frame whose preceding frame already
+    /// mentions it.
+    ///
+    /// The `Display` output of an [`std::io::Error`] created from an OS error ends with its
+    /// code, such as `No such file or directory (os error 2)`. Wrappers embedding their
+    /// source in their message repeat that code, so it shows up at several layers. A frame
+    /// ending with ` (os error N)` loses that suffix when the preceding frame's message
+    /// contains the same `(os error N)`, the outermost mention being kept. Unlike
+    /// [`dedup`](StackIter::dedup), frames are never merged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu_virtstack::{Location, StackFrame, StackIter};
+    ///
+    /// let location = Location::new("src/lib.rs", 1, 1);
+    /// let frames = vec![
+    ///     StackFrame::new(location, "Failed to open config.toml: Not found (os error 2)"),
+    ///     StackFrame::new(location, "Not found (os error 2)"),
+    /// ];
+    ///
+    /// let messages: Vec<_> = StackIter::from_frames(frames)
+    ///     .dedup_os_errors()
+    ///     .map(|frame| frame.message)
+    ///     .collect();
+    /// assert_eq!(messages, ["Failed to open config.toml: Not found (os error 2)", "Not found"]);
+    /// ```
+    pub fn dedup_os_errors(self) -> Self {
+        Self {
+            state: State::OsErrors {
+                inner: Box::new(self),
+                parent: None,
+            },
+            remaining: usize::MAX,
+        }
+    }
+
+    fn next_untruncated(&mut self) -> Option<StackFrame> {
+        match &mut self.state {
+            State::Chain {
+                chain,
+                location,
+                root,
+            } => {
+                let error = chain.next()?;
+                Some(core::mem::take(root).frame(error, *location))
+            }
+            State::Nested {
+                root,
+                location,
+                details,
+                nested,
+            } => match root.take() {
+                Some(error) => Some(core::mem::take(details).frame(error, *location)),
+                None => nested.next(),
+            },
+            State::Frames(frames) => frames.next(),
+            State::Cached(frames) => frames.next().cloned(),
+            State::Dedup { inner, pending } => {
+                let mut current = match pending.take() {
+                    Some(frame) => frame,
+                    None => inner.next()?,
+                };
+
+                for next in inner.by_ref() {
+                    if !next.message.is_empty() && current.message.ends_with(&*next.message) {
+                        current.location = next.location;
+                    } else {
+                        *pending = Some(next);
+                        break;
+                    }
+                }
+                Some(current)
+            }
+            State::Clean { inner, pending } => {
+                let mut current = match pending.take() {
+                    Some(frame) => frame,
+                    None => inner.next()?,
+                };
+                *pending = inner.next();
+                if let Some(next) = pending {
+                    strip_source_suffix(&mut current.message, &next.message);
+                }
+                Some(current)
+            }
+            State::OsErrors { inner, parent } => {
+                let mut frame = inner.next()?;
+                // The next frame is compared against the message as it was
+                let message = frame
+                    .message
+                    .contains(OS_ERROR)
+                    .then(|| frame.message.clone());
+                if let Some(parent) = parent.as_deref() {
+                    strip_os_error(&mut frame.message, parent);
+                }
+                *parent = message;
+                Some(frame)
+            }
+            State::Done => None,
+        }
+    }
+
+    /// Advances past the next frame like [`Iterator::next`] without building it, returning
+    /// whether there was one.
+    fn skip_frame(&mut self) -> bool {
+        if self.remaining == 0 {
+            // Mirrors `next`, which only reports truncation for states with a location
+            let truncates = match &self.state {
+                State::Chain { .. } | State::Nested { .. } => true,
+                State::Frames(frames) => !frames.as_slice().is_empty(),
+                State::Cached(frames) => !frames.as_slice().is_empty(),
+                State::Dedup { .. }
+                | State::Clean { .. }
+                | State::OsErrors { .. }
+                | State::Done => false,
+            };
+            return truncates && self.finish();
+        }
+
+        let skipped = self.skip_untruncated();
+        if skipped {
+            self.remaining -= 1;
+        } else {
+            self.state = State::Done;
+        }
+        skipped
+    }
+
+    fn skip_untruncated(&mut self) -> bool {
+        match &mut self.state {
+            State::Chain { chain, root, .. } => match chain.next() {
+                Some(_) => {
+                    *root = Root::default();
+                    true
+                }
+                None => false,
+            },
+            State::Nested {
+                root,
+                details,
+                nested,
+                ..
+            } => match root.take() {
+                Some(_) => {
+                    *details = Root::default();
+                    true
+                }
+                None => nested.skip_frame(),
+            },
+            State::Frames(frames) => frames.next().is_some(),
+            State::Cached(frames) => frames.next().is_some(),
+            // Merging frames requires comparing their messages
+            // Stripping depends on the message of the previous frame
+            State::Dedup { .. } | State::OsErrors { .. } => self.next_untruncated().is_some(),
+            State::Clean { inner, pending } => pending.take().is_some() || inner.skip_frame(),
+            State::Done => false,
+        }
+    }
+
+    /// Ends the iteration, returning whether any frames were left over.
+    fn finish(&mut self) -> bool {
+        match core::mem::replace(&mut self.state, State::Done) {
+            State::Chain { chain, .. } => chain.next.is_some(),
+            State::Nested {
+                root, mut nested, ..
+            } => root.is_some() || nested.skip_frame(),
+            State::Frames(mut frames) => frames.next().is_some(),
+            State::Cached(mut frames) => frames.next().is_some(),
+            State::Dedup { mut inner, pending } | State::Clean { mut inner, pending } => {
+                pending.is_some() || inner.skip_frame()
+            }
+            State::OsErrors { mut inner, .. } => inner.skip_frame(),
+            State::Done => false,
+        }
+    }
+}
+
+/// Details known up front about the first frame, the one of the error itself.
+#[derive(Default)]
+struct Root {
+    variant: Option<&'static str>,
+    message: Option<&'static str>,
+    module: Option<&'static str>,
+    #[cfg(feature = "thread")]
+    thread: Option<crate::CapturedThread>,
+    #[cfg(feature = "timestamp")]
+    timestamp: Option<std::time::SystemTime>,
+    metadata: Vec<(String, String)>,
+}
+
+impl Root {
+    fn frame(self, error: &dyn core::error::Error, location: Location) -> StackFrame {
+        let mut frame = match self.message {
+            Some(message) => StackFrame::new(location, message),
+            None => StackFrame::new(location, error.to_string()),
+        };
+        frame.variant = self.variant;
+        frame.module = self.module;
+        #[cfg(feature = "thread")]
+        {
+            frame.thread = self.thread.as_ref().map(crate::CapturedThread::name);
+        }
+        #[cfg(feature = "timestamp")]
+        {
+            frame.timestamp = self.timestamp;
+        }
+        frame.metadata = self.metadata;
+        frame
+    }
+}
+
+impl Iterator for StackIter<'_> {
+    type Item = StackFrame;
+
+    fn next(&mut self) -> Option<StackFrame> {
+        if self.remaining == 0 {
+            let location = match &self.state {
+                State::Chain { location, .. } | State::Nested { location, .. } => *location,
+                State::Frames(frames) => frames.as_slice().first()?.location,
+                State::Cached(frames) => frames.as_slice().first()?.location,
+                State::Dedup { .. }
+                | State::Clean { .. }
+                | State::OsErrors { .. }
+                | State::Done => return None,
+            };
+            // Only report truncation if there actually was something left
+            return self
+                .finish()
+                .then(|| StackFrame::new(location, TRUNCATED_MESSAGE));
+        }
+
+        let frame = self.next_untruncated();
+        match frame {
+            Some(_) => self.remaining -= 1,
+            None => self.state = State::Done,
+        }
+        frame
+    }
+
+    /// Counts the remaining frames without formatting their messages, unless they have to
+    /// be compared for [`dedup`](StackIter::dedup).
+    fn count(mut self) -> usize {
+        let mut count = 0;
+        while self.skip_frame() {
+            count += 1;
+        }
+        count
+    }
+}
+
+/// Applies [`StackIter::clean_messages`] to collected frames.
+pub(crate) fn clean_stack(frames: &mut [StackFrame]) {
+    for index in 1..frames.len() {
+        let (outer, inner) = frames.split_at_mut(index);
+        strip_source_suffix(&mut outer[index - 1].message, &inner[0].message);
+    }
+}
+
+/// Removes `": {source}"` from the end of `message`, if it ends that way.
+fn strip_source_suffix(message: &mut Cow<'static, str>, source: &str) {
+    let Some(len) = message
+        .strip_suffix(source)
+        .and_then(|rest| rest.strip_suffix(": "))
+        .map(str::len)
+    else {
+        return;
+    };
+    match message {
+        Cow::Borrowed(borrowed) => *borrowed = &borrowed[..len],
+        Cow::Owned(owned) => owned.truncate(len),
+    }
+}
+
+/// Start of the code the `Display` output of an OS [`std::io::Error`] ends with.
+const OS_ERROR: &str = "(os error ";
+
+/// Removes a trailing ` (os error N)` from `message` if `parent` contains `(os error N)`.
+fn strip_os_error(message: &mut Cow<'static, str>, parent: &str) {
+    let Some(start) = message.rfind(OS_ERROR) else {
+        return;
+    };
+    let code = &message[start..];
+    let is_code = code
+        .strip_prefix(OS_ERROR)
+        .and_then(|code| code.strip_suffix(')'))
+        .is_some_and(|digits| {
+            let digits = digits.strip_prefix('-').unwrap_or(digits);
+            !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit())
+        });
+    if !is_code || !parent.contains(code) {
+        return;
+    }
+
+    let len = message[..start].trim_end().len();
+    match message {
+        Cow::Borrowed(borrowed) => *borrowed = &borrowed[..len],
+        Cow::Owned(owned) => owned.truncate(len),
+    }
+}
+
+impl core::iter::FusedIterator for StackIter<'_> {}
+
+/// Iterates over `error` followed by each error of its `source()` chain, like
+/// [`snafu::ErrorCompat::iter_chain`] but for any error.
+///
+/// The first item is `error` itself. This is the walk [`StackIter::new`] performs for the
+/// frames of sources without a virtual stack, without formatting anything or limiting its
+/// length, so a cyclic `source()` chain never ends. Typed errors can be downcast from the
+/// items when `error` is `'static`.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::iter_chain;
+///
+/// #[derive(Debug, Snafu)]
+/// #[snafu(display("Failed to save report"))]
+/// struct SaveError {
+///     source: std::io::Error,
+/// }
+///
+/// let io = std::io::Error::new(std::io::ErrorKind::StorageFull, "disk full");
+/// let error = Err::<(), _>(io).context(SaveSnafu).unwrap_err();
+///
+/// let messages: Vec<_> = iter_chain(&error).map(|e| e.to_string()).collect();
+/// assert_eq!(messages, ["Failed to save report", "disk full"]);
+/// let io = iter_chain(&error).find_map(|e| e.downcast_ref::<std::io::Error>());
+/// assert_eq!(io.map(|e| e.kind()), Some(std::io::ErrorKind::StorageFull));
+/// ```
+pub fn iter_chain<'a, 'b>(
+    error: &'a (dyn core::error::Error + 'b),
+) -> impl Iterator<Item = &'a (dyn core::error::Error + 'b)> {
+    Chain { next: Some(error) }
+}
+
+/// Iterator returned by [`iter_chain`].
+struct Chain<'a, 'b> {
+    next: Option<&'a (dyn core::error::Error + 'b)>,
+}
+
+impl<'a, 'b> Iterator for Chain<'a, 'b> {
+    type Item = &'a (dyn core::error::Error + 'b);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let error = self.next.take()?;
+        self.next = error.source().map(|source| source as _);
+        Some(error)
+    }
+}
+
+impl core::iter::FusedIterator for Chain<'_, '_> {}
+
+/// A read-only view of a frame, yielded by [`FrameIter`].
+///
+/// Unlike a [`StackFrame`], a view may borrow its message from the error it was taken from,
+/// such as the frames stored in a [`StackCache`](crate::StackCache), so inspecting a trace
+/// doesn't clone them. Messages of placeholder-free `display("...")` literals are borrowed
+/// as well, any other message is an owned `String`. Use [`into_owned`] when the frame has
+/// to outlive the error.
+///
+/// [`into_owned`]: FrameView::into_owned
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrameView<'a> {
+    /// Location where the error occurred or was propagated
+    pub location: Location,
+    /// Error message for this frame
+    pub message: Cow<'a, str>,
+    /// Name of the enum variant that produced this frame, if known
+    pub variant: Option<&'static str>,
+}
+
+impl FrameView<'_> {
+    /// Converts the view into an owned frame with the same location, message and variant.
+    pub fn into_owned(self) -> StackFrame {
+        let mut frame = StackFrame::new(self.location, self.message.into_owned());
+        frame.variant = self.variant;
+        frame
+    }
+}
+
+impl<'a> From<&'a StackFrame> for FrameView<'a> {
+    fn from(frame: &'a StackFrame) -> Self {
+        Self {
+            location: frame.location,
+            message: Cow::Borrowed(&frame.message),
+            variant: frame.variant,
+        }
+    }
+}
+
+impl From<StackFrame> for FrameView<'_> {
+    fn from(frame: StackFrame) -> Self {
+        Self {
+            location: frame.location,
+            message: frame.message,
+            variant: frame.variant,
+        }
+    }
+}
+
+impl core::fmt::Display for FrameView<'_> {
+    /// Formats the view like a [`StackFrame`]: `[{variant}] {message} at {location}`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(variant) = self.variant {
+            write!(f, "[{variant}] ")?;
+        }
+        let separator = crate::location_separator();
+        write!(f, "{}{separator}{}", self.message, self.location)
+    }
+}
+
+/// Iterator over borrowed views of the frames of a virtual stack, returned by
+/// [`VirtualStackTrace::frames`](crate::VirtualStackTrace::frames).
+///
+/// Frames are built lazily like those of [`StackIter`]. Frames that are already stored,
+/// such as those of the [`StackCache`](crate::StackCache) of the error the iterator was
+/// created for, are lent out instead of cloned. Any other view is converted from a newly
+/// built [`StackFrame`], keeping its message as it is.
+#[derive(Debug)]
+pub struct FrameIter<'a>(StackIter<'a>);
+
+impl<'a> FrameIter<'a> {
+    /// Views the frames yielded by `frames`.
+    pub fn new(frames: StackIter<'a>) -> Self {
+        Self(frames)
+    }
+}
+
+impl<'a> Iterator for FrameIter<'a> {
+    type Item = FrameView<'a>;
+
+    fn next(&mut self) -> Option<FrameView<'a>> {
+        let frames = &mut self.0;
+        if let (State::Cached(cached), 1..) = (&mut frames.state, frames.remaining) {
+            // Stored frames outlive the iterator, so they can be lent out as they are
+            frames.remaining -= 1;
+            return cached.next().map(FrameView::from);
+        }
+        frames.next().map(FrameView::from)
+    }
+
+    fn count(self) -> usize {
+        self.0.count()
+    }
+}
+
+impl core::iter::FusedIterator for FrameIter<'_> {}
+
+impl core::fmt::Debug for StackIter<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StackIter").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,172 @@
+//! Logging virtual stacks at the boundary of `tower` services.
+
+use core::convert::Infallible;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::VirtualStackTrace;
+
+/// Errors a [`VirtStackService`] can log.
+///
+/// Every [`VirtualStackTrace`] error logs its stack through
+/// [`emit_tracing`](VirtualStackTrace::emit_tracing). The default method does nothing, so
+/// services failing with other errors opt in with an empty impl:
+///
+/// ```rust
+/// # use snafu_virtstack::LogVirtualStack;
+/// struct PlainError;
+///
+/// impl LogVirtualStack for PlainError {}
+/// ```
+///
+/// `tower::BoxError` and [`Infallible`] are already covered that way.
+pub trait LogVirtualStack {
+    /// Logs the virtual stack of the error, if it has one.
+    fn log_virtual_stack(&self) {}
+}
+
+impl<T: VirtualStackTrace + ?Sized> LogVirtualStack for T {
+    fn log_virtual_stack(&self) {
+        self.emit_tracing();
+    }
+}
+
+impl LogVirtualStack for alloc::boxed::Box<dyn core::error::Error + Send + Sync> {}
+
+impl LogVirtualStack for Infallible {}
+
+/// Layer logging the virtual stack of every error returned by the wrapped service.
+///
+/// Errors are logged through [`emit_tracing`](VirtualStackTrace::emit_tracing) once the
+/// response future resolves and then returned unchanged, so error logging happens at the
+/// service boundary instead of in every handler.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, VirtStackLayer};
+/// use tower::{Service, ServiceBuilder, ServiceExt};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum ApiError {
+///     #[snafu(display("User {id} not found"))]
+///     UserNotFound { id: u64 },
+/// }
+///
+/// async fn get_user(id: u64) -> Result<String, ApiError> {
+///     UserNotFoundSnafu { id }.fail()
+/// }
+///
+/// let mut service = ServiceBuilder::new()
+///     .layer(VirtStackLayer::new())
+///     .service_fn(get_user);
+///
+/// pollster::block_on(async {
+///     // The error has been logged by the time it is returned
+///     let error = service.ready().await?.call(7).await.unwrap_err();
+///     assert_eq!(error.to_string(), "User 7 not found");
+///     Ok::<_, ApiError>(())
+/// })
+/// .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VirtStackLayer {
+    _priv: (),
+}
+
+impl VirtStackLayer {
+    /// Creates the layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for VirtStackLayer {
+    type Service = VirtStackService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        VirtStackService::new(inner)
+    }
+}
+
+/// Service logging the virtual stack of every error returned by the service it wraps,
+/// created by [`VirtStackLayer`].
+#[derive(Clone, Debug)]
+pub struct VirtStackService<S> {
+    inner: S,
+}
+
+impl<S> VirtStackService<S> {
+    /// Wraps `inner`.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    /// Returns the wrapped service.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the wrapped service mutably.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwraps the wrapped service.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, Request> Service<Request> for VirtStackService<S>
+where
+    S: Service<Request>,
+    S::Error: LogVirtualStack,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = VirtStackFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Errors while waiting for readiness aren't request failures, leave them to the caller
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        VirtStackFuture {
+            inner: self.inner.call(request),
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Response future of [`VirtStackService`], logging the error the inner future
+    /// resolves to.
+    #[derive(Debug)]
+    pub struct VirtStackFuture<F> {
+        #[pin]
+        inner: F,
+    }
+}
+
+impl<F, T, E> Future for VirtStackFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: LogVirtualStack,
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = core::task::ready!(self.project().inner.poll(cx));
+        if let Err(error) = &result {
+            error.log_virtual_stack();
+        }
+        Poll::Ready(result)
+    }
+}
@@ -24,14 +24,16 @@
 //!
 //! ## Basic Usage
 //!
-//! Simply add the `#[stack_trace_debug]` attribute to your SNAFU error enum:
+//! Simply add the `#[stack_trace_debug]` attribute to your SNAFU error enum, above the
+//! `#[derive(Snafu)]`. The attribute and the common traits and types can also be imported
+//! at once with `use snafu_virtstack::prelude::*;`, see [`prelude`].
 //!
 //! ```rust
 //! use snafu::prelude::*;
 //! use snafu_virtstack::stack_trace_debug;
 //!
-//! #[derive(Snafu)]
 //! #[stack_trace_debug]  // Add this attribute
+//! #[derive(Snafu)]
 //! enum MyError {
 //!     #[snafu(display("Failed to read file: {filename}"))]
 //!     FileRead { filename: String, source: std::io::Error },
@@ -51,6 +53,25 @@
 //! }
 //! ```
 //!
+//! ## Location Capture
+//!
+//! The attribute injects an implicit `location: snafu::Location` field into error structs and
+//! every struct and unit variant of error enums, which SNAFU fills in wherever the error is
+//! built (`.context(...)`, `.fail()`, `ensure!`, ...). This is why the attribute must come
+//! *before* `#[derive(Snafu)]`: once the derive has run the type can no longer be changed.
+//! Types and variants that already declare a `location` field keep their own. Variants
+//! marked `#[snafu(context(false))]` have no context selector; SNAFU builds them in its
+//! `#[track_caller]` `From` implementation, so they capture the `?` or `.into()` converting
+//! their source.
+//!
+//! Sources that are themselves annotated with `#[stack_trace_debug]` contribute their own
+//! frames, with the locations they captured, so a chain across modules shows the full
+//! propagation path. The source field is found the same way SNAFU finds it: a field
+//! named `source`, unless marked `#[snafu(source(false))]`, or any field marked
+//! `#[snafu(source)]` or `#[snafu(source(from(...)))]`; boxed sources work as well.
+//! Foreign sources such as [`std::io::Error`] don't capture a location and are attributed
+//! to the point where they were wrapped.
+//!
 //! ## Generated Debug Output
 //!
 //! When an error occurs, the generated [`Debug`] implementation will display:
@@ -58,8 +79,204 @@
 //! ```text
 //! Error: Failed to read file: config.json
 //! Virtual Stack Trace:
-//!   0: Failed to read file: config.json at src/main.rs:15:23
-//!   1: No such file or directory (os error 2) at src/main.rs:16:10
+//!   0: [FileRead] Failed to read file: config.json at src/main.rs:15:23
+//!   1: No such file or directory (os error 2) at src/main.rs:15:23
+//! ```
+//!
+//! Frames of enum errors are prefixed with the name of the variant that produced them, which
+//! tells apart variants with similar messages. Foreign sources have no variant.
+//!
+//! The alternate form (`{:#?}`) renders the same frames as an indented tree, which stays
+//! readable when pasted into an issue tracker:
+//!
+//! ```text
+//! Error: Failed to load settings
+//! Virtual Stack Trace:
+//!   0: [LoadSettings] Failed to load settings at src/main.rs:20:10
+//!   └─ 1: [FileRead] Failed to read file: config.json at src/main.rs:15:23
+//!      └─ 2: No such file or directory (os error 2) at src/main.rs:15:23
+//! ```
+//!
+//! Everything the crate adds around the messages follows a fixed grammar: frame indices,
+//! lines and columns are written in ASCII digits without grouping or padding, and locations
+//! always take the form `file:line:column`. Neither the system locale nor the flags of the
+//! format string, such as `{:+?}` or `{:x?}`, change them, so log parsers can rely on it.
+//! Only the messages themselves are passed through as the errors display them.
+//!
+//! ## Status Codes
+//!
+//! Following GreptimeDB, each error can be classified with a [`StatusCode`] through the
+//! [`ErrorExt`] trait. Annotate variants with `#[status_code(...)]`; variants without the
+//! attribute are [`StatusCode::Internal`]:
+//!
+//! ```rust
+//! use snafu::prelude::*;
+//! use snafu_virtstack::{stack_trace_debug, ErrorExt, StatusCode};
+//!
+//! #[stack_trace_debug]
+//! #[derive(Snafu)]
+//! enum ServiceError {
+//!     #[snafu(display("User {id} not found"))]
+//!     #[status_code(NotFound)]
+//!     UserNotFound { id: u64 },
+//!
+//!     #[snafu(display("Failed to query database"))]
+//!     DatabaseQuery { source: std::io::Error },
+//! }
+//!
+//! let error = UserNotFoundSnafu { id: 42u64 }.build();
+//! assert_eq!(error.status_code(), StatusCode::NotFound);
+//! ```
+//!
+//! ### Client-Facing Messages
+//!
+//! An error has two audiences. [`ErrorExt::output_msg`] is the short message safe to return
+//! to API clients: the top-level `Display` string, or the text of the variant's
+//! `#[external_msg("...")]` attribute. The generated [`Debug`] output is the full trace for
+//! internal logs, with every message and source location. Send only the former over the wire:
+//!
+//! ```rust
+//! use snafu::prelude::*;
+//! use snafu_virtstack::{stack_trace_debug, ErrorExt};
+//!
+//! #[stack_trace_debug]
+//! #[derive(Snafu)]
+//! enum ServiceError {
+//!     #[snafu(display("Failed to connect to {host}"))]
+//!     #[external_msg("Storage backend unavailable")]
+//!     Connect { host: String, source: std::io::Error },
+//! }
+//!
+//! let io = std::io::Error::other("connection refused");
+//! let error = Err::<(), _>(io)
+//!     .context(ConnectSnafu { host: "10.0.0.7:5432" })
+//!     .unwrap_err();
+//!
+//! assert_eq!(error.output_msg(), "Storage backend unavailable");
+//! assert!(format!("{error:?}").contains("Failed to connect to 10.0.0.7:5432"));
+//! ```
+//!
+//! ## Frame Fields
+//!
+//! Fields the display string leaves out can still be recorded in the trace by marking them
+//! `#[frame]`. They are attached to the frame of the error as `field_name=value` pairs of
+//! [`StackFrame::metadata`], formatted with their `Debug` output:
+//!
+//! ```rust
+//! use snafu::prelude::*;
+//! use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+//!
+//! #[stack_trace_debug]
+//! #[derive(Snafu)]
+//! #[snafu(display("Failed to charge card"))]
+//! struct ChargeError {
+//!     #[frame]
+//!     order_id: u64,
+//!     #[frame]
+//!     retries: u8,
+//! }
+//!
+//! let error = ChargeSnafu { order_id: 1042u64, retries: 2u8 }.build();
+//! let frame = &error.virtual_stack()[0];
+//! assert_eq!(frame.metadata[0], ("order_id".to_string(), "1042".to_string()));
+//! assert_eq!(frame.metadata[1], ("retries".to_string(), "2".to_string()));
+//! ```
+//!
+//! [`VerboseFormatter`] shows them on a `with order_id=1042 retries=2` line. A marked field
+//! whose type doesn't implement `Debug` is a compile error:
+//!
+//! ```rust,compile_fail
+//! use snafu::prelude::*;
+//! use snafu_virtstack::stack_trace_debug;
+//!
+//! struct Card;
+//!
+//! #[stack_trace_debug]
+//! #[derive(Snafu)]
+//! #[snafu(display("Failed to charge card"))]
+//! struct ChargeError {
+//!     #[frame]
+//!     card: Card,
+//! }
+//! ```
+//!
+//! ## Disabling at Runtime
+//!
+//! Much like `RUST_BACKTRACE`, setting the `VIRTSTACK_DISABLE=1` environment variable makes
+//! the generated [`Debug`] implementations fall back to a single `Error: {error}` line without
+//! walking the source chain. The variable is read once per process; [`set_enabled`] overrides
+//! it programmatically.
+//!
+//! File paths in rendered locations can be shortened process-wide with [`set_path_style`]:
+//! [`PathStyle::Relative`] shows `src/config.rs:42:15` and [`PathStyle::FileName`] just
+//! `config.rs:42:15`. Overly long messages can be cut off when rendered with
+//! [`set_max_message_len`], [`set_root_first`] lists the root cause first, and
+//! [`set_clean_messages`] strips the repeated source text of `display("...: {source}")`
+//! messages. With `std`, `set_skip_files` hides the frames of files under given prefixes,
+//! such as `.cargo/registry`, behind a `(N frames hidden)` line.
+//!
+//! ## Reporting from `main`
+//!
+//! Return a [`Report`] from `main` to print the trace of a failed program to stderr and exit
+//! with a failure code:
+//!
+//! ```rust,no_run
+//! # use snafu::prelude::*;
+//! # use snafu_virtstack::stack_trace_debug;
+//! # #[stack_trace_debug]
+//! # #[derive(Snafu)]
+//! # enum MyError {
+//! #     #[snafu(display("Something went wrong"))]
+//! #     SomethingWrong,
+//! # }
+//! use snafu_virtstack::Report;
+//!
+//! fn run() -> Result<(), MyError> {
+//!     SomethingWrongSnafu.fail()
+//! }
+//!
+//! fn main() -> Report<MyError> {
+//!     Report::capture(run)
+//! }
+//! ```
+//!
+//! ## Derive Form
+//!
+//! [`StackTraceDebug`] generates the same implementations as a derive listed next to
+//! `Snafu`. Derives can't add fields, so declare the `location` field yourself wherever it
+//! should be captured, and pass arguments through `#[virtstack(...)]`:
+//!
+//! ```rust
+//! use snafu::prelude::*;
+//! use snafu_virtstack::StackTraceDebug;
+//!
+//! #[derive(Snafu, StackTraceDebug)]
+//! #[virtstack(max_depth = 16, root_first)]
+//! #[snafu(display("Failed to load user {id}"))]
+//! #[status_code(NotFound)]
+//! struct LoadError {
+//!     id: u64,
+//!     #[snafu(implicit)]
+//!     location: snafu::Location,
+//! }
+//! ```
+//!
+//! The derive only relies on `Display` and [`Error::source`](core::error::Error::source),
+//! so it also works on errors that implement them by hand instead of deriving `Snafu`. The
+//! `location` field is then filled in by the code creating the error, for instance from
+//! `snafu::Location::default()`. The attribute requires `Snafu`, which fills in the fields
+//! it injects.
+//!
+//! The `cache` argument injects a field, so it is only available with the attribute:
+//!
+//! ```compile_fail
+//! use snafu::prelude::*;
+//! use snafu_virtstack::StackTraceDebug;
+//!
+//! #[derive(Snafu, StackTraceDebug)]
+//! #[virtstack(cache)]
+//! #[snafu(display("Something went wrong"))]
+//! struct MyError;
 //! ```
 //!
 //! ## Advanced Usage
@@ -70,14 +287,14 @@
 //! use snafu_virtstack::VirtualStackTrace;
 //! # use snafu::prelude::*;
 //! # use snafu_virtstack::stack_trace_debug;
-//! # #[derive(Snafu)]
 //! # #[stack_trace_debug]
+//! # #[derive(Snafu)]
 //! # enum MyError {
 //! #     #[snafu(display("Something went wrong"))]
 //! #     SomethingWrong,
 //! # }
 //!
-//! let error = MyError::SomethingWrong;
+//! let error = SomethingWrongSnafu.build();
 //! let stack = error.virtual_stack();
 //!
 //! for (i, frame) in stack.iter().enumerate() {
@@ -90,12 +307,100 @@
 //! }
 //! ```
 //!
+//! ## Optional Features
+//!
+//! - `std` (default): Reads the `VIRTSTACK_DISABLE` environment variable. Without it the crate
+//!   is `#![no_std]` and only needs `alloc`; the generated code uses `core`/`alloc` paths and
+//!   [`core::error::Error`]
+//! - `hyperlinks`: Wraps each location in the generated [`Debug`] output in an OSC 8 hyperlink
+//!   to its file when stderr is a terminal, making it clickable in terminals such as iTerm2,
+//!   WezTerm or VS Code. `FORCE_HYPERLINK=1` forces links on and `FORCE_HYPERLINK=0` off
+//! - `tracing`: Adds `VirtualStackTrace::emit_tracing`, which logs each frame as a structured
+//!   `tracing` event
+//! - `tonic`: Adds `ErrorExt::to_tonic_status`, which converts errors into `tonic::Status`
+//!   with the gRPC code matching their [`StatusCode`]
+//! - `http`: Adds `HttpError`, an `axum` `IntoResponse` wrapper answering with the HTTP status
+//!   matching the error's [`StatusCode`] and a JSON body, while logging the full stack
+//!   through `tracing`
+//! - `anyhow`: Adds `VirtualStackTrace::into_anyhow`, which converts errors into
+//!   `anyhow::Error` with the rendered virtual stack as context
+//! - `regex`: Adds `redact_secrets`, a redactor for `VirtualStackTrace::redacted` that masks
+//!   email addresses and token-like strings
+//! - `miette`: Adds `StackDiagnostic`, a wrapper implementing `miette::Diagnostic` whose
+//!   `help` renders the virtual stack trace
+//! - `sentry`: Adds `VirtualStackTrace::to_sentry_event`, which builds a Sentry event whose
+//!   exception stack trace lists the frames of the virtual stack
+//! - `opentelemetry`: Adds `VirtualStackTrace::record_otel`, which records an error as an
+//!   `exception` event on an OpenTelemetry span, with the virtual stack as its
+//!   `exception.stacktrace`
+//! - `thread`: Records the thread each error is created on, by name or id, in
+//!   `StackFrame::thread` through another injected implicit field, and shows it in the
+//!   [`VerboseFormatter`] output. Without the feature neither the field nor the frame entry
+//!   exist
+//! - `timestamp`: Records the time each error is created at in `StackFrame::timestamp`
+//!   through another injected implicit field, and shows it as an RFC 3339 UTC timestamp in
+//!   the [`VerboseFormatter`] output and in serialized frames. Without the feature neither
+//!   the field nor the frame entry exist
+//! - `smallvec`: Makes [`Stack`], the return type of [`VirtualStackTrace::virtual_stack`], a
+//!   `SmallVec<[StackFrame; 4]>` so that shallow chains don't allocate for their frames
+//! - `serde`: Implements `Serialize` for [`StackFrame`] as a flat `message`/`file`/`line`/`column`
+//!   object, and `Serialize`/`Deserialize` for its owned mirror [`OwnedFrame`] and for
+//!   [`StackReport`]. Also adds `VirtualStackTrace::to_problem_json` for RFC 7807
+//!   `application/problem+json` bodies and `VirtualStackTrace::to_json` for structured logs
+//! - `valuable`: Implements `valuable::Valuable` for [`StackFrame`], exposing its `message`,
+//!   `file`, `line` and `column` as structured fields, and adds `Frames`, which exposes a
+//!   whole stack as a list, e.g. for `tracing` subscribers recording `valuable` values
+//! - `testing`: Adds `assert_stack!`, which asserts the depth of a virtual stack and the
+//!   variant and message of each frame, for tests of error construction, and
+//!   `VirtualStackTrace::diff`, which lists the frames where two stacks diverge
+//! - `color`: Colors the frame index, message and location in the generated [`Debug`]
+//!   output when stderr is a terminal, and its label after the [`Level`] of the error.
+//!   `NO_COLOR` and `CLICOLOR=0` disable colors and `CLICOLOR_FORCE` forces them on even
+//!   when piped. The same decision is available to custom formatters through
+//!   `should_colorize`
+//!
+//! The injected `location` field is marked `#[snafu(provide)]`. Enabling SNAFU's
+//! `unstable-provider-api` feature on a nightly compiler makes it available through
+//! `core::error::request_ref::<snafu::Location>`, which returns the root cause's location.
+//!
 //! ## Requirements
 //!
-//! - Must be applied to `enum` types only
-//! - The enum should derive [`Snafu`] for full functionality
+//! - Must be applied to `enum` or `struct` types
+//! - The type must derive `Snafu`, with `#[stack_trace_debug]` placed above the derive
+//! - Enum variants must be struct-like or unit variants, like Snafu itself requires. The
+//!   [`StackTraceDebug`] derive, which doesn't need Snafu, also supports tuple variants
 //! - Works best with error enums that have source fields for error chaining
 //!
+//! A type without a visible `#[derive(Snafu)]`, including one whose derive comes first, is
+//! rejected at compile time:
+//!
+//! ```compile_fail
+//! use snafu::Snafu;
+//! use snafu_virtstack::stack_trace_debug;
+//!
+//! #[derive(Snafu)]
+//! #[stack_trace_debug]
+//! enum MyError {
+//!     #[snafu(display("Something went wrong"))]
+//!     SomethingWrong,
+//! }
+//! ```
+//!
+//! A tuple variant is reported by the Snafu derive alone, without follow-up errors from the
+//! generated code:
+//!
+//! ```compile_fail
+//! use snafu::Snafu;
+//! use snafu_virtstack::stack_trace_debug;
+//!
+//! #[stack_trace_debug]
+//! #[derive(Snafu)]
+//! enum MyError {
+//!     #[snafu(display("Failed to parse"))]
+//!     Parse(std::num::ParseIntError),
+//! }
+//! ```
+//!
 //! ## Performance Benefits
 //!
 //! The virtual stack trace approach provides several key advantages:
@@ -116,7 +421,7 @@
 //! ### 3. Production-Ready
 //! - Safe to use in production environments
 //! - No performance penalties in the happy path
-//! - Can be enabled/disabled at runtime if needed
+//! - Can be enabled/disabled at runtime if needed via [`set_enabled`] or `VIRTSTACK_DISABLE`
 //!
 //! ## How It Works
 //!
@@ -124,8 +429,9 @@
 //!    - [`VirtualStackTrace`] trait for stack frame collection
 //!    - Custom [`Debug`] implementation for formatted output
 //!
-//! 2. **Location Tracking**: Uses Rust's `#[track_caller]` to capture precise locations
-//!    where errors are propagated
+//! 2. **Location Tracking**: Injects SNAFU's implicit `location` field into each variant,
+//!    which relies on Rust's `#[track_caller]` to capture precise locations where errors
+//!    are propagated
 //!
 //! 3. **Error Chain Walking**: Automatically traverses the `source()` chain to build
 //!    complete error context
@@ -133,28 +439,511 @@
 //! 4. **Zero-Cost Until Needed**: Stack frames are only generated when the error is
 //!    actually inspected
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod cache;
+#[cfg(feature = "color")]
+mod color;
+mod config;
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod foreign;
+mod format;
+#[cfg(feature = "tonic")]
+mod grpc;
+#[cfg(feature = "hyperlinks")]
+mod hyperlink;
+mod iter;
+#[cfg(feature = "tower")]
+mod layer;
+mod multi;
+#[cfg(feature = "opentelemetry")]
+mod otel;
+#[cfg(feature = "std")]
+mod panic;
+#[cfg(feature = "regex")]
+mod redact;
+#[cfg(feature = "std")]
+mod report;
+#[cfg(feature = "sentry")]
+mod sentry;
+mod status;
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "thread")]
+mod thread;
+#[cfg(feature = "timestamp")]
+mod timestamp;
+#[cfg(feature = "valuable")]
+mod value;
+#[cfg(feature = "http")]
+mod web;
+mod whatever;
+
 // Re-export the proc macro so users only need to depend on this crate
-pub use snafu_virtstack_macro::stack_trace_debug;
+pub use snafu_virtstack_macro::{StackTraceDebug, stack_trace_debug};
+
+pub use cache::StackCache;
+#[cfg(feature = "color")]
+pub use color::should_colorize;
+pub use config::{
+    DEFAULT_LOCATION_SEPARATOR, DISABLE_ENV_VAR, clean_messages, compact_leaf, is_enabled,
+    location_separator, max_frames, max_message_len, path_style, root_first, set_clean_messages,
+    set_compact_leaf, set_enabled, set_max_frames, set_max_message_len, set_path_style,
+    set_root_first,
+};
+#[cfg(feature = "std")]
+pub use config::{
+    set_error_observer, set_location_separator, set_path_trimmer, set_skip_files, skip_files,
+};
+#[cfg(feature = "miette")]
+pub use diagnostic::StackDiagnostic;
+pub use format::{CompactFormatter, FrameFormatter, PathStyle, StackDisplay, VerboseFormatter};
+#[cfg(feature = "tonic")]
+pub use grpc::TONIC_STACK_METADATA_KEY;
+pub use iter::{FrameIter, FrameView, StackIter, iter_chain};
+#[cfg(feature = "tower")]
+pub use layer::{LogVirtualStack, VirtStackFuture, VirtStackLayer, VirtStackService};
+pub use multi::MultiError;
+#[cfg(feature = "std")]
+pub use panic::{PanicError, catch_panic};
+#[cfg(feature = "regex")]
+pub use redact::redact_secrets;
+#[cfg(feature = "std")]
+pub use report::Report;
+pub use status::{ErrorExt, Level, StatusCode};
+#[cfg(feature = "testing")]
+pub use testing::{FrameDiff, render_diff};
+#[cfg(feature = "thread")]
+pub use thread::CapturedThread;
+#[cfg(feature = "timestamp")]
+pub use timestamp::CapturedTime;
+#[cfg(feature = "valuable")]
+pub use value::Frames;
+#[cfg(feature = "http")]
+pub use web::HttpError;
+pub use whatever::VirtWhatever;
+
+pub mod prelude {
+    //! Traits, types and macros used by most projects. Add
+    //! `use snafu_virtstack::prelude::*;` next to `use snafu::prelude::*;` to get started.
+    //!
+    //! ```rust
+    //! use snafu::prelude::*;
+    //! use snafu_virtstack::prelude::*;
+    //!
+    //! #[stack_trace_debug]
+    //! #[derive(Snafu)]
+    //! enum MyError {
+    //!     #[snafu(display("Not found"))]
+    //!     #[status_code(NotFound)]
+    //!     Missing,
+    //! }
+    //!
+    //! let error = MissingSnafu.build();
+    //! let frame: StackFrame = error.virtual_stack().remove(0);
+    //! assert_eq!(frame.variant, Some("Missing"));
+    //! assert_eq!(error.status_code(), StatusCode::NotFound);
+    //! ```
+
+    pub use crate::{
+        ErrorExt, StackFrame, StackTraceDebug, StatusCode, VirtualStackTrace, stack_trace_debug,
+        virt_whatever,
+    };
+}
+
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Support code for the [`stack_trace_debug`], [`virt_whatever!`] and `assert_stack!` expansions.
+/// Not public API.
+#[doc(hidden)]
+pub mod __private {
+    use crate::format::{Label, Truncated};
+    use crate::{StackFrame, StackIter, VirtualStackTrace};
+
+    #[cfg(feature = "testing")]
+    pub use crate::testing::{ExpectedFrame, assert_stack};
+
+    pub use alloc::format;
+    pub use alloc::string::{String, ToString};
+    pub use snafu::FromString;
+
+    /// Wraps a source field so the generated code can ask for its virtual stack.
+    ///
+    /// Calling `(&&Source(&field)).nested_stack()` resolves to [`LeafSource`] for standard
+    /// library errors, to [`NestedStack`] when the field type implements
+    /// [`VirtualStackTrace`] and to [`ForeignSource`] otherwise.
+    pub struct Source<'a, T: ?Sized>(pub &'a T);
+
+    /// Marks the standard library errors implementing [`VirtualStackTrace`], which are
+    /// still walked like foreign sources so they keep the location of their wrapper.
+    pub trait StdSource {}
+
+    impl<T: StdSource + ?Sized> StdSource for alloc::boxed::Box<T> {}
+
+    /// Picked for standard library errors, which are walked through `Error::source()`.
+    pub trait LeafSource<'a> {
+        fn nested_stack(&self) -> Option<StackIter<'a>>;
+    }
+
+    impl<'a, T: StdSource + ?Sized> LeafSource<'a> for &Source<'a, T> {
+        fn nested_stack(&self) -> Option<StackIter<'a>> {
+            None
+        }
+    }
+
+    /// Picked for sources with a virtual stack of their own.
+    pub trait NestedStack<'a> {
+        fn nested_stack(&self) -> Option<StackIter<'a>>;
+    }
+
+    impl<'a, T: VirtualStackTrace + ?Sized> NestedStack<'a> for &&Source<'a, T> {
+        #[track_caller]
+        fn nested_stack(&self) -> Option<StackIter<'a>> {
+            let source: &'a T = self.0;
+            Some(source.virtual_stack_iter())
+        }
+    }
+
+    /// Fallback for any other source, which is walked through `Error::source()` instead.
+    pub trait ForeignSource<'a> {
+        fn nested_stack(&self) -> Option<StackIter<'a>>;
+    }
+
+    impl<'a, T: ?Sized> ForeignSource<'a> for Source<'a, T> {
+        fn nested_stack(&self) -> Option<StackIter<'a>> {
+            None
+        }
+    }
+
+    /// Formats the value of a `#[frame]` field for the metadata of its frame.
+    pub fn frame_value<T: FrameValue + ?Sized>(value: &T) -> String {
+        alloc::format!("{value:?}")
+    }
+
+    /// Bound of `#[frame]` fields, only there to explain the missing `Debug` impl.
+    #[diagnostic::on_unimplemented(
+        message = "`#[frame]` field of type `{Self}` doesn't implement `Debug`",
+        label = "captured into the frame with `#[frame]`",
+        note = "`#[frame]` fields are recorded with their `Debug` output, derive or implement \
+                `Debug` for `{Self}`"
+    )]
+    pub trait FrameValue: core::fmt::Debug {}
+
+    impl<T: core::fmt::Debug + ?Sized> FrameValue for T {}
+
+    /// Settings of a generated `Debug` implementation, from the macro arguments or else the
+    /// process-wide settings.
+    pub struct DebugOptions<'a> {
+        pub error_label: &'a str,
+        pub trace_label: &'a str,
+        pub max_message_len: Option<usize>,
+        pub max_frames: Option<usize>,
+        pub root_first: bool,
+        pub clean_messages: bool,
+        pub compact_leaf: bool,
+        pub location_separator: &'a str,
+        pub level: crate::Level,
+    }
+
+    impl DebugOptions<'_> {
+        /// Default labels with the process-wide settings.
+        pub(crate) fn global() -> Self {
+            Self {
+                error_label: "Error",
+                trace_label: "Virtual Stack Trace",
+                max_message_len: crate::max_message_len(),
+                max_frames: crate::max_frames(),
+                root_first: crate::root_first(),
+                clean_messages: crate::clean_messages(),
+                compact_leaf: crate::compact_leaf(),
+                location_separator: crate::location_separator(),
+                level: crate::Level::Error,
+            }
+        }
+    }
+
+    /// Writes the generated `Debug` output of an enabled trace in a single pass.
+    ///
+    /// The header reuses the message of the first frame, which is the `Display` output of
+    /// `error`, so each message is only formatted once. Messages, including the header, are
+    /// cut off after `max_message_len` characters, with `root_first` the frames are
+    /// written from the root cause outwards, with `clean_messages` the frame messages
+    /// are stripped of the message of their source, and with `compact_leaf` an error
+    /// without sources is written on a single line unless `{:#?}` is used.
+    pub fn write_debug(
+        f: &mut core::fmt::Formatter<'_>,
+        options: &DebugOptions<'_>,
+        error: &dyn VirtualStackTrace,
+    ) -> core::fmt::Result {
+        write_stack_debug(f, options, error.virtual_stack_iter())
+    }
+
+    /// Renders the generated `Debug` output of the error whose frames are `frames` with the
+    /// default labels, for [`VirtualStackTrace::render`].
+    pub(crate) fn render(frames: StackIter<'_>) -> String {
+        struct Trace<'a>(core::cell::Cell<Option<StackIter<'a>>>);
+
+        impl core::fmt::Debug for Trace<'_> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                let Some(mut frames) = self.0.take() else {
+                    return Ok(());
+                };
+                if !crate::is_enabled() {
+                    let first = frames.next();
+                    let message = first.as_ref().map_or("", |first| &first.message);
+                    return write!(f, "Error: {}", Truncated::global(message));
+                }
+                write_stack_debug(f, &DebugOptions::global(), frames)
+            }
+        }
+
+        alloc::format!("{:?}", Trace(core::cell::Cell::new(Some(frames))))
+    }
+
+    fn write_stack_debug(
+        f: &mut core::fmt::Formatter<'_>,
+        options: &DebugOptions<'_>,
+        frames: StackIter<'_>,
+    ) -> core::fmt::Result {
+        let mut frames = frames.peekable();
+        let mut first = None;
+        if options.compact_leaf && !f.alternate() {
+            first = frames.next();
+            if let Some(leaf) = first.as_ref().filter(|_| frames.peek().is_none()) {
+                crate::config::observe(core::slice::from_ref(leaf));
+                let message = Truncated {
+                    message: &leaf.message,
+                    max_len: options.max_message_len,
+                };
+                let label = Label::new(options);
+                let separator = options.location_separator;
+                return write!(f, "{label}: {message}{separator}{}", leaf.location);
+            }
+        }
+        let frames = first.into_iter().chain(frames);
+
+        // The observer, the reversed order and the clean messages need all frames at once,
+        // otherwise they're rendered as they come
+        if options.root_first || options.clean_messages || crate::config::has_observer() {
+            let mut frames: crate::Stack = frames.collect();
+            crate::config::observe(&frames);
+            // The header keeps showing the full message of the outermost error
+            let full_header = match frames.first() {
+                Some(first) if options.clean_messages => Some(first.message.clone()),
+                _ => None,
+            };
+            if options.clean_messages {
+                crate::iter::clean_stack(&mut frames);
+            }
+            let header = match &full_header {
+                Some(header) => header,
+                None => frames.first().map_or("", |first| &first.message),
+            };
+            if options.root_first {
+                return write_collected(f, options, header, frames.iter().rev());
+            }
+            return write_collected(f, options, header, frames.iter());
+        }
+        write_frames(f, options, frames)
+    }
+
+    fn write_frames(
+        f: &mut core::fmt::Formatter<'_>,
+        options: &DebugOptions<'_>,
+        mut frames: impl Iterator<Item = StackFrame>,
+    ) -> core::fmt::Result {
+        let DebugOptions {
+            trace_label,
+            max_message_len,
+            ..
+        } = *options;
+        let error_label = Label::new(options);
+        let Some(first) = frames.next() else {
+            return writeln!(f, "{error_label}: \n{trace_label}:");
+        };
+
+        let header = Truncated {
+            message: &first.message,
+            max_len: max_message_len,
+        };
+        writeln!(f, "{error_label}: {header}")?;
+        writeln!(f, "{trace_label}:")?;
+        let frames = core::iter::once(first).chain(frames);
+        write_visible_frames(f, frames, options)
+    }
+
+    /// Like `write_frames` for frames collected up front, which may be reversed or cleaned,
+    /// so the header showing the outermost error is passed separately.
+    fn write_collected<'a>(
+        f: &mut core::fmt::Formatter<'_>,
+        options: &DebugOptions<'_>,
+        header: &str,
+        frames: impl Iterator<Item = &'a StackFrame>,
+    ) -> core::fmt::Result {
+        let header = Truncated {
+            message: header,
+            max_len: options.max_message_len,
+        };
+        writeln!(f, "{}: {header}", Label::new(options))?;
+        writeln!(f, "{}:", options.trace_label)?;
+        write_visible_frames(f, frames, options)
+    }
+
+    /// Writes the numbered `frames`, leaving out those hidden by
+    /// [`set_skip_files`](crate::set_skip_files) and summarizing those past `max_frames`.
+    pub(crate) fn write_visible_frames(
+        f: &mut core::fmt::Formatter<'_>,
+        frames: impl Iterator<Item = impl core::borrow::Borrow<StackFrame>>,
+        options: &DebugOptions<'_>,
+    ) -> core::fmt::Result {
+        let DebugOptions {
+            max_message_len,
+            max_frames,
+            location_separator,
+            ..
+        } = *options;
+        let mut hidden = crate::format::HiddenFrames::new();
+        let mut frames = frames.enumerate();
+        while let Some((index, frame)) = frames.next() {
+            if max_frames == Some(index) {
+                hidden.flush(f)?;
+                // Counting the rest doesn't format their messages
+                return crate::format::write_more_frames(f, 1 + frames.count());
+            }
+            let frame = frame.borrow();
+            if hidden.hide(frame) {
+                continue;
+            }
+            hidden.flush(f)?;
+            write_frame(f, index, frame, max_message_len, location_separator)?;
+        }
+        hidden.flush(f)
+    }
+
+    /// Collects the stack returned by a generated `virtual_stack`, notifying the observer.
+    pub fn collect_stack(stack: StackIter<'_>) -> crate::Stack {
+        let frames: crate::Stack = stack.collect();
+        crate::config::observe(&frames);
+        frames
+    }
+
+    /// Writes one numbered line of the generated `Debug` output.
+    ///
+    /// `{:#?}` nests each source one level deeper than its parent.
+    pub fn write_frame(
+        f: &mut core::fmt::Formatter<'_>,
+        index: usize,
+        frame: &StackFrame,
+        max_message_len: Option<usize>,
+        separator: &str,
+    ) -> core::fmt::Result {
+        let message = Truncated {
+            message: &frame.message,
+            max_len: max_message_len,
+        };
+
+        if f.alternate() && index > 0 {
+            write!(f, "  {:indent$}└─ ", "", indent = 3 * (index - 1))?;
+        } else {
+            write!(f, "  ")?;
+        }
+
+        #[cfg(feature = "hyperlinks")]
+        let location = crate::hyperlink::Hyperlink(&frame.location);
+        #[cfg(not(feature = "hyperlinks"))]
+        let location = frame.location;
+
+        #[cfg(feature = "color")]
+        if crate::color::enabled() {
+            use crate::color::{INDEX, LOCATION, MESSAGE, RESET};
+
+            write!(f, "{INDEX}{index}{RESET}: ")?;
+            if let Some(variant) = frame.variant {
+                write!(f, "[{variant}] ")?;
+            }
+            return writeln!(
+                f,
+                "{MESSAGE}{message}{RESET}{separator}{LOCATION}{location}{RESET}"
+            );
+        }
+
+        // Same layout as `CompactFormatter`, with the location possibly hyperlinked
+        write!(f, "{index}: ")?;
+        if let Some(variant) = frame.variant {
+            write!(f, "[{variant}] ")?;
+        }
+        writeln!(f, "{message}{separator}{location}")
+    }
+}
+
+/// Default maximum number of frames collected by a generated [`VirtualStackTrace::virtual_stack`].
+///
+/// Walking stops once this many frames have been collected, which guarantees termination
+/// even if a `source()` chain contains a cycle. It can be raised per type with
+/// `#[stack_trace_debug(max_depth = N)]`.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Frames returned by [`VirtualStackTrace::virtual_stack`].
+///
+/// A `Vec<StackFrame>` by default. With the `smallvec` feature it is a
+/// `SmallVec<[StackFrame; 4]>`, which keeps the frames of shallow chains off the heap.
+/// Both dereference to `[StackFrame]`, so slice methods work either way.
+#[cfg(not(feature = "smallvec"))]
+pub type Stack = alloc::vec::Vec<StackFrame>;
+
+/// Frames returned by [`VirtualStackTrace::virtual_stack`].
+///
+/// A `Vec<StackFrame>` by default. With the `smallvec` feature it is a
+/// `SmallVec<[StackFrame; 4]>`, which keeps the frames of shallow chains off the heap.
+/// Both dereference to `[StackFrame]`, so slice methods work either way.
+#[cfg(feature = "smallvec")]
+pub type Stack = smallvec::SmallVec<[StackFrame; 4]>;
+
+/// Message of the synthetic frame appended when the source chain exceeds the maximum depth.
+pub const TRUNCATED_MESSAGE: &str = "... source chain truncated (possible cycle)";
 
 /// Core trait for virtual stack trace functionality.
 ///
 /// This trait is automatically implemented by the [`stack_trace_debug`] proc macro attribute.
 /// It provides access to the virtual stack trace showing the error propagation path.
 ///
+/// # Relation to `Error`
+///
+/// Every type using [`stack_trace_debug`] also implements [`Error`](core::error::Error)
+/// through its `Snafu` derive, but `Error` is not a supertrait: `Box<dyn VirtualStackTrace>`
+/// and other unsized implementors couldn't implement it. A trait object of this trait is
+/// turned into an error with [`as_error`](VirtualStackTrace::as_error) instead, while the
+/// methods needing the typed error, such as [`sources`](VirtualStackTrace::sources), are
+/// only available when `Self: Error`.
+///
+/// # Standard Library Errors
+///
+/// Common leaf errors of the standard library, such as [`std::io::Error`],
+/// [`ParseIntError`](core::num::ParseIntError) or [`Utf8Error`](core::str::Utf8Error),
+/// implement the trait too, so any source in a chain can be asked for its stack. It is the
+/// error followed by its `source()` chain, all attributed to the caller since they don't
+/// capture a location. As sources of a `#[stack_trace_debug]` error they keep the location
+/// of their wrapper.
+///
 /// # Example
 ///
 /// ```rust
 /// use snafu::prelude::*;
 /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
 ///
-/// #[derive(Snafu)]
 /// #[stack_trace_debug]
+/// #[derive(Snafu)]
 /// enum MyError {
 ///     #[snafu(display("Something went wrong"))]
 ///     SomethingWrong,
 /// }
 ///
-/// let error = MyError::SomethingWrong;
+/// let error = SomethingWrongSnafu.build();
 /// let stack = error.virtual_stack();
 /// for frame in stack {
 ///     println!("{}", frame);
@@ -163,69 +952,1810 @@ pub use snafu_virtstack_macro::stack_trace_debug;
 pub trait VirtualStackTrace {
     /// Returns a virtual stack trace showing error propagation path.
     ///
-    /// Each [`StackFrame`] in the returned vector represents one step in the error
+    /// Each [`StackFrame`] in the returned [`Stack`] represents one step in the error
     /// propagation chain, from the outermost error context down to the root cause.
-    fn virtual_stack(&self) -> Vec<StackFrame>;
-}
+    fn virtual_stack(&self) -> Stack;
 
-/// Represents a single frame in the virtual stack trace.
-///
-/// Each frame captures the location where an error was propagated and the
-/// associated error message. This provides precise context about the error
-/// propagation path without the overhead of system backtraces.
-#[derive(Debug, Clone)]
-pub struct StackFrame {
-    /// Location where the error occurred or was propagated
-    pub location: &'static std::panic::Location<'static>,
-    /// Error message for this frame
-    pub message: String,
-}
+    /// Returns a lazy iterator over the virtual stack.
+    ///
+    /// Yields the same frames as [`virtual_stack`](VirtualStackTrace::virtual_stack) but only
+    /// formats each error message once its frame is reached, which avoids building the whole
+    /// stack when only the first few frames are of interest. The generated implementation
+    /// walks the source chain lazily; the default one iterates over `virtual_stack()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// // Only the outermost message is formatted
+    /// let first = error.virtual_stack_iter().next().unwrap();
+    /// assert_eq!(first.message, "Failed to read config");
+    /// ```
+    #[track_caller]
+    fn virtual_stack_iter(&self) -> StackIter<'_> {
+        StackIter::from_frames(self.virtual_stack())
+    }
 
-impl StackFrame {
-    /// Creates a new stack frame with the given location and message.
+    /// Returns an iterator over read-only views of the frames, for code that only inspects
+    /// the trace.
     ///
-    /// # Arguments
+    /// Frames are built lazily like with
+    /// [`virtual_stack_iter`](VirtualStackTrace::virtual_stack_iter), but each
+    /// [`FrameView`] may borrow its message instead of owning a copy:
     ///
-    /// * `location` - The location where the error occurred, typically from `std::panic::Location::caller()`
-    /// * `message` - A descriptive message for this error frame
+    /// - with `#[stack_trace_debug(cache)]` and the `std` feature, the frames stored by the
+    ///   error itself are lent out without being cloned
+    /// - otherwise a frame is still built for each view, but the message of a variant whose
+    ///   `display("...")` is a literal without placeholders is borrowed rather than
+    ///   formatted into a `String`
+    ///
+    /// All other messages, including those of foreign sources, are formatted into an owned
+    /// `String` as with [`virtual_stack_iter`](VirtualStackTrace::virtual_stack_iter).
+    /// [`virtual_stack`](VirtualStackTrace::virtual_stack) remains the way to get frames
+    /// that outlive the error.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use snafu_virtstack::StackFrame;
-    /// use std::panic::Location;
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
     ///
-    /// #[track_caller]
-    /// fn create_frame() -> StackFrame {
-    ///     StackFrame::new(
-    ///         Location::caller(),
-    ///         "Something went wrong".to_string()
-    ///     )
+    /// #[stack_trace_debug(cache)]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
     /// }
+    ///
+    /// let io = std::io::Error::other("config.json is missing");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// let messages: Vec<_> = error.frames().map(|frame| frame.message).collect();
+    /// assert_eq!(messages, ["Failed to read config", "config.json is missing"]);
     /// ```
-    pub fn new(location: &'static std::panic::Location<'static>, message: String) -> Self {
-        Self { location, message }
+    #[track_caller]
+    fn frames(&self) -> FrameIter<'_> {
+        FrameIter::new(self.virtual_stack_iter())
     }
-}
 
-impl std::fmt::Display for StackFrame {
-    /// Formats the stack frame showing the message and location information.
+    /// Returns `self` as a `'static` [`Error`](core::error::Error) trait object, for code
+    /// holding only a `Box<dyn VirtualStackTrace>` or `&(dyn VirtualStackTrace + 'static)`.
     ///
-    /// The format is: `{message} at {file}:{line}:{column}`
+    /// The generated implementation and [`MultiError`] return `Some(self)`, references and
+    /// boxes forward to the inner error. The default implementation returns `None`, since
+    /// a type implementing this trait by hand isn't required to be an `Error`.
     ///
-    /// # Example Output
+    /// The result can be downcast and passed to APIs expecting `'static` errors, such as
+    /// `Box<dyn Error>` sources. That requires `Self: 'static`, so errors borrowing
+    /// non-`'static` data, and trait objects with a shorter lifetime such as a plain
+    /// `&dyn VirtualStackTrace` parameter, can't be turned into an error this way.
     ///
-    /// ```text
-    /// Failed to read configuration file at src/config.rs:42:15
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// fn cause(error: &(dyn VirtualStackTrace + 'static)) -> Option<String> {
+    ///     let source = error.as_error()?.source()?;
+    ///     Some(source.to_string())
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error: Box<dyn VirtualStackTrace> =
+    ///     Box::new(Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err());
+    /// assert_eq!(cause(&*error).as_deref(), Some("disk on fire"));
+    ///
+    /// // The error keeps its type behind the trait object
+    /// let typed = error.as_error().unwrap().downcast_ref::<MyError>().unwrap();
+    /// assert!(matches!(typed, MyError::ReadConfig { .. }));
     /// ```
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{} at {}:{}:{}",
-            self.message,
-            self.location.file(),
-            self.location.line(),
-            self.location.column()
-        )
+    fn as_error(&self) -> Option<&(dyn core::error::Error + 'static)>
+    where
+        Self: 'static,
+    {
+        None
+    }
+
+    /// Returns the location the error captured where it was created, without building any
+    /// frame.
+    ///
+    /// The generated implementation returns the `location` field of the struct or of the
+    /// current variant, which SNAFU fills in at the `.context(...)`, `.fail()` or `.build()`
+    /// call site. Variants without a `location` field, such as tuple variants, and the
+    /// default implementation return `None`; their frames are located where the stack is
+    /// built instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("config.json is missing");
+    /// let (error, line) = (Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err(), line!());
+    ///
+    /// let location = error.location().unwrap();
+    /// assert_eq!(location.line(), line);
+    /// assert_eq!(location, error.first_frame().unwrap().location);
+    /// ```
+    fn location(&self) -> Option<Location> {
+        None
+    }
+
+    /// Returns the outermost frame, the one of the error itself, located where it was
+    /// created or propagated.
+    ///
+    /// Only this frame is built: the source chain isn't walked and no other message is
+    /// formatted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("config.json is missing");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// assert_eq!(error.first_frame().unwrap().message, "Failed to read config");
+    /// assert_eq!(error.last_frame().unwrap().message, "config.json is missing");
+    /// ```
+    #[track_caller]
+    fn first_frame(&self) -> Option<StackFrame> {
+        self.virtual_stack_iter().next()
+    }
+
+    /// Returns the innermost frame, the one of the deepest source.
+    ///
+    /// This is the same frame as [`root_cause`](VirtualStackTrace::root_cause), found by
+    /// walking the chain without collecting the frames before it.
+    #[track_caller]
+    fn last_frame(&self) -> Option<StackFrame> {
+        self.virtual_stack_iter().last()
+    }
+
+    /// Returns the deepest frame of the virtual stack, i.e. the root cause.
+    ///
+    /// Returns `None` if the stack is empty. If the source chain was truncated at the
+    /// maximum depth, e.g. because it contains a cycle, the synthetic
+    /// [`TRUNCATED_MESSAGE`] frame is skipped and the deepest frame that was visited is
+    /// returned instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::new(std::io::ErrorKind::NotFound, "config.json is missing");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// let root = error.root_cause().unwrap();
+    /// assert_eq!(root.message, "config.json is missing");
+    /// assert_eq!(error.root_message().as_deref(), Some("config.json is missing"));
+    /// ```
+    #[track_caller]
+    fn root_cause(&self) -> Option<StackFrame> {
+        self.virtual_stack_iter()
+            .filter(|frame| !frame.is_truncation())
+            .last()
+    }
+
+    /// Returns just the message of the [root cause](VirtualStackTrace::root_cause), which is
+    /// never the [`TRUNCATED_MESSAGE`] of a truncated chain.
+    #[track_caller]
+    fn root_message(&self) -> Option<String> {
+        self.root_cause().map(|frame| frame.message.into_owned())
+    }
+
+    /// Returns the number of frames in the virtual stack, i.e. `virtual_stack().len()`.
+    ///
+    /// The source chain is walked without formatting any error message, so this is cheap
+    /// enough for hot paths such as recording the chain depth as a metric. Only types using
+    /// `#[stack_trace_debug(dedup)]` or `dedup_os_errors` still format the messages, to
+    /// compare them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    /// assert_eq!(error.frame_count(), 2);
+    /// ```
+    #[track_caller]
+    fn frame_count(&self) -> usize {
+        self.virtual_stack_iter().count()
+    }
+
+    /// Returns the first frame, from the outermost error inwards, matching `predicate`.
+    ///
+    /// Frames are built lazily, so the rest of the chain isn't formatted once a match is
+    /// found.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// let frame = error.find_frame(|frame| frame.message.contains("fire"));
+    /// assert_eq!(frame.unwrap().message, "disk on fire");
+    /// ```
+    #[track_caller]
+    fn find_frame<P: FnMut(&StackFrame) -> bool>(&self, mut predicate: P) -> Option<StackFrame>
+    where
+        Self: Sized,
+    {
+        self.virtual_stack_iter().find(|frame| predicate(frame))
+    }
+
+    /// Returns the first frame whose file path contains `substr`, e.g. to skip frames
+    /// from third-party crates and jump to the first one in your own code.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Something went wrong"))]
+    ///     SomethingWrong,
+    /// }
+    ///
+    /// let error = SomethingWrongSnafu.build();
+    /// assert!(error.first_frame_in_file(".rs").is_some());
+    /// assert!(error.first_frame_in_file("third_party/").is_none());
+    /// ```
+    #[track_caller]
+    fn first_frame_in_file(&self, substr: &str) -> Option<StackFrame> {
+        self.virtual_stack_iter()
+            .find(|frame| frame.location.file().contains(substr))
+    }
+
+    /// Returns the virtual stack with each frame's message passed through `redact`, keeping
+    /// the locations and variants intact.
+    ///
+    /// Use it to strip user data such as file names or tokens from a trace before it
+    /// reaches a sink that must not see them, while rendering the unredacted trace
+    /// elsewhere. With the `regex` feature, `redact_secrets` is a
+    /// ready-made redactor for emails and tokens.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to open {path}"))]
+    ///     Open { path: String },
+    /// }
+    ///
+    /// let error = OpenSnafu { path: "/home/alice/notes.txt" }.build();
+    /// let stack = error.redacted(|message| message.replace("/home/alice", "~"));
+    /// assert_eq!(stack[0].message, "Failed to open ~/notes.txt");
+    /// assert_eq!(stack[0].location, error.virtual_stack()[0].location);
+    /// ```
+    #[track_caller]
+    fn redacted<F: Fn(&str) -> String>(&self, redact: F) -> Stack
+    where
+        Self: Sized,
+    {
+        self.virtual_stack_iter()
+            .map(|mut frame| {
+                frame.message = Cow::Owned(redact(&frame.message));
+                frame
+            })
+            .collect()
+    }
+
+    /// Iterates over `self` followed by each error of its `source()` chain, like
+    /// [`iter_chain`].
+    ///
+    /// Unlike [`virtual_stack`](VirtualStackTrace::virtual_stack), this yields the typed
+    /// errors themselves so they can be downcast. The chain is walked as is, so a cyclic
+    /// `source()` chain never ends.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// let kind = error.sources().find_map(|e| e.downcast_ref::<std::io::Error>()).map(|e| e.kind());
+    /// assert_eq!(kind, Some(std::io::ErrorKind::NotFound));
+    /// assert_eq!(error.sources().count(), 2);
+    /// ```
+    fn sources(&self) -> impl Iterator<Item = &(dyn core::error::Error + 'static)>
+    where
+        Self: core::error::Error + Sized + 'static,
+    {
+        iter_chain(self)
+    }
+
+    /// Collects every error of [`sources`](VirtualStackTrace::sources) that downcasts to
+    /// `T`, outermost first, including `self` if it is a `T`.
+    ///
+    /// Unlike a `find_map` over the sources, this keeps looking after the first match, for
+    /// chains where the same error type appears at several layers. Like `sources`, it never
+    /// returns on a cyclic `source()` chain.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum StoreError {
+    ///     #[snafu(display("Failed to write chunk"))]
+    ///     Write { source: std::io::Error },
+    /// }
+    ///
+    /// // A layer reporting the error of another one as an `io::Error`
+    /// let disk = std::io::Error::new(std::io::ErrorKind::StorageFull, "disk full");
+    /// let chunk = Err::<(), _>(disk).context(WriteSnafu).unwrap_err();
+    /// let io = std::io::Error::other(chunk);
+    /// let error = Err::<(), _>(io).context(WriteSnafu).unwrap_err();
+    ///
+    /// let kinds: Vec<_> = error.sources_of_type::<std::io::Error>().iter().map(|e| e.kind()).collect();
+    /// assert_eq!(kinds, [std::io::ErrorKind::Other, std::io::ErrorKind::StorageFull]);
+    /// ```
+    fn sources_of_type<T: core::error::Error + 'static>(&self) -> Vec<&T>
+    where
+        Self: core::error::Error + Sized + 'static,
+    {
+        self.sources()
+            .filter_map(|error| error.downcast_ref::<T>())
+            .collect()
+    }
+
+    /// Renders the virtual stack as numbered lines, laying out each frame with `formatter`.
+    ///
+    /// Each frame is written as `  {index}: {frame}` followed by a newline, matching the
+    /// frame lines of the generated [`Debug`](std::fmt::Debug) output when used with
+    /// [`CompactFormatter`]. The root cause comes first if [`set_root_first`] is enabled,
+    /// messages are stripped of the message of their source if [`set_clean_messages`] is,
+    /// and frames past the [`set_max_frames`] limit are summarized in a single line.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VerboseFormatter, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Something went wrong"))]
+    ///     SomethingWrong,
+    /// }
+    ///
+    /// let rendered = SomethingWrongSnafu.build().render_with(&VerboseFormatter);
+    /// assert!(rendered.starts_with("  0: [SomethingWrong] Something went wrong\n      at "));
+    /// ```
+    #[track_caller]
+    fn render_with(&self, formatter: &dyn FrameFormatter) -> String {
+        use core::fmt::Write;
+
+        let mut frames = self.virtual_stack();
+        if clean_messages() {
+            iter::clean_stack(&mut frames);
+        }
+        if root_first() {
+            frames.reverse();
+        }
+
+        // Writing to a String never fails
+        let mut rendered = String::new();
+        let mut hidden = format::HiddenFrames::new();
+        for (index, frame) in frames.iter().enumerate() {
+            if max_frames() == Some(index) {
+                let _ = hidden.flush(&mut rendered);
+                let _ = format::write_more_frames(&mut rendered, frames.len() - index);
+                return rendered;
+            }
+            if hidden.hide(frame) {
+                continue;
+            }
+            let _ = hidden.flush(&mut rendered);
+            let frame = format::Formatted { formatter, frame };
+            let _ = writeln!(rendered, "  {index}: {frame}");
+        }
+        let _ = hidden.flush(&mut rendered);
+        rendered
+    }
+
+    /// Renders the whole trace, header included, exactly as the [`Debug`](std::fmt::Debug)
+    /// output of the error prints it.
+    ///
+    /// Types using [`stack_trace_debug`] return their `Debug` output, so their arguments
+    /// such as `error_label` apply, as does a custom `Debug` kept with `no_debug`. Other
+    /// implementations get the layout of the generated output with the default labels.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Something went wrong"))]
+    ///     SomethingWrong,
+    /// }
+    ///
+    /// let error = SomethingWrongSnafu.build();
+    /// assert_eq!(error.render(), format!("{error:?}"));
+    /// ```
+    #[track_caller]
+    fn render(&self) -> String {
+        __private::render(self.virtual_stack_iter())
+    }
+
+    /// Wraps `self` in a [`StackDisplay`], which renders the numbered frames without the
+    /// `Error:` header of the generated [`Debug`](std::fmt::Debug) output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Something went wrong"))]
+    ///     SomethingWrong,
+    /// }
+    ///
+    /// let error = SomethingWrongSnafu.build();
+    /// let trace = error.stack_display().to_string();
+    /// assert!(trace.starts_with("  0: [SomethingWrong] Something went wrong at "));
+    /// ```
+    fn stack_display(&self) -> StackDisplay<'_>
+    where
+        Self: Sized,
+    {
+        StackDisplay(self)
+    }
+
+    /// Renders the whole chain on a single line, joining the frame messages with `": "` like
+    /// `anyhow`'s alternate `{:#}` display, e.g. `outer: middle: root`.
+    ///
+    /// Locations are left out, see
+    /// [`chain_oneline_with_locations`](VirtualStackTrace::chain_oneline_with_locations) to
+    /// keep them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    /// assert_eq!(error.chain_oneline(), "Failed to read config: disk on fire");
+    /// ```
+    #[track_caller]
+    fn chain_oneline(&self) -> String {
+        let mut line = String::new();
+        for (index, frame) in self.virtual_stack_iter().enumerate() {
+            if index > 0 {
+                line.push_str(": ");
+            }
+            line.push_str(&frame.message);
+        }
+        line
+    }
+
+    /// Like [`chain_oneline`](VirtualStackTrace::chain_oneline), with the `(file:line)` of
+    /// each frame appended to its message, e.g. `outer (src/main.rs:10): root (src/io.rs:4)`.
+    ///
+    /// Files are shortened according to the current [`PathStyle`].
+    #[track_caller]
+    fn chain_oneline_with_locations(&self) -> String {
+        use core::fmt::Write;
+
+        let mut line = String::new();
+        for (index, frame) in self.virtual_stack_iter().enumerate() {
+            if index > 0 {
+                line.push_str(": ");
+            }
+            let file = config::rendered_file(frame.location.file());
+            // Writing to a String never fails
+            let _ = write!(line, "{} ({file}:{})", frame.message, frame.location.line());
+        }
+        line
+    }
+
+    /// Renders the stack as a single [logfmt](https://brandur.org/logfmt) line, for log
+    /// pipelines that parse `key=value` pairs rather than JSON.
+    ///
+    /// The line starts with `msg`, the message of the outermost error, followed by the
+    /// `frame{index}_file`, `frame{index}_line` and `frame{index}_msg` of each frame. Empty
+    /// values and those containing spaces, `=`, quotes, backslashes or control characters
+    /// are quoted, escaping `"` and `\` with a backslash and writing newlines, carriage
+    /// returns and tabs as `\n`, `\r` and `\t`, and other control characters as `\u{..}`.
+    /// Files are shortened according to the current [`PathStyle`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk \"sda\" on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    /// let line = error.to_logfmt();
+    ///
+    /// assert!(line.starts_with("msg=\"Failed to read config\" frame0_file="));
+    /// assert!(line.ends_with(" frame1_msg=\"disk \\\"sda\\\" on fire\""));
+    /// ```
+    #[track_caller]
+    fn to_logfmt(&self) -> String {
+        use core::fmt::Write;
+
+        let mut line = String::new();
+        for (index, frame) in self.virtual_stack_iter().enumerate() {
+            let message = format::LogfmtValue(&frame.message);
+            let file = config::rendered_file(frame.location.file());
+            let file = format::LogfmtValue(&file);
+            let line_number = frame.location.line();
+            // Writing to a String never fails
+            if index == 0 {
+                let _ = write!(line, "msg={message} ");
+            } else {
+                line.push(' ');
+            }
+            let _ = write!(
+                line,
+                "frame{index}_file={file} frame{index}_line={line_number} frame{index}_msg={message}"
+            );
+        }
+        line
+    }
+
+    /// Returns the stack as structured [journald] fields, ready to be passed to
+    /// `sd_journal_send` or a systemd journal client.
+    ///
+    /// `MESSAGE` is the message of the outermost error, and `CODE_FILE` and `CODE_LINE` are
+    /// the location it was created or propagated at. Each frame follows as `VIRTSTACK_{index}`,
+    /// formatted like its [`Display`](core::fmt::Display) output. Files are shortened
+    /// according to the current [`PathStyle`]. An error without frames has no fields.
+    ///
+    /// [journald]: https://www.freedesktop.org/software/systemd/man/latest/systemd.journal-fields.html
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk full");
+    /// let (error, line) = (Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err(), line!());
+    /// let fields = error.journald_fields();
+    ///
+    /// assert_eq!(fields[0], ("MESSAGE".to_string(), "Failed to read config".to_string()));
+    /// assert_eq!(fields[1].0, "CODE_FILE");
+    /// assert_eq!(fields[2], ("CODE_LINE".to_string(), line.to_string()));
+    /// assert!(fields[3].1.starts_with("[ReadConfig] Failed to read config at "));
+    /// assert!(fields[4].1.starts_with("disk full at "));
+    /// ```
+    #[track_caller]
+    fn journald_fields(&self) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        for (index, frame) in self.virtual_stack_iter().enumerate() {
+            if index == 0 {
+                let file = config::rendered_file(frame.location.file());
+                fields.push(("MESSAGE".to_string(), frame.message.to_string()));
+                fields.push(("CODE_FILE".to_string(), file.to_string()));
+                fields.push(("CODE_LINE".to_string(), frame.location.line().to_string()));
+            }
+            fields.push((alloc::format!("VIRTSTACK_{index}"), frame.to_string()));
+        }
+        fields
+    }
+
+    /// Captures the virtual stack in a [`StackReport`] that owns all of its data.
+    ///
+    /// The report doesn't borrow from the error, so it can be sent to another thread or
+    /// over a channel, stored, or serialized with the `serde` feature after the error is
+    /// gone. Unlike [`report`](VirtualStackTrace::report), which wraps the error itself for
+    /// returning from `main`, this only keeps the outermost message and the frames.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk full");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    /// let report = error.to_report();
+    /// drop(error);
+    ///
+    /// let report = std::thread::spawn(move || report).join().unwrap();
+    /// assert_eq!(report.message, "Failed to read config");
+    /// assert_eq!(report.frames.len(), 2);
+    /// assert_eq!(report.frames[1].message, "disk full");
+    /// ```
+    #[track_caller]
+    fn to_report(&self) -> StackReport {
+        let frames: Vec<OwnedFrame> = self.virtual_stack_iter().map(OwnedFrame::from).collect();
+        let message = frames
+            .first()
+            .map_or_else(String::new, |frame| frame.message.clone());
+        StackReport { message, frames }
+    }
+
+    /// Returns an owned copy of the frames of the virtual stack, for keeping the trace of
+    /// an error that can't be cloned itself, such as a `Box<dyn VirtualStackTrace>`.
+    ///
+    /// This materializes the stack rather than cloning the error: the frames are collected
+    /// once, and the copy is unaffected by whatever happens to the error afterwards. Unlike
+    /// [`virtual_stack`](VirtualStackTrace::virtual_stack) it always returns a `Vec`, with
+    /// the `smallvec` feature as well. Use [`to_report`](VirtualStackTrace::to_report) for
+    /// frames that don't borrow `'static` data and can be deserialized.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk full");
+    /// let error: Box<dyn VirtualStackTrace> =
+    ///     Box::new(Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err());
+    /// let frames = error.clone_stack();
+    /// drop(error);
+    ///
+    /// assert_eq!(frames.len(), 2);
+    /// assert_eq!(frames[0].message, "Failed to read config");
+    /// ```
+    #[track_caller]
+    fn clone_stack(&self) -> Vec<StackFrame> {
+        self.virtual_stack_iter().collect()
+    }
+
+    /// Compares the stack of this error with the stack of `other` frame by frame, returning
+    /// the frames whose message or location differ, for tracking down where two error paths
+    /// diverge.
+    ///
+    /// A stack that is longer than the other one yields a [`FrameDiff::Removed`] or
+    /// [`FrameDiff::Added`] for each extra frame. [`render_diff`] turns the result into a
+    /// readable report. Requires the `testing` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, FrameDiff, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let read = |message: &str| {
+    ///     let io = std::io::Error::other(message.to_string());
+    ///     Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err()
+    /// };
+    /// let (left, right) = (read("disk full"), read("permission denied"));
+    ///
+    /// let diffs = left.diff(&right);
+    /// assert_eq!(diffs.len(), 1);
+    /// assert!(matches!(&diffs[0], FrameDiff::Changed { index: 1, .. }));
+    /// ```
+    #[cfg(feature = "testing")]
+    #[track_caller]
+    fn diff(&self, other: &dyn VirtualStackTrace) -> Vec<FrameDiff> {
+        testing::diff(self.virtual_stack_iter(), other.virtual_stack_iter())
+    }
+
+    /// Writes the full trace, exactly as rendered by the generated [`Debug`](core::fmt::Debug)
+    /// implementation, to `writer`, ending with a newline.
+    ///
+    /// The trace is rendered up front and handed to `writer` in a single
+    /// [`write_all`](std::io::Write::write_all) call.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Something went wrong"))]
+    ///     SomethingWrong,
+    /// }
+    ///
+    /// let error = SomethingWrongSnafu.build();
+    /// let mut out = Vec::new();
+    /// error.write_stack(&mut out).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), format!("{error:?}"));
+    /// ```
+    #[cfg(feature = "std")]
+    fn write_stack<W: std::io::Write + ?Sized>(&self, writer: &mut W) -> std::io::Result<()>
+    where
+        Self: core::fmt::Debug + Sized,
+    {
+        let mut rendered = alloc::format!("{self:?}");
+        if !rendered.ends_with('\n') {
+            rendered.push('\n');
+        }
+        writer.write_all(rendered.as_bytes())
+    }
+
+    /// Prints the full trace to stderr, like `eprintln!("{:?}", self)`.
+    ///
+    /// The output is written while holding the stderr lock, so traces printed from several
+    /// threads at once don't interleave. Errors writing to stderr are ignored.
+    #[cfg(feature = "std")]
+    fn eprint_stack(&self)
+    where
+        Self: core::fmt::Debug + Sized,
+    {
+        let _ = self.write_stack(&mut std::io::stderr().lock());
+    }
+
+    /// Prints the full trace to stdout, like `println!("{:?}", self)`.
+    ///
+    /// The output is written while holding the stdout lock, so traces printed from several
+    /// threads at once don't interleave. Errors writing to stdout are ignored.
+    #[cfg(feature = "std")]
+    fn print_stack(&self)
+    where
+        Self: core::fmt::Debug + Sized,
+    {
+        let _ = self.write_stack(&mut std::io::stdout().lock());
+    }
+
+    /// Wraps `self` in a [`Report`], which prints the trace to stderr and fails the process
+    /// when returned from `main`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, Report, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Something went wrong"))]
+    ///     SomethingWrong,
+    /// }
+    ///
+    /// fn main() -> Report<MyError> {
+    ///     SomethingWrongSnafu.build().report()
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    fn report(self) -> Report<Self>
+    where
+        Self: Sized,
+    {
+        Report::new(self)
+    }
+
+    /// Describes the error as an [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807)
+    /// `application/problem+json` object.
+    ///
+    /// `title` is the top-level message and `detail` the message of the
+    /// [root cause](VirtualStackTrace::root_cause). The `trace` extension member lists every
+    /// frame as a `{ message, file, line, column }` object, like the `Serialize`
+    /// implementation of [`StackFrame`]. `type` is `about:blank`, which callers can replace.
+    ///
+    /// Use [`to_problem_json_without_trace`](VirtualStackTrace::to_problem_json_without_trace)
+    /// for public-facing responses that shouldn't reveal the trace.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// let problem = error.to_problem_json();
+    /// assert_eq!(problem["title"], "Failed to read config");
+    /// assert_eq!(problem["detail"], "disk on fire");
+    /// assert_eq!(problem["trace"].as_array().unwrap().len(), 2);
+    /// ```
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    fn to_problem_json(&self) -> serde_json::Value {
+        let stack = self.virtual_stack();
+        let mut problem = problem_json(&stack);
+        problem["trace"] = serde_json::json!(stack);
+        problem
+    }
+
+    /// Like [`to_problem_json`](VirtualStackTrace::to_problem_json), without the `trace`
+    /// member.
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    fn to_problem_json_without_trace(&self) -> serde_json::Value {
+        problem_json(&self.virtual_stack())
+    }
+
+    /// Serializes the error as a JSON object for structured logs.
+    ///
+    /// The object holds the top-level `message`, the error's `code` as classified by
+    /// [`ErrorExt::status_code`], the `variant` of the outermost frame when it has one, and
+    /// the `stack` of frames in the format of the `Serialize` implementation of
+    /// [`StackFrame`]:
+    ///
+    /// ```json
+    /// {"code": "NotFound", "message": "...", "variant": "...", "stack": [{"message": "...", ...}]}
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails, which doesn't happen for the frames built by
+    /// this crate.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     #[status_code(NotFound)]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// let json: serde_json::Value = serde_json::from_str(&error.to_json().unwrap()).unwrap();
+    /// assert_eq!(json["message"], "Failed to read config");
+    /// assert_eq!(json["code"], "NotFound");
+    /// assert_eq!(json["variant"], "ReadConfig");
+    /// assert_eq!(json["stack"][1]["message"], "disk on fire");
+    /// ```
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    fn to_json(&self) -> Result<String, serde_json::Error>
+    where
+        Self: ErrorExt + Sized,
+    {
+        serde_json::to_string(&error_json(&self.virtual_stack(), self.status_code()))
+    }
+
+    /// Like [`to_json`](VirtualStackTrace::to_json), indented over multiple lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails, which doesn't happen for the frames built by
+    /// this crate.
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    fn to_json_pretty(&self) -> Result<String, serde_json::Error>
+    where
+        Self: ErrorExt + Sized,
+    {
+        serde_json::to_string_pretty(&error_json(&self.virtual_stack(), self.status_code()))
+    }
+
+    /// Emits the virtual stack as structured [`tracing`](https://docs.rs/tracing) events.
+    ///
+    /// The outermost frame is logged at `ERROR` level and every following frame at `DEBUG`,
+    /// each with `index`, `file`, `line` and `column` fields and the frame message as the
+    /// event message.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    /// error.emit_tracing();
+    /// ```
+    #[cfg(feature = "tracing")]
+    #[track_caller]
+    fn emit_tracing(&self) {
+        for (index, frame) in self.virtual_stack_iter().enumerate() {
+            let location = frame.location;
+            if index == 0 {
+                tracing::event!(
+                    tracing::Level::ERROR,
+                    index,
+                    file = location.file(),
+                    line = location.line(),
+                    column = location.column(),
+                    message = %frame.message,
+                );
+            } else {
+                tracing::event!(
+                    tracing::Level::DEBUG,
+                    index,
+                    file = location.file(),
+                    line = location.line(),
+                    column = location.column(),
+                    message = %frame.message,
+                );
+            }
+        }
+    }
+
+    /// Converts the error into an [`anyhow::Error`](https://docs.rs/anyhow) that keeps its
+    /// virtual stack.
+    ///
+    /// The rendered stack, introduced by `Virtual Stack Trace:`, is attached as context on
+    /// top of the error itself, so the resulting chain is the stack followed by the error and
+    /// its sources:
+    ///
+    /// - `{}` shows only the stack
+    /// - `{:#}` shows the stack, then every message of the chain separated by `": "`
+    /// - `{:?}` shows the stack, then the error and its sources under `Caused by:`
+    ///
+    /// The error stays in the chain, so `downcast_ref` to its type still works.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// fn run() -> anyhow::Result<()> {
+    ///     let io = std::io::Error::other("disk on fire");
+    ///     Err(io).context(ReadConfigSnafu).map_err(VirtualStackTrace::into_anyhow)
+    /// }
+    ///
+    /// let error = run().unwrap_err();
+    /// assert!(error.to_string().starts_with("Virtual Stack Trace:\n  0: [ReadConfig] Failed to read config at "));
+    /// assert!(error.downcast_ref::<MyError>().is_some());
+    /// ```
+    #[cfg(feature = "anyhow")]
+    #[track_caller]
+    fn into_anyhow(self) -> anyhow::Error
+    where
+        Self: core::error::Error + Send + Sync + Sized + 'static,
+    {
+        let trace = alloc::format!("Virtual Stack Trace:\n{}", StackDisplay(&self));
+        let trace = alloc::string::String::from(trace.trim_end());
+        anyhow::Error::new(self).context(trace)
+    }
+
+    /// Builds a Sentry event for this error, ready for `sentry::capture_event`.
+    ///
+    /// The event holds a single exception named after the error type, with the outermost
+    /// message as its value and a stack trace made of the virtual stack. Each frame becomes
+    /// a Sentry frame with the frame's file, line and column, and its message as the function
+    /// name. Sentry expects the innermost frame last, so the root cause ends the trace.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// let event = error.to_sentry_event();
+    /// let exception = &event.exception[0];
+    /// assert_eq!(exception.ty, "MyError");
+    /// let frames = &exception.stacktrace.as_ref().unwrap().frames;
+    /// assert_eq!(frames[0].function.as_deref(), Some("Failed to read config"));
+    /// assert_eq!(frames[1].function.as_deref(), Some("disk on fire"));
+    /// ```
+    #[cfg(feature = "sentry")]
+    #[track_caller]
+    fn to_sentry_event(&self) -> sentry_core::protocol::Event<'static>
+    where
+        Self: Sized,
+    {
+        let ty = sentry::short_type_name(core::any::type_name::<Self>());
+        sentry::event(ty, &self.virtual_stack())
+    }
+
+    /// Records this error as an `exception` event on an OpenTelemetry span.
+    ///
+    /// Following the semantic conventions for exceptions, the event carries these
+    /// attributes:
+    ///
+    /// - `exception.message`: the outermost message, i.e. the `Display` output of the error
+    /// - `exception.type`: the full type name of the error, such as `my_app::ConfigError`
+    /// - `exception.stacktrace`: the virtual stack, one `{index}: {frame}` line per frame
+    ///   from the outermost error to the root cause, with frames written as by their
+    ///   `Display`
+    ///
+    /// Like [`Span::record_error`](opentelemetry::trace::Span::record_error), nothing is
+    /// recorded on spans that aren't recording, and the span status is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use opentelemetry::trace::{Span, Status, Tracer};
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, VirtualStackTrace};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Failed to read config"))]
+    ///     ReadConfig { source: std::io::Error },
+    /// }
+    ///
+    /// let io = std::io::Error::other("disk on fire");
+    /// let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    ///
+    /// let mut span = opentelemetry::global::tracer("config").start("load_config");
+    /// error.record_otel(&mut span);
+    /// span.set_status(Status::error(error.to_string()));
+    /// ```
+    #[cfg(feature = "opentelemetry")]
+    #[track_caller]
+    fn record_otel(&self, span: &mut impl opentelemetry::trace::Span)
+    where
+        Self: Sized,
+    {
+        if span.is_recording() {
+            let ty = core::any::type_name::<Self>();
+            let attributes = otel::exception_attributes(ty, &self.virtual_stack());
+            span.add_event("exception", attributes);
+        }
+    }
+}
+
+/// References forward to the error they point to, so borrowed errors can be passed where a
+/// `VirtualStackTrace` is expected.
+impl<T: VirtualStackTrace + ?Sized> VirtualStackTrace for &T {
+    #[track_caller]
+    fn virtual_stack(&self) -> Stack {
+        (**self).virtual_stack()
+    }
+
+    #[track_caller]
+    fn virtual_stack_iter(&self) -> StackIter<'_> {
+        (**self).virtual_stack_iter()
+    }
+
+    fn as_error(&self) -> Option<&(dyn core::error::Error + 'static)>
+    where
+        Self: 'static,
+    {
+        (**self).as_error()
+    }
+
+    fn location(&self) -> Option<Location> {
+        (**self).location()
+    }
+
+    #[track_caller]
+    fn render(&self) -> String {
+        (**self).render()
+    }
+}
+
+/// Boxed errors, e.g. sources stored through `#[snafu(source(from(...)))]`, keep the
+/// virtual stack of the error they contain.
+impl<T: VirtualStackTrace + ?Sized> VirtualStackTrace for alloc::boxed::Box<T> {
+    #[track_caller]
+    fn virtual_stack(&self) -> Stack {
+        (**self).virtual_stack()
+    }
+
+    #[track_caller]
+    fn virtual_stack_iter(&self) -> StackIter<'_> {
+        (**self).virtual_stack_iter()
+    }
+
+    fn as_error(&self) -> Option<&(dyn core::error::Error + 'static)>
+    where
+        Self: 'static,
+    {
+        (**self).as_error()
+    }
+
+    fn location(&self) -> Option<Location> {
+        (**self).location()
+    }
+
+    #[track_caller]
+    fn render(&self) -> String {
+        (**self).render()
+    }
+}
+
+/// A source code location where an error was created or propagated.
+///
+/// This is a plain `file:line:column` triple that can be built from both
+/// [`std::panic::Location`] and [`snafu::Location`], the type injected into each variant
+/// by [`stack_trace_debug`].
+///
+/// Both carry the same information. SNAFU fills in its `snafu::Location` from
+/// `std::panic::Location::caller()` through `#[track_caller]`, and copies it into a type with
+/// public fields so it can be stored in errors and requested through the `Error` provider
+/// API. Either one, or a reference to a `snafu::Location` field, can be passed wherever an
+/// `impl Into<Location>` is expected, such as [`StackFrame::new`].
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::GenerateImplicitData;
+/// use snafu_virtstack::{Location, StackFrame};
+///
+/// let (captured, line) = (snafu::Location::generate(), line!());
+/// let frame = StackFrame::new(&captured, "Failed to read config");
+///
+/// assert_eq!(frame.location, Location::from(captured));
+/// assert_eq!(frame.location.line(), line);
+/// ```
+///
+/// [`snafu::Location`]: https://docs.rs/snafu/latest/snafu/struct.Location.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    file: &'static str,
+    line: u32,
+    column: u32,
+}
+
+impl Location {
+    /// Creates a new location from its parts.
+    pub const fn new(file: &'static str, line: u32, column: u32) -> Self {
+        Self { file, line, column }
+    }
+
+    /// Returns the location of the caller, following `#[track_caller]` annotations.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu_virtstack::Location;
+    ///
+    /// let (location, line) = (Location::caller(), line!());
+    /// assert_eq!(location.line(), line);
+    /// ```
+    #[track_caller]
+    pub fn caller() -> Self {
+        core::panic::Location::caller().into()
+    }
+
+    /// Returns the name of the source file.
+    pub const fn file(&self) -> &'static str {
+        self.file
+    }
+
+    /// Returns the line number.
+    pub const fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Returns the column number.
+    pub const fn column(&self) -> u32 {
+        self.column
+    }
+}
+
+impl From<&'static core::panic::Location<'static>> for Location {
+    fn from(location: &'static core::panic::Location<'static>) -> Self {
+        Self::new(location.file(), location.line(), location.column())
+    }
+}
+
+impl From<snafu::Location> for Location {
+    fn from(location: snafu::Location) -> Self {
+        Self::new(location.file, location.line, location.column)
+    }
+}
+
+impl From<&snafu::Location> for Location {
+    fn from(location: &snafu::Location) -> Self {
+        Self::from(*location)
+    }
+}
+
+impl core::fmt::Display for Location {
+    /// Formats the location as `{file}:{line}:{column}`, with the file trimmed according
+    /// to the current [`path_style`].
+    ///
+    /// Line and column are always plain ASCII digits separated by `:`, whatever the locale
+    /// or the flags of the format string.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let file = config::rendered_file(self.file);
+        write!(f, "{}:{}:{}", file, self.line, self.column)
+    }
+}
+
+/// Represents a single frame in the virtual stack trace.
+///
+/// Each frame captures the location where an error was propagated and the
+/// associated error message. This provides precise context about the error
+/// propagation path without the overhead of system backtraces.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StackFrame {
+    /// Location where the error occurred or was propagated
+    pub location: Location,
+    /// Error message for this frame
+    ///
+    /// Borrowed for constant messages, such as those of variants with a placeholder-free
+    /// `#[snafu(display("..."))]`, so they don't allocate.
+    pub message: Cow<'static, str>,
+    /// Name of the enum variant that produced this frame, if known
+    ///
+    /// Set on the frames of `#[stack_trace_debug]` enums; `None` for structs and for
+    /// sources walked through `Error::source()`.
+    pub variant: Option<&'static str>,
+    /// Module path of the `#[stack_trace_debug]` type that produced this frame, if known
+    ///
+    /// This is the module the error type is defined in, as given by `module_path!()`, not
+    /// the one it was propagated from, which [`location`](StackFrame::location) points at.
+    /// `None` for sources walked through `Error::source()`.
+    pub module: Option<&'static str>,
+    /// Name (or id, if unnamed) of the thread the error of this frame was created on
+    ///
+    /// Only recorded with the `thread` feature, and `None` for sources walked through
+    /// `Error::source()`.
+    #[cfg(feature = "thread")]
+    pub thread: Option<String>,
+    /// Time the error of this frame was created at
+    ///
+    /// Only recorded with the `timestamp` feature, and `None` for sources walked through
+    /// `Error::source()`.
+    #[cfg(feature = "timestamp")]
+    pub timestamp: Option<std::time::SystemTime>,
+    /// Structured `key=value` context attached to this frame, in insertion order
+    ///
+    /// Empty unless added with [`with_meta`](StackFrame::with_meta).
+    pub metadata: Vec<(String, String)>,
+}
+
+impl StackFrame {
+    /// Creates a new stack frame with the given location and message.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The location where the error occurred, typically from `std::panic::Location::caller()`
+    ///   or a captured [`snafu::Location`](https://docs.rs/snafu/latest/snafu/struct.Location.html)
+    /// * `message` - A descriptive message for this error frame, either an owned `String` or
+    ///   a `&'static str` that is stored without allocating
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu_virtstack::StackFrame;
+    /// use std::panic::Location;
+    ///
+    /// #[track_caller]
+    /// fn create_frame() -> StackFrame {
+    ///     StackFrame::new(
+    ///         Location::caller(),
+    ///         "Something went wrong".to_string()
+    ///     )
+    /// }
+    /// ```
+    pub fn new(location: impl Into<Location>, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            location: location.into(),
+            message: message.into(),
+            variant: None,
+            module: None,
+            #[cfg(feature = "thread")]
+            thread: None,
+            #[cfg(feature = "timestamp")]
+            timestamp: None,
+            metadata: Vec::new(),
+        }
+    }
+
+    /// Attributes the frame to the enum variant named `variant`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu_virtstack::{Location, StackFrame};
+    ///
+    /// let frame = StackFrame::new(Location::new("src/config.rs", 42, 15), "Failed".to_string())
+    ///     .with_variant("FileRead");
+    /// assert_eq!(frame.to_string(), "[FileRead] Failed at src/config.rs:42:15");
+    /// ```
+    pub fn with_variant(mut self, variant: &'static str) -> Self {
+        self.variant = Some(variant);
+        self
+    }
+
+    /// Attributes the frame to an error type defined in `module`, e.g. `module_path!()`.
+    pub fn with_module(mut self, module: &'static str) -> Self {
+        self.module = Some(module);
+        self
+    }
+
+    /// Attaches a `key=value` pair of structured context to the frame.
+    ///
+    /// Pairs are kept in the order they were added and shown by [`VerboseFormatter`] and
+    /// in the serialized frame, but not by the compact [`Display`](core::fmt::Display) form.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu_virtstack::{Location, StackFrame};
+    ///
+    /// let frame = StackFrame::new(Location::new("src/api.rs", 12, 9), "Request failed")
+    ///     .with_meta("request_id", "f3a9")
+    ///     .with_meta("attempt", 2);
+    /// assert_eq!(frame.metadata[1], ("attempt".to_string(), "2".to_string()));
+    /// ```
+    pub fn with_meta(mut self, key: impl Into<String>, value: impl core::fmt::Display) -> Self {
+        self.metadata.push((key.into(), value.to_string()));
+        self
+    }
+
+    /// Returns true for the synthetic frame ending a source chain truncated at the maximum
+    /// depth.
+    pub(crate) fn is_truncation(&self) -> bool {
+        self.variant.is_none() && self.message == TRUNCATED_MESSAGE
+    }
+
+    /// Renders the location as `{file name}:{line}:{column}`, leaving out the directories.
+    ///
+    /// Both `/` and `\` separate directories, so Windows paths are shortened as well. The
+    /// process-wide [`set_path_style`] and path trimmer don't apply to it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu_virtstack::{Location, StackFrame};
+    ///
+    /// let frame = StackFrame::new(Location::new("src/config.rs", 42, 15), "Failed");
+    /// assert_eq!(frame.short_location(), "config.rs:42:15");
+    /// ```
+    pub fn short_location(&self) -> String {
+        alloc::format!(
+            "{}:{}:{}",
+            PathStyle::FileName.trim(self.location.file()),
+            self.location.line(),
+            self.location.column()
+        )
+    }
+}
+
+impl core::fmt::Display for StackFrame {
+    /// Formats the stack frame showing the message and location information.
+    ///
+    /// The format is: `[{variant}] {message} at {file}:{line}:{column}`, as laid out by
+    /// [`CompactFormatter`]. The variant prefix is omitted when [`StackFrame::variant`]
+    /// is `None`.
+    ///
+    /// # Example Output
+    ///
+    /// ```text
+    /// [FileRead] Failed to read configuration file at src/config.rs:42:15
+    /// ```
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        CompactFormatter.format_frame(f, self)
+    }
+}
+
+/// An owned copy of a [`StackFrame`] that doesn't borrow any `'static` data.
+///
+/// [`StackFrame`] holds `&'static str` file names, which can't be produced by a
+/// deserializer. `OwnedFrame` mirrors its serialized shape with owned fields so that
+/// serialized frames can be read back, e.g. by a log processing tool.
+///
+/// With the `serde` feature enabled it implements both `Serialize` and `Deserialize`,
+/// using the same `message`, `file`, `line` and `column` fields as [`StackFrame`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedFrame {
+    /// Error message for this frame
+    pub message: String,
+    /// Source file where the error occurred or was propagated
+    pub file: String,
+    /// Line number within `file`
+    pub line: u32,
+    /// Column number within `line`
+    pub column: u32,
+    /// Name of the enum variant that produced this frame, if known
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub variant: Option<String>,
+    /// Module path of the error type that produced this frame, if known
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub module: Option<String>,
+    /// Name of the thread the error of this frame was created on, if recorded
+    #[cfg(feature = "thread")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub thread: Option<String>,
+    /// Time the error of this frame was created at as an RFC 3339 UTC timestamp, if recorded
+    #[cfg(feature = "timestamp")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub timestamp: Option<String>,
+    /// Structured `key=value` context attached to this frame, serialized as a JSON object
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty", with = "metadata_map")
+    )]
+    pub metadata: Vec<(String, String)>,
+}
+
+impl From<&StackFrame> for OwnedFrame {
+    fn from(frame: &StackFrame) -> Self {
+        Self {
+            variant: frame.variant.map(ToString::to_string),
+            module: frame.module.map(ToString::to_string),
+            #[cfg(feature = "thread")]
+            thread: frame.thread.clone(),
+            #[cfg(feature = "timestamp")]
+            timestamp: frame
+                .timestamp
+                .map(|time| timestamp::Rfc3339(time).to_string()),
+            metadata: frame.metadata.clone(),
+            message: frame.message.to_string(),
+            file: frame.location.file().to_string(),
+            line: frame.location.line(),
+            column: frame.location.column(),
+        }
+    }
+}
+
+impl From<StackFrame> for OwnedFrame {
+    fn from(frame: StackFrame) -> Self {
+        Self {
+            file: frame.location.file().to_string(),
+            line: frame.location.line(),
+            column: frame.location.column(),
+            message: frame.message.into_owned(),
+            variant: frame.variant.map(ToString::to_string),
+            module: frame.module.map(ToString::to_string),
+            #[cfg(feature = "thread")]
+            thread: frame.thread,
+            #[cfg(feature = "timestamp")]
+            timestamp: frame
+                .timestamp
+                .map(|time| timestamp::Rfc3339(time).to_string()),
+            metadata: frame.metadata,
+        }
+    }
+}
+
+impl core::fmt::Display for OwnedFrame {
+    /// Formats the frame exactly like [`StackFrame`] does, including the
+    /// [`max_message_len`] truncation of its message.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        format::write_compact(
+            f,
+            self.variant.as_deref(),
+            &self.message,
+            format_args!(
+                "{}:{}:{}",
+                config::rendered_file(&self.file),
+                self.line,
+                self.column
+            ),
+        )
+    }
+}
+
+/// A self-contained snapshot of the virtual stack of an error, returned by
+/// [`VirtualStackTrace::to_report`].
+///
+/// It owns its message and frames, so it is `Send`, `Sync` and `'static` whatever the
+/// error it was taken from, and outlives that error. With the `serde` feature it
+/// implements `Serialize` and `Deserialize` as a `{ message, frames }` object whose frames
+/// have the shape of serialized [`StackFrame`]s.
+///
+/// `Display` writes it like the generated [`Debug`](core::fmt::Debug) output with the
+/// default labels:
+///
+/// ```text
+/// Error: Failed to read config
+/// Virtual Stack Trace:
+///   0: [ReadConfig] Failed to read config at src/config.rs:42:15
+///   1: disk full at src/config.rs:42:15
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StackReport {
+    /// Message of the outermost error, i.e. its `Display` output
+    pub message: String,
+    /// Frames of the virtual stack, from the outermost error to the root cause
+    pub frames: Vec<OwnedFrame>,
+}
+
+impl core::fmt::Display for StackReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "Error: {}", self.message)?;
+        writeln!(f, "Virtual Stack Trace:")?;
+        for (index, frame) in self.frames.iter().enumerate() {
+            writeln!(f, "  {index}: {frame}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the standard members of an RFC 7807 problem from `stack`.
+#[cfg(feature = "serde")]
+fn problem_json(stack: &[StackFrame]) -> serde_json::Value {
+    let title = stack.first().map(|frame| &*frame.message);
+    let detail = stack.last().map(|frame| &*frame.message);
+    serde_json::json!({
+        "type": "about:blank",
+        "title": title,
+        "detail": detail,
+    })
+}
+
+/// Builds the object serialized by [`VirtualStackTrace::to_json`].
+#[cfg(feature = "serde")]
+fn error_json(stack: &[StackFrame], code: StatusCode) -> serde_json::Value {
+    let outermost = stack.first();
+    let mut json = serde_json::json!({
+        "code": code.as_str(),
+        "message": outermost.map(|frame| &*frame.message),
+    });
+    if let Some(variant) = outermost.and_then(|frame| frame.variant) {
+        json["variant"] = serde_json::json!(variant);
+    }
+    json["stack"] = serde_json::json!(stack);
+    json
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for StackFrame {
+    /// Serializes the frame as a flat `{ message, file, line, column }` map, with
+    /// additional `variant`, `module`, `thread`, `timestamp` and `metadata` entries when they
+    /// are known. The timestamp is serialized as an RFC 3339 UTC string and the metadata as
+    /// an object of its `key=value` pairs.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        #[cfg(feature = "thread")]
+        let thread = self.thread.as_deref();
+        #[cfg(not(feature = "thread"))]
+        let thread: Option<&str> = None;
+        #[cfg(feature = "timestamp")]
+        let timestamp = self.timestamp.map(timestamp::Rfc3339);
+        #[cfg(not(feature = "timestamp"))]
+        let timestamp: Option<&str> = None;
+
+        let len = 4
+            + usize::from(self.variant.is_some())
+            + usize::from(self.module.is_some())
+            + usize::from(thread.is_some())
+            + usize::from(timestamp.is_some())
+            + usize::from(!self.metadata.is_empty());
+        let mut state = serializer.serialize_struct("StackFrame", len)?;
+        state.serialize_field("message", &self.message)?;
+        state.serialize_field("file", self.location.file())?;
+        state.serialize_field("line", &self.location.line())?;
+        state.serialize_field("column", &self.location.column())?;
+        match self.variant {
+            Some(variant) => state.serialize_field("variant", variant)?,
+            None => state.skip_field("variant")?,
+        }
+        match self.module {
+            Some(module) => state.serialize_field("module", module)?,
+            None => state.skip_field("module")?,
+        }
+        match thread {
+            Some(thread) => state.serialize_field("thread", thread)?,
+            None => state.skip_field("thread")?,
+        }
+        match timestamp {
+            Some(timestamp) => state.serialize_field("timestamp", &timestamp.to_string())?,
+            None => state.skip_field("timestamp")?,
+        }
+        if self.metadata.is_empty() {
+            state.skip_field("metadata")?;
+        } else {
+            state.serialize_field("metadata", &metadata_map::Pairs(&self.metadata))?;
+        }
+        state.end()
+    }
+}
+
+/// (De)serializes frame metadata as an object while keeping the order of its pairs.
+#[cfg(feature = "serde")]
+mod metadata_map {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    pub(crate) struct Pairs<'a>(pub(crate) &'a [(String, String)]);
+
+    impl serde::Serialize for Pairs<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(self.0.iter().map(|(key, value)| (key, value)))
+        }
+    }
+
+    pub(crate) fn serialize<S: serde::Serializer>(
+        pairs: &[(String, String)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&Pairs(pairs), serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(String, String)>, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Vec<(String, String)>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("a map of metadata strings")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut pairs = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(pair) = map.next_entry()? {
+                    pairs.push(pair);
+                }
+                Ok(pairs)
+            }
+        }
+
+        deserializer.deserialize_map(Visitor)
     }
 }
@@ -0,0 +1,181 @@
+//! Aggregating several independent errors into one.
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::format::Truncated;
+use crate::{CompactFormatter, FrameFormatter, Location, Stack, StackFrame, VirtualStackTrace};
+
+/// An error made of several independent errors, such as the failed fields of a validation.
+///
+/// A source chain can only describe a single cause, so `MultiError` instead keeps every
+/// child error together with its own virtual stack. Its
+/// [`virtual_stack`](VirtualStackTrace::virtual_stack) starts with a frame for the aggregate
+/// itself, located where it was created, followed by the frames of each child in order.
+/// The [`Debug`](core::fmt::Debug) output draws every child as a separate branch:
+///
+/// ```text
+/// Error: Invalid user
+/// Virtual Stack Trace:
+///   0: Invalid user at src/user.rs:12:21
+///   ├─ 0: [InvalidEmail] Invalid email "bob" at src/user.rs:14:22
+///   │  1: Missing @ at src/user.rs:14:22
+///   └─ 0: [MissingName] Name is required at src/user.rs:17:22
+/// ```
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, MultiError, VirtualStackTrace};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum FieldError {
+///     #[snafu(display("Name is required"))]
+///     MissingName,
+///
+///     #[snafu(display("Age {age} is out of range"))]
+///     InvalidAge { age: i32 },
+/// }
+///
+/// fn validate(name: &str, age: i32) -> Result<(), MultiError> {
+///     let mut errors = MultiError::new("Invalid user");
+///     if name.is_empty() {
+///         errors.push(MissingNameSnafu.build());
+///     }
+///     if !(0..150).contains(&age) {
+///         errors.push(InvalidAgeSnafu { age }.build());
+///     }
+///     errors.into_result()
+/// }
+///
+/// assert!(validate("Bob", 42).is_ok());
+///
+/// let error = validate("", -1).unwrap_err();
+/// assert_eq!(error.len(), 2);
+/// let messages: Vec<_> = error.virtual_stack().iter().map(|frame| frame.message.clone()).collect();
+/// assert_eq!(messages, ["Invalid user", "Name is required", "Age -1 is out of range"]);
+/// ```
+pub struct MultiError {
+    message: Cow<'static, str>,
+    location: Location,
+    errors: Vec<Box<dyn VirtualStackTrace + Send + Sync>>,
+}
+
+impl MultiError {
+    /// Creates an empty aggregate described by `message`, located at the caller.
+    #[track_caller]
+    pub fn new(message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            message: message.into(),
+            location: Location::caller(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Adds `error` as the next child.
+    pub fn push(&mut self, error: impl VirtualStackTrace + Send + Sync + 'static) {
+        self.errors.push(Box::new(error));
+    }
+
+    /// Returns the number of child errors.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Returns true if no child error has been added.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the child errors in the order they were added.
+    pub fn errors(&self) -> impl Iterator<Item = &(dyn VirtualStackTrace + Send + Sync)> {
+        self.errors.iter().map(|error| &**error)
+    }
+
+    /// Returns `Ok(())` if no child error has been added, and the aggregate otherwise.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+
+    fn root_frame(&self) -> StackFrame {
+        StackFrame::new(self.location, self.message.clone())
+    }
+}
+
+impl core::fmt::Display for MultiError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl core::fmt::Debug for MultiError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let header = Truncated::global(&self.message);
+        if !crate::is_enabled() {
+            return write!(f, "Error: {header}");
+        }
+
+        writeln!(f, "Error: {header}")?;
+        writeln!(f, "Virtual Stack Trace:")?;
+        write_branch_frame(f, "  ", 0, &self.root_frame())?;
+
+        let last = self.errors.len().saturating_sub(1);
+        for (child, error) in self.errors.iter().enumerate() {
+            let (first, rest) = if child == last {
+                ("  └─ ", "     ")
+            } else {
+                ("  ├─ ", "  │  ")
+            };
+            for (index, frame) in error.virtual_stack_iter().enumerate() {
+                let indent = if index == 0 { first } else { rest };
+                write_branch_frame(f, indent, index, &frame)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes one line of the [`MultiError`] tree.
+fn write_branch_frame(
+    f: &mut core::fmt::Formatter<'_>,
+    indent: &str,
+    index: usize,
+    frame: &StackFrame,
+) -> core::fmt::Result {
+    write!(f, "{indent}{index}: ")?;
+    CompactFormatter.format_frame(f, frame)?;
+    writeln!(f)
+}
+
+impl core::error::Error for MultiError {}
+
+impl VirtualStackTrace for MultiError {
+    fn virtual_stack(&self) -> Stack {
+        let children = self
+            .errors
+            .iter()
+            .flat_map(|error| error.virtual_stack_iter());
+        core::iter::once(self.root_frame())
+            .chain(children)
+            .collect()
+    }
+
+    fn as_error(&self) -> Option<&(dyn core::error::Error + 'static)>
+    where
+        Self: 'static,
+    {
+        Some(self)
+    }
+
+    fn location(&self) -> Option<Location> {
+        Some(self.location)
+    }
+
+    fn render(&self) -> String {
+        alloc::format!("{self:?}")
+    }
+}
@@ -0,0 +1,39 @@
+//! Recording virtual stacks as OpenTelemetry exception events.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use opentelemetry::KeyValue;
+
+use crate::StackFrame;
+
+/// Key of the attribute holding the outermost message.
+pub(crate) const MESSAGE_KEY: &str = "exception.message";
+/// Key of the attribute holding the type name of the error.
+pub(crate) const TYPE_KEY: &str = "exception.type";
+/// Key of the attribute holding the rendered virtual stack.
+pub(crate) const STACKTRACE_KEY: &str = "exception.stacktrace";
+
+/// Builds the attributes of the `exception` event recorded for an error of type `ty` with
+/// the frames of `stack`, following the OpenTelemetry semantic conventions for exceptions.
+pub(crate) fn exception_attributes(ty: &'static str, stack: &[StackFrame]) -> Vec<KeyValue> {
+    let message = stack.first().map_or("", |frame| &frame.message);
+    alloc::vec![
+        KeyValue::new(MESSAGE_KEY, String::from(message)),
+        KeyValue::new(TYPE_KEY, ty),
+        KeyValue::new(STACKTRACE_KEY, stacktrace(stack)),
+    ]
+}
+
+/// Renders one `{index}: {frame}` line per frame, outermost first.
+fn stacktrace(stack: &[StackFrame]) -> String {
+    let mut rendered = String::new();
+    for (index, frame) in stack.iter().enumerate() {
+        if index > 0 {
+            rendered.push('\n');
+        }
+        let _ = write!(rendered, "{index}: {frame}");
+    }
+    rendered
+}
@@ -0,0 +1,76 @@
+//! Turning panics into errors.
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use core::any::Any;
+use std::panic::AssertUnwindSafe;
+
+use snafu::Snafu;
+
+use crate::stack_trace_debug;
+
+/// A panic caught by [`catch_panic`].
+///
+/// Its single frame is located where the panic was caught and shows the panic message, so
+/// panicking code can be reported through the same path as any other error. The panic
+/// payload itself isn't kept, since it is neither `Sync` nor, in general, printable.
+#[stack_trace_debug(crate = "crate")]
+#[derive(Snafu)]
+#[snafu(display("Panicked: {message}"))]
+pub struct PanicError {
+    message: String,
+}
+
+impl PanicError {
+    /// Returns the message the code panicked with.
+    ///
+    /// Payloads that are neither a `&str` nor a `String`, e.g. from `std::panic::panic_any`,
+    /// are described as `Box<dyn Any>`, like the default panic hook does.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Runs `f`, turning a panic into a [`PanicError`] located at the caller.
+///
+/// This uses [`std::panic::catch_unwind`], so its caveats apply: panics are only caught when
+/// the program unwinds rather than aborting on panic, and the panic hook still runs, which
+/// by default prints the message to stderr. `f` is treated as unwind safe; don't keep using
+/// state it may have left inconsistent after a panic.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu_virtstack::{catch_panic, VirtualStackTrace};
+///
+/// let (result, line) = (catch_panic(|| -> u32 { panic!("job {} failed", 7) }), line!());
+/// let error = result.unwrap_err();
+///
+/// assert_eq!(error.message(), "job 7 failed");
+/// let frame = &error.virtual_stack()[0];
+/// assert_eq!(frame.message, "Panicked: job 7 failed");
+/// assert_eq!(frame.location.line(), line);
+///
+/// assert_eq!(catch_panic(|| 42).unwrap(), 42);
+/// ```
+#[track_caller]
+pub fn catch_panic<T, F: FnOnce() -> T>(f: F) -> Result<T, PanicError> {
+    match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => Ok(value),
+        Err(payload) => PanicSnafu {
+            message: payload_message(payload),
+        }
+        .fail(),
+    }
+}
+
+/// Extracts the message of a panic payload.
+fn payload_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast_ref::<&'static str>() {
+            Some(message) => message.to_string(),
+            None => String::from("Box<dyn Any>"),
+        },
+    }
+}
@@ -0,0 +1,89 @@
+//! Returning virtual stack traces from `main`.
+
+use std::process::{ExitCode, Termination};
+
+use crate::VirtualStackTrace;
+
+/// Outcome of a program that prints the virtual stack trace of its error on exit.
+///
+/// Returning `Report<E>` from `main` prints the error to stderr exactly like its generated
+/// [`Debug`](core::fmt::Debug) output and exits with [`ExitCode::FAILURE`]. A successful
+/// report exits with [`ExitCode::SUCCESS`] without printing anything.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, Report};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("Failed to read config"))]
+///     ReadConfig { source: std::io::Error },
+/// }
+///
+/// fn run() -> Result<(), MyError> {
+///     std::fs::read_to_string("config.toml").context(ReadConfigSnafu)?;
+///     Ok(())
+/// }
+///
+/// fn main() -> Report<MyError> {
+///     Report::capture(run)
+/// }
+/// ```
+pub struct Report<E>(Result<(), E>);
+
+impl<E> Report<E> {
+    /// Creates a report of a failed program.
+    pub fn new(error: E) -> Self {
+        Self(Err(error))
+    }
+
+    /// Creates a report of a successful program.
+    pub fn ok() -> Self {
+        Self(Ok(()))
+    }
+
+    /// Runs `body` and reports its outcome, allowing `?` inside of it.
+    pub fn capture(body: impl FnOnce() -> Result<(), E>) -> Self {
+        Self(body())
+    }
+
+    /// Returns the reported error, if any.
+    pub fn error(&self) -> Option<&E> {
+        self.0.as_ref().err()
+    }
+
+    /// Converts the report back into the result it was built from.
+    pub fn into_result(self) -> Result<(), E> {
+        self.0
+    }
+}
+
+impl<E> From<Result<(), E>> for Report<E> {
+    fn from(result: Result<(), E>) -> Self {
+        Self(result)
+    }
+}
+
+impl<E: core::fmt::Debug> core::fmt::Debug for Report<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.0 {
+            Ok(()) => f.write_str("Ok"),
+            Err(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<E: VirtualStackTrace + core::fmt::Debug> Termination for Report<E> {
+    fn report(self) -> ExitCode {
+        match self.0 {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => {
+                error.eprint_stack();
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Conversion of virtual stacks into Sentry events.
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use sentry_core::protocol::{Event, Exception, Frame, Level, Stacktrace};
+
+use crate::StackFrame;
+
+/// Builds an error event with a single exception of type `ty` whose stack trace lists the
+/// frames of `stack`.
+///
+/// Sentry expects the most recent call last, so the outermost frame comes first and the
+/// root cause last, which is the order of the virtual stack itself.
+pub(crate) fn event(ty: &str, stack: &[StackFrame]) -> Event<'static> {
+    let outermost = stack.first();
+    let frames: Vec<Frame> = stack.iter().map(frame).collect();
+    let exception = Exception {
+        ty: ty.to_string(),
+        value: outermost.map(|frame| frame.message.to_string()),
+        module: outermost
+            .and_then(|frame| frame.module)
+            .map(ToString::to_string),
+        stacktrace: Some(Stacktrace {
+            frames,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    Event {
+        level: Level::Error,
+        exception: alloc::vec![exception].into(),
+        ..Default::default()
+    }
+}
+
+/// Maps a frame onto a Sentry frame, using its message as the function name.
+fn frame(frame: &StackFrame) -> Frame {
+    Frame {
+        function: Some(frame.message.to_string()),
+        module: frame.module.map(ToString::to_string),
+        filename: Some(frame.location.file().to_string()),
+        lineno: Some(frame.location.line().into()),
+        colno: Some(frame.location.column().into()),
+        vars: frame
+            .metadata
+            .iter()
+            .map(|(key, value)| (key.clone(), value.as_str().into()))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Returns the last segment of the path of `type_name`, ignoring generic arguments.
+pub(crate) fn short_type_name(type_name: &str) -> &str {
+    let path = type_name.split('<').next().unwrap_or(type_name);
+    path.rsplit("::").next().unwrap_or(path)
+}
@@ -0,0 +1,247 @@
+//! Coarse error classification modeled on GreptimeDB's `ErrorExt`.
+
+use alloc::string::{String, ToString};
+
+/// Broad category of an error, used to map errors onto protocol-level codes.
+///
+/// Each variant of a [`stack_trace_debug`](crate::stack_trace_debug) error picks its code with
+/// `#[status_code(...)]`, defaulting to [`StatusCode::Internal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StatusCode {
+    /// An unexpected failure inside the application
+    Internal,
+    /// The cause of the error is unknown
+    Unknown,
+    /// The requested operation isn't supported
+    Unsupported,
+    /// The caller supplied invalid arguments
+    InvalidArguments,
+    /// The requested resource doesn't exist
+    NotFound,
+    /// The resource being created already exists
+    AlreadyExists,
+    /// The caller isn't authenticated
+    Unauthorized,
+    /// The caller is authenticated but not allowed to perform the operation
+    PermissionDenied,
+    /// The operation was cancelled
+    Cancelled,
+    /// The operation didn't complete in time
+    Timeout,
+    /// The caller exceeded a rate limit or quota
+    RateLimited,
+    /// A dependency or the service itself is temporarily unavailable
+    Unavailable,
+}
+
+impl StatusCode {
+    /// Returns the name of the status code, e.g. `"NotFound"`.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Internal => "Internal",
+            Self::Unknown => "Unknown",
+            Self::Unsupported => "Unsupported",
+            Self::InvalidArguments => "InvalidArguments",
+            Self::NotFound => "NotFound",
+            Self::AlreadyExists => "AlreadyExists",
+            Self::Unauthorized => "Unauthorized",
+            Self::PermissionDenied => "PermissionDenied",
+            Self::Cancelled => "Cancelled",
+            Self::Timeout => "Timeout",
+            Self::RateLimited => "RateLimited",
+            Self::Unavailable => "Unavailable",
+        }
+    }
+}
+
+impl core::fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Severity of an error, used to route alerts.
+///
+/// Each variant of a [`stack_trace_debug`](crate::stack_trace_debug) error picks its level
+/// with `#[level(...)]`, defaulting to [`Level::Error`]. Levels are ordered from the least
+/// to the most severe, so thresholds can be checked with `>=`. With the `color` feature the
+/// `Error:` label of the generated [`Debug`](core::fmt::Debug) output is yellow for
+/// warnings and red for fatal errors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Level {
+    /// A failure that is expected to recover on its own, such as a retried request
+    Warn,
+    /// A failure that needs attention
+    #[default]
+    Error,
+    /// A failure the process or a critical component can't continue after
+    Fatal,
+}
+
+impl Level {
+    /// Returns the name of the level, e.g. `"Warn"`.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Warn => "Warn",
+            Self::Error => "Error",
+            Self::Fatal => "Fatal",
+        }
+    }
+}
+
+impl core::fmt::Display for Level {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Extension trait exposing the [`StatusCode`] of an error.
+///
+/// This trait is automatically implemented by the [`stack_trace_debug`](crate::stack_trace_debug)
+/// proc macro attribute from per-variant `#[status_code(...)]` attributes.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, ErrorExt, StatusCode};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("User {id} not found"))]
+///     #[status_code(NotFound)]
+///     UserNotFound { id: u64 },
+///
+///     #[snafu(display("Database connection failed"))]
+///     DatabaseConnection,
+/// }
+///
+/// assert_eq!(UserNotFoundSnafu { id: 7u64 }.build().status_code(), StatusCode::NotFound);
+/// assert_eq!(DatabaseConnectionSnafu.build().status_code(), StatusCode::Internal);
+/// ```
+pub trait ErrorExt {
+    /// Returns the status code classifying this error.
+    fn status_code(&self) -> StatusCode;
+
+    /// Returns the severity of this error, for alert routing.
+    ///
+    /// Defaults to [`Level::Error`]. Variants annotated with `#[level(Warn)]` or
+    /// `#[level(Fatal)]` return that level instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, ErrorExt, Level};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("Cache miss for {key}"))]
+    ///     #[level(Warn)]
+    ///     CacheMiss { key: String },
+    ///
+    ///     #[snafu(display("Database connection failed"))]
+    ///     DatabaseConnection,
+    ///
+    ///     #[snafu(display("Storage is corrupted"))]
+    ///     #[level(Fatal)]
+    ///     Corrupted,
+    /// }
+    ///
+    /// assert_eq!(CacheMissSnafu { key: "user:7" }.build().level(), Level::Warn);
+    /// assert_eq!(DatabaseConnectionSnafu.build().level(), Level::Error);
+    /// assert!(CorruptedSnafu.build().level() >= Level::Error);
+    /// ```
+    fn level(&self) -> Level {
+        Level::Error
+    }
+
+    /// Returns the message that is safe to show to clients, without any virtual stack.
+    ///
+    /// Defaults to the top-level [`Display`](core::fmt::Display) string. Variants annotated
+    /// with `#[external_msg("...")]` return that text instead, keeping their detailed display
+    /// for the internal trace. The `tonic` and `http` integrations send this message.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, ErrorExt};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("User {id} not found"))]
+    ///     UserNotFound { id: u64 },
+    ///
+    ///     #[snafu(display("Query on replica {replica} timed out"))]
+    ///     #[external_msg("Service temporarily unavailable")]
+    ///     QueryTimeout { replica: String },
+    /// }
+    ///
+    /// assert_eq!(UserNotFoundSnafu { id: 7u64 }.build().output_msg(), "User 7 not found");
+    ///
+    /// let error = QueryTimeoutSnafu { replica: "db-3" }.build();
+    /// assert_eq!(error.output_msg(), "Service temporarily unavailable");
+    /// assert_eq!(error.to_string(), "Query on replica db-3 timed out");
+    /// ```
+    fn output_msg(&self) -> String
+    where
+        Self: core::fmt::Display,
+    {
+        self.to_string()
+    }
+
+    /// Converts the error into a [`tonic::Status`] with the gRPC code matching its
+    /// [`status_code`](ErrorExt::status_code) and its [`output_msg`](ErrorExt::output_msg).
+    ///
+    /// Only the outermost message is exposed; the virtual stack stays private. Use
+    /// [`to_tonic_status_with_stack`](ErrorExt::to_tonic_status_with_stack) to send it along.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use snafu::prelude::*;
+    /// use snafu_virtstack::{stack_trace_debug, ErrorExt};
+    ///
+    /// #[stack_trace_debug]
+    /// #[derive(Snafu)]
+    /// enum MyError {
+    ///     #[snafu(display("User {id} not found"))]
+    ///     #[status_code(NotFound)]
+    ///     UserNotFound { id: u64 },
+    /// }
+    ///
+    /// let status = UserNotFoundSnafu { id: 7u64 }.build().to_tonic_status();
+    /// assert_eq!(status.code(), tonic::Code::NotFound);
+    /// assert_eq!(status.message(), "User 7 not found");
+    /// ```
+    #[cfg(feature = "tonic")]
+    fn to_tonic_status(&self) -> tonic::Status
+    where
+        Self: core::fmt::Display,
+    {
+        tonic::Status::new(self.status_code().into(), self.output_msg())
+    }
+
+    /// Like [`to_tonic_status`](ErrorExt::to_tonic_status), additionally attaching the
+    /// rendered virtual stack as binary metadata under [`TONIC_STACK_METADATA_KEY`].
+    ///
+    /// The stack reveals internal messages and source locations, so only send it to
+    /// trusted clients.
+    ///
+    /// [`TONIC_STACK_METADATA_KEY`]: crate::TONIC_STACK_METADATA_KEY
+    #[cfg(feature = "tonic")]
+    #[track_caller]
+    fn to_tonic_status_with_stack(&self) -> tonic::Status
+    where
+        Self: crate::VirtualStackTrace + core::fmt::Display,
+    {
+        let stack = self.render_with(&crate::CompactFormatter);
+        crate::grpc::status_with_stack(self.status_code(), self.output_msg(), &stack)
+    }
+}
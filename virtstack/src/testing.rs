@@ -0,0 +1,225 @@
+//! Assertions on the shape of virtual stacks and comparisons between them.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::{StackFrame, StackIter, VirtualStackTrace};
+
+/// Asserts that the virtual stack of an error has the given frames, outermost first.
+///
+/// Each expected frame is either a string the frame's message must contain, or
+/// `"Variant" contains "..."`, which also requires the frame to come from that enum variant.
+/// The stack must have exactly as many frames as listed. On a mismatch the assertion panics
+/// with the expected frames and the full stack.
+///
+/// Requires the `testing` feature, which is meant to be enabled in `[dev-dependencies]`.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{assert_stack, stack_trace_debug};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum ConfigError {
+///     #[snafu(display("Failed to read {path}"))]
+///     FileRead { path: String, source: std::io::Error },
+/// }
+///
+/// let error = Err::<(), _>(std::io::Error::other("io error"))
+///     .context(FileReadSnafu { path: "config.json" })
+///     .unwrap_err();
+///
+/// assert_stack!(error, ["FileRead" contains "config.json", "io error"]);
+/// ```
+///
+/// A mismatch reports both sides:
+///
+/// ```text
+/// assertion `stack` failed: frame 1 doesn't match
+///   expected: "timed out"
+///      stack:
+///   0: [FileRead] Failed to read config.json at src/config.rs:42:15
+///   1: io error at src/config.rs:42:15
+/// ```
+#[macro_export]
+macro_rules! assert_stack {
+    ($error:expr, [$($first:literal $(contains $needle:literal)?),* $(,)?] $(,)?) => {
+        $crate::__private::assert_stack(
+            &$error,
+            &[$($crate::assert_stack!(@frame $first $(contains $needle)?)),*],
+        )
+    };
+    (@frame $variant:literal contains $needle:literal) => {
+        $crate::__private::ExpectedFrame {
+            variant: ::core::option::Option::Some($variant),
+            message: $needle,
+        }
+    };
+    (@frame $needle:literal) => {
+        $crate::__private::ExpectedFrame {
+            variant: ::core::option::Option::None,
+            message: $needle,
+        }
+    };
+}
+
+/// A frame expected by [`assert_stack!`].
+pub struct ExpectedFrame {
+    pub variant: Option<&'static str>,
+    pub message: &'static str,
+}
+
+impl core::fmt::Display for ExpectedFrame {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.variant {
+            Some(variant) => write!(f, "{variant:?} contains {:?}", self.message),
+            None => write!(f, "{:?}", self.message),
+        }
+    }
+}
+
+/// Checks the frames of `error` against `expected`, for [`assert_stack!`].
+#[track_caller]
+pub fn assert_stack(error: &dyn VirtualStackTrace, expected: &[ExpectedFrame]) {
+    let stack = error.virtual_stack();
+    let mismatch = stack.iter().zip(expected).position(|(frame, expected)| {
+        !frame.message.contains(expected.message)
+            || expected
+                .variant
+                .is_some_and(|variant| frame.variant != Some(variant))
+    });
+
+    let mut report = String::new();
+    if let Some(index) = mismatch {
+        let _ = writeln!(
+            report,
+            "assertion `stack` failed: frame {index} doesn't match"
+        );
+        let _ = writeln!(report, "  expected: {}", expected[index]);
+    } else if stack.len() != expected.len() {
+        let _ = writeln!(
+            report,
+            "assertion `stack` failed: expected {} frames, found {}",
+            expected.len(),
+            stack.len()
+        );
+        let _ = writeln!(report, "  expected:");
+        for (index, frame) in expected.iter().enumerate() {
+            let _ = writeln!(report, "  {index}: {frame}");
+        }
+    } else {
+        return;
+    }
+
+    let _ = writeln!(report, "     stack:");
+    for (index, frame) in stack.iter().enumerate() {
+        let _ = writeln!(report, "  {index}: {frame}");
+    }
+    panic!("{}", report.trim_end());
+}
+
+/// A difference between two virtual stacks at one frame index, as returned by
+/// [`VirtualStackTrace::diff`].
+///
+/// Frames are compared by message and location only. `Display` writes the frames that
+/// differ on `-` and `+` lines, like a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameDiff {
+    /// Both stacks have a frame at `index`, with different messages or locations.
+    Changed {
+        index: usize,
+        left: StackFrame,
+        right: StackFrame,
+    },
+    /// Only the left stack, the one `diff` was called on, has a frame at `index`.
+    Removed { index: usize, frame: StackFrame },
+    /// Only the right stack, the one passed to `diff`, has a frame at `index`.
+    Added { index: usize, frame: StackFrame },
+}
+
+impl FrameDiff {
+    /// Returns the index of the frame that differs.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Changed { index, .. }
+            | Self::Removed { index, .. }
+            | Self::Added { index, .. } => *index,
+        }
+    }
+}
+
+impl core::fmt::Display for FrameDiff {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "frame {}:", self.index())?;
+        match self {
+            Self::Changed { left, right, .. } => write!(f, "  - {left}\n  + {right}"),
+            Self::Removed { frame, .. } => write!(f, "  - {frame}"),
+            Self::Added { frame, .. } => write!(f, "  + {frame}"),
+        }
+    }
+}
+
+/// Renders the differences returned by [`VirtualStackTrace::diff`] one frame after another,
+/// or `stacks are equal` when there are none.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{render_diff, stack_trace_debug, VirtualStackTrace};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum ConfigError {
+///     #[snafu(display("Failed to read {path}"))]
+///     FileRead { path: String, location: snafu::Location },
+/// }
+///
+/// let location = snafu::Location::new("src/config.rs", 42, 15);
+/// let left = FileReadSnafu { path: "a.json", location }.build();
+/// let right = FileReadSnafu { path: "b.json", location }.build();
+///
+/// assert_eq!(
+///     render_diff(&left.diff(&right)),
+///     "frame 0:\n  \
+///        - [FileRead] Failed to read a.json at src/config.rs:42:15\n  \
+///        + [FileRead] Failed to read b.json at src/config.rs:42:15"
+/// );
+/// assert_eq!(render_diff(&left.diff(&left)), "stacks are equal");
+/// ```
+pub fn render_diff(diffs: &[FrameDiff]) -> String {
+    if diffs.is_empty() {
+        return String::from("stacks are equal");
+    }
+    let mut rendered = String::new();
+    for (i, diff) in diffs.iter().enumerate() {
+        if i > 0 {
+            rendered.push('\n');
+        }
+        let _ = write!(rendered, "{diff}");
+    }
+    rendered
+}
+
+/// Compares two stacks frame by frame, for [`VirtualStackTrace::diff`].
+pub(crate) fn diff(mut left: StackIter<'_>, mut right: StackIter<'_>) -> Vec<FrameDiff> {
+    let mut diffs = Vec::new();
+    for index in 0.. {
+        let diff = match (left.next(), right.next()) {
+            (Some(left), Some(right)) => {
+                if left.message == right.message && left.location == right.location {
+                    continue;
+                }
+                FrameDiff::Changed { index, left, right }
+            }
+            (Some(frame), None) => FrameDiff::Removed { index, frame },
+            (None, Some(frame)) => FrameDiff::Added { index, frame },
+            (None, None) => break,
+        };
+        diffs.push(diff);
+    }
+    diffs
+}
@@ -0,0 +1,34 @@
+//! Recording the thread errors are created on.
+
+use alloc::string::{String, ToString};
+use std::thread::Thread;
+
+/// Handle to the thread an error was created on.
+///
+/// With the `thread` feature, [`stack_trace_debug`](crate::stack_trace_debug) injects an
+/// implicit field of this type next to `location`, and the error's frame reports it in
+/// [`StackFrame::thread`](crate::StackFrame::thread). Capturing only clones the handle of
+/// the current thread, its name is formatted when the frame is built.
+#[derive(Debug, Clone)]
+pub struct CapturedThread(Thread);
+
+impl CapturedThread {
+    /// Captures the current thread.
+    pub fn current() -> Self {
+        Self(std::thread::current())
+    }
+
+    /// Returns the name of the thread, or its id such as `ThreadId(2)` for unnamed threads.
+    pub fn name(&self) -> String {
+        match self.0.name() {
+            Some(name) => name.to_string(),
+            None => alloc::format!("{:?}", self.0.id()),
+        }
+    }
+}
+
+impl snafu::GenerateImplicitData for CapturedThread {
+    fn generate() -> Self {
+        Self::current()
+    }
+}
@@ -0,0 +1,91 @@
+//! Recording the time errors are created at.
+
+use core::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Time an error was created at.
+///
+/// With the `timestamp` feature, [`stack_trace_debug`](crate::stack_trace_debug) injects an
+/// implicit field of this type next to `location`, and the error's frame reports it in
+/// [`StackFrame::timestamp`](crate::StackFrame::timestamp). It displays as an RFC 3339 UTC
+/// timestamp with millisecond precision, such as `2024-05-17T09:41:07.123Z`.
+///
+/// To keep errors small, the time is stored as nanoseconds since the Unix epoch in a single
+/// `u64`, so times before 1970 are recorded as the epoch itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CapturedTime(u64);
+
+impl CapturedTime {
+    /// Captures the current system time.
+    pub fn now() -> Self {
+        Self::from(SystemTime::now())
+    }
+
+    /// Returns the captured time.
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_nanos(self.0)
+    }
+}
+
+impl From<SystemTime> for CapturedTime {
+    fn from(time: SystemTime) -> Self {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        Self(u64::try_from(since_epoch.as_nanos()).unwrap_or(u64::MAX))
+    }
+}
+
+impl core::fmt::Display for CapturedTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Rfc3339(self.time()).fmt(f)
+    }
+}
+
+impl snafu::GenerateImplicitData for CapturedTime {
+    fn generate() -> Self {
+        Self::now()
+    }
+}
+
+/// Displays a [`SystemTime`] as an RFC 3339 UTC timestamp with millisecond precision.
+///
+/// Times before the Unix epoch are shown as the epoch itself.
+pub(crate) struct Rfc3339(pub(crate) SystemTime);
+
+impl core::fmt::Display for Rfc3339 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let since_epoch = self.0.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = since_epoch.as_secs();
+        let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+        let (year, month, day) = civil_from_days(days);
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60,
+            since_epoch.subsec_millis()
+        )
+    }
+}
+
+/// Converts a number of days since 1970-01-01 into a proleptic Gregorian `(year, month, day)`.
+///
+/// This is Howard Hinnant's `civil_from_days`, restricted to dates after the epoch.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    // Shift the epoch to 0000-03-01, so leap days end each 400 year era
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    (year, month, day)
+}
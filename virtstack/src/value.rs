@@ -0,0 +1,98 @@
+//! Structured inspection of virtual stacks through `valuable`.
+
+use valuable::{
+    Fields, Listable, NamedField, NamedValues, StructDef, Structable, Valuable, Value, Visit,
+};
+
+use crate::StackFrame;
+
+/// The fields a [`StackFrame`] exposes through `valuable`, in the order of its serialization.
+static FRAME_FIELDS: &[NamedField<'static>] = &[
+    NamedField::new("message"),
+    NamedField::new("file"),
+    NamedField::new("line"),
+    NamedField::new("column"),
+];
+
+impl Valuable for StackFrame {
+    fn as_value(&self) -> Value<'_> {
+        Value::Structable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        visit.visit_named_fields(&NamedValues::new(
+            FRAME_FIELDS,
+            &[
+                Value::String(&self.message),
+                Value::String(self.location.file()),
+                Value::U32(self.location.line()),
+                Value::U32(self.location.column()),
+            ],
+        ));
+    }
+}
+
+impl Structable for StackFrame {
+    fn definition(&self) -> StructDef<'_> {
+        StructDef::new_static("StackFrame", Fields::Named(FRAME_FIELDS))
+    }
+}
+
+/// The frames of a virtual stack as a `valuable` list, outermost first.
+///
+/// Each frame is a structure with `message`, `file`, `line` and `column` fields, so
+/// subscribers built on `valuable` record the whole stack as structured data instead of a
+/// rendered string. `Frames` works with any [`Stack`](crate::Stack), whether it is a `Vec`
+/// or a `SmallVec`.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{Frames, stack_trace_debug, VirtualStackTrace};
+/// use valuable::{Valuable, Value};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// #[snafu(display("Query failed"))]
+/// struct QueryError {
+///     source: std::io::Error,
+/// }
+///
+/// let io = std::io::Error::other("connection reset");
+/// let error = Err::<(), _>(io).context(QuerySnafu).unwrap_err();
+/// let stack = error.virtual_stack();
+/// let frames = Frames(&stack);
+///
+/// let Value::Listable(list) = frames.as_value() else {
+///     unreachable!()
+/// };
+/// assert_eq!(list.size_hint(), (2, Some(2)));
+/// ```
+///
+/// With `tracing`'s own `valuable` feature, which requires building with
+/// `RUSTFLAGS="--cfg tracing_unstable"`, the frames can be recorded as a field:
+///
+/// ```rust,ignore
+/// let stack = error.virtual_stack();
+/// let frames = Frames(&stack);
+/// tracing::error!(stack = frames.as_value(), "request failed");
+/// ```
+#[derive(Clone, Copy)]
+pub struct Frames<'a>(pub &'a [StackFrame]);
+
+impl Valuable for Frames<'_> {
+    fn as_value(&self) -> Value<'_> {
+        Value::Listable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        StackFrame::visit_slice(self.0, visit);
+    }
+}
+
+impl Listable for Frames<'_> {
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
@@ -0,0 +1,120 @@
+//! Turning errors into HTTP responses for `axum` services.
+
+use axum_core::response::{IntoResponse, Response};
+
+use crate::{ErrorExt, StatusCode, VirtualStackTrace};
+
+impl From<StatusCode> for http::StatusCode {
+    /// Maps each status code onto the closest HTTP status.
+    fn from(code: StatusCode) -> Self {
+        match code {
+            StatusCode::Internal | StatusCode::Unknown => http::StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::Unsupported => http::StatusCode::NOT_IMPLEMENTED,
+            StatusCode::InvalidArguments => http::StatusCode::BAD_REQUEST,
+            StatusCode::NotFound => http::StatusCode::NOT_FOUND,
+            StatusCode::AlreadyExists => http::StatusCode::CONFLICT,
+            StatusCode::Unauthorized => http::StatusCode::UNAUTHORIZED,
+            StatusCode::PermissionDenied => http::StatusCode::FORBIDDEN,
+            // Non-standard "Client Closed Request", as popularized by nginx
+            StatusCode::Cancelled => http::StatusCode::from_u16(499).unwrap(),
+            StatusCode::Timeout => http::StatusCode::GATEWAY_TIMEOUT,
+            StatusCode::RateLimited => http::StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::Unavailable => http::StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+/// Wrapper turning an error into an HTTP response, for use as the error type of `axum`
+/// handlers.
+///
+/// The response status is derived from the error's [`StatusCode`] and the body is a JSON
+/// object with the `code` and the public [`output_msg`](ErrorExt::output_msg) as `message`:
+///
+/// ```json
+/// {"code": "NotFound", "message": "User 7 not found"}
+/// ```
+///
+/// The full virtual stack is logged server-side through
+/// [`emit_tracing`](VirtualStackTrace::emit_tracing) instead of being sent to the client,
+/// unless [`HttpError::include_stack`] is enabled, e.g. for debug builds.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{stack_trace_debug, HttpError};
+///
+/// #[stack_trace_debug]
+/// #[derive(Snafu)]
+/// enum MyError {
+///     #[snafu(display("User {id} not found"))]
+///     #[status_code(NotFound)]
+///     UserNotFound { id: u64 },
+/// }
+///
+/// async fn get_user(id: u64) -> Result<String, HttpError<MyError>> {
+///     Err(UserNotFoundSnafu { id }.build())?
+/// }
+/// ```
+pub struct HttpError<E> {
+    error: E,
+    include_stack: bool,
+}
+
+impl<E> HttpError<E> {
+    /// Wraps `error`, keeping its virtual stack out of the response body.
+    pub fn new(error: E) -> Self {
+        Self {
+            error,
+            include_stack: false,
+        }
+    }
+
+    /// Sets whether the response body also contains the virtual stack, as a `stack` array
+    /// of serialized [`StackFrame`](crate::StackFrame)s.
+    ///
+    /// The stack reveals internal messages and source locations, so only enable this for
+    /// trusted clients, e.g. with `include_stack(cfg!(debug_assertions))`.
+    pub fn include_stack(mut self, include: bool) -> Self {
+        self.include_stack = include;
+        self
+    }
+
+    /// Returns the wrapped error.
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+}
+
+impl<E> From<E> for HttpError<E> {
+    fn from(error: E) -> Self {
+        Self::new(error)
+    }
+}
+
+impl<E: core::fmt::Debug> core::fmt::Debug for HttpError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<E> IntoResponse for HttpError<E>
+where
+    E: ErrorExt + VirtualStackTrace + core::fmt::Display,
+{
+    fn into_response(self) -> Response {
+        self.error.emit_tracing();
+
+        let code = self.error.status_code();
+        let mut body = serde_json::json!({
+            "code": code.as_str(),
+            "message": self.error.output_msg(),
+        });
+        if self.include_stack {
+            body["stack"] = serde_json::json!(self.error.virtual_stack());
+        }
+
+        let headers = [(http::header::CONTENT_TYPE, "application/json")];
+        (http::StatusCode::from(code), headers, body.to_string()).into_response()
+    }
+}
@@ -0,0 +1,99 @@
+//! A stringly-typed error carrying a virtual stack.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::error::Error;
+
+use snafu::Snafu;
+
+use crate::stack_trace_debug;
+
+/// A basic error type for ad-hoc error sites, like SNAFU's [`Whatever`](snafu::Whatever) but
+/// recording a virtual stack frame instead of a backtrace.
+///
+/// It implements [`FromString`](snafu::FromString), so it works with SNAFU's
+/// `whatever_context` and `with_whatever_context` extension methods as well as with the
+/// [`virt_whatever!`](crate::virt_whatever) macro. Its source chain is walked like that of
+/// any other error, and its [`Debug`](core::fmt::Debug) output is the usual virtual stack
+/// trace.
+///
+/// # Example
+///
+/// ```rust
+/// use snafu::prelude::*;
+/// use snafu_virtstack::{VirtWhatever, VirtualStackTrace};
+///
+/// fn parse_port(input: &str) -> Result<u16, VirtWhatever> {
+///     input
+///         .parse()
+///         .with_whatever_context(|_| format!("Invalid port {input:?}"))
+/// }
+///
+/// let error = parse_port("http").unwrap_err();
+/// let stack = error.virtual_stack();
+/// assert_eq!(stack[0].message, r#"Invalid port "http""#);
+/// assert_eq!(stack[1].message, "invalid digit found in string");
+/// ```
+#[stack_trace_debug(crate = "crate")]
+#[derive(Snafu)]
+#[snafu(whatever, display("{message}"))]
+pub struct VirtWhatever {
+    #[snafu(source(from(Box<dyn Error + Send + Sync>, Some)))]
+    source: Option<Box<dyn Error + Send + Sync>>,
+    message: String,
+}
+
+impl VirtWhatever {
+    /// Returns the message this error was created with.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Returns early with a [`VirtWhatever`] error, recording the location of the invocation.
+///
+/// Works like SNAFU's `whatever!`: the first form formats a new error, the second unwraps a
+/// `Result` or returns its error wrapped as the source of a new one. The error is converted
+/// with [`From`], so the enclosing function may return any error type that can be built from
+/// a [`VirtWhatever`].
+///
+/// # Example
+///
+/// ```rust
+/// use snafu_virtstack::{virt_whatever, VirtWhatever, VirtualStackTrace};
+///
+/// fn read_level(input: &str) -> Result<u8, VirtWhatever> {
+///     let level: u8 = virt_whatever!(input.parse(), "Invalid level {input:?}");
+///     if level > 9 {
+///         virt_whatever!("Level {level} is out of range");
+///     }
+///     Ok(level)
+/// }
+///
+/// assert_eq!(read_level("3").unwrap(), 3);
+/// assert_eq!(read_level("12").unwrap_err().to_string(), "Level 12 is out of range");
+/// assert_eq!(read_level("x").unwrap_err().frame_count(), 2);
+/// ```
+#[macro_export]
+macro_rules! virt_whatever {
+    ($fmt:literal $(, $($arg:expr),* $(,)?)?) => {
+        return ::core::result::Result::Err(::core::convert::From::from(
+            <$crate::VirtWhatever as $crate::__private::FromString>::without_source(
+                $crate::__private::format!($fmt $(, $($arg),*)?),
+            ),
+        ))
+    };
+    ($source:expr, $fmt:literal $(, $($arg:expr),* $(,)?)?) => {
+        match $source {
+            ::core::result::Result::Ok(value) => value,
+            ::core::result::Result::Err(error) => {
+                return ::core::result::Result::Err(::core::convert::From::from(
+                    <$crate::VirtWhatever as $crate::__private::FromString>::with_source(
+                        ::core::convert::Into::into(error),
+                        $crate::__private::format!($fmt $(, $($arg),*)?),
+                    ),
+                ));
+            }
+        }
+    };
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "anyhow")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to load configuration"))]
+    Load { source: std::io::Error },
+}
+
+fn load() -> (Result<(), ConfigError>, u32) {
+    let io = std::io::Error::other("permission denied");
+    (Err(io).context(LoadSnafu), line!())
+}
+
+#[test]
+fn anyhow_errors_keep_the_stack_and_the_original_error() {
+    let (result, line) = load();
+    let error = result.unwrap_err().into_anyhow();
+
+    let chain: Vec<String> = error.chain().map(ToString::to_string).collect();
+    assert_eq!(chain.len(), 3);
+    let trace: Vec<&str> = chain[0].lines().collect();
+    assert_eq!(trace.len(), 3);
+    assert_eq!(trace[0], "Virtual Stack Trace:");
+    assert!(trace[1].starts_with("  0: [Load] Failed to load configuration at "));
+    assert!(trace[1].ends_with(&format!("tests/anyhow.rs:{line}:14")));
+    assert!(trace[2].starts_with("  1: permission denied at "));
+    assert_eq!(chain[1], "Failed to load configuration");
+    assert_eq!(chain[2], "permission denied");
+
+    let alternate = format!("{error:#}");
+    assert!(alternate.ends_with(": Failed to load configuration: permission denied"));
+
+    let debug = format!("{error:?}");
+    assert!(debug.starts_with("Virtual Stack Trace:\n"));
+    assert!(debug.contains("Caused by:"));
+
+    assert!(matches!(
+        error.downcast_ref::<ConfigError>(),
+        Some(ConfigError::Load { .. })
+    ));
+    assert!(error.downcast::<ConfigError>().is_ok());
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "testing")]
+
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{assert_stack, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to read {path}"))]
+    FileRead {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Config is empty"))]
+    Empty,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to start"))]
+struct StartError {
+    source: ConfigError,
+}
+
+fn read_error() -> ConfigError {
+    Err::<(), _>(std::io::Error::other("io error"))
+        .context(FileReadSnafu {
+            path: "config.json",
+        })
+        .unwrap_err()
+}
+
+#[test]
+fn matches_variants_and_messages() {
+    assert_stack!(read_error(), ["FileRead" contains "config.json", "io error"]);
+    assert_stack!(EmptySnafu.build(), ["Empty" contains "empty"]);
+}
+
+#[test]
+fn plain_entries_match_any_frame() {
+    let error = StartSnafu.into_error(read_error());
+    assert_stack!(error, ["start", "config.json", "io error",]);
+}
+
+#[test]
+fn accepts_references() {
+    let error = read_error();
+    assert_stack!(&error, ["Failed to read config.json", "io"]);
+}
+
+#[test]
+#[should_panic(expected = "frame 1 doesn't match\n  expected: \"timed out\"")]
+fn reports_mismatched_messages() {
+    assert_stack!(read_error(), ["FileRead" contains "config.json", "timed out"]);
+}
+
+#[test]
+#[should_panic(expected = "frame 0 doesn't match\n  expected: \"Empty\" contains \"config\"")]
+fn reports_mismatched_variants() {
+    assert_stack!(read_error(), ["Empty" contains "config", "io error"]);
+}
+
+#[test]
+#[should_panic(expected = "expected 1 frames, found 2")]
+fn reports_missing_frames() {
+    assert_stack!(read_error(), ["config.json"]);
+}
+
+#[test]
+#[should_panic(expected = "expected 2 frames, found 1")]
+fn reports_extra_frames() {
+    assert_stack!(EmptySnafu.build(), ["empty", "io error"]);
+}
+
+#[test]
+fn failures_show_the_full_stack() {
+    let panic = std::panic::catch_unwind(|| {
+        assert_stack!(read_error(), ["FileRead" contains "config.json"]);
+    })
+    .unwrap_err();
+
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.contains("\n     stack:\n  0: [FileRead] Failed to read config.json at "));
+    assert!(message.contains("\n  1: io error at "));
+}
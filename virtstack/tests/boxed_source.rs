@@ -0,0 +1,113 @@
+use std::error::Error;
+use std::fmt;
+
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+type BoxedError = Box<dyn Error + Send + Sync>;
+
+/// Foreign error with an optional source, for building chains of arbitrary depth.
+#[derive(Debug)]
+struct Layer {
+    message: &'static str,
+    source: Option<BoxedError>,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl Error for Layer {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|source| source as _)
+    }
+}
+
+fn chain(messages: &[&'static str]) -> BoxedError {
+    messages
+        .iter()
+        .rev()
+        .fold(None, |source, &message| {
+            Some(Box::new(Layer { message, source }) as BoxedError)
+        })
+        .unwrap()
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Failed to parse header"))]
+    ParseHeader { source: BoxedError },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Plugin failed"))]
+    Plugin { source: BoxedError },
+
+    #[snafu(display("Request failed"))]
+    Request { source: Box<InnerError> },
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn boxed_sources_are_walked_to_the_end_of_their_chain() {
+    let error = Err::<(), _>(chain(&["decoder crashed", "invalid utf-8", "byte 0xff"]))
+        .context(PluginSnafu)
+        .unwrap_err();
+
+    let messages: Vec<_> = error
+        .virtual_stack()
+        .iter()
+        .map(|frame| frame.message.to_string())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "Plugin failed",
+            "decoder crashed",
+            "invalid utf-8",
+            "byte 0xff"
+        ]
+    );
+}
+
+#[test]
+fn boxed_virtual_stack_errors_keep_their_sources() {
+    let inner = Err::<(), _>(chain(&["unexpected end of input"]))
+        .context(ParseHeaderSnafu)
+        .unwrap_err();
+    let error = Err::<(), _>(Box::new(inner) as BoxedError)
+        .context(PluginSnafu)
+        .unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack[1].message, "Failed to parse header");
+    assert_eq!(stack[2].message, "unexpected end of input");
+}
+
+#[test]
+fn boxed_concrete_sources_splice_their_frames() {
+    let (inner, line) = (
+        Err::<(), _>(chain(&["unexpected end of input"])).context(ParseHeaderSnafu),
+        line!(),
+    );
+    let error = inner.map_err(Box::new).context(RequestSnafu).unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack[1].variant, Some("ParseHeader"));
+    assert_eq!(stack[1].location.line(), line - 1);
+    assert_eq!(stack[2].message, "unexpected end of input");
+}
+
+#[test]
+fn errors_with_boxed_sources_are_send_and_sync() {
+    assert_send_sync::<InnerError>();
+    assert_send_sync::<OuterError>();
+}
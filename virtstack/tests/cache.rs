@@ -0,0 +1,87 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Foreign source counting how often its message is formatted.
+#[derive(Debug, Default)]
+struct Counting(AtomicUsize);
+
+impl std::fmt::Display for Counting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        write!(f, "counted")
+    }
+}
+
+impl std::error::Error for Counting {}
+
+#[stack_trace_debug(cache)]
+#[derive(Snafu)]
+enum CachedError {
+    #[snafu(display("Cached failure"))]
+    Cached { source: Counting },
+
+    #[snafu(display("Unit failure"))]
+    Unit,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum UncachedError {
+    #[snafu(display("Uncached failure"))]
+    Uncached { source: Counting },
+}
+
+fn formatted(source: &Counting) -> usize {
+    source.0.load(Ordering::Relaxed)
+}
+
+#[test]
+fn cached_stack_is_computed_once() {
+    let error = Err::<(), _>(Counting::default())
+        .context(CachedSnafu)
+        .unwrap_err();
+    let CachedError::Cached { source, .. } = &error else {
+        unreachable!()
+    };
+
+    let stack = error.virtual_stack();
+    let debug = format!("{error:?}");
+    let again = error.virtual_stack();
+
+    assert_eq!(formatted(source), 1);
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[1].message, "counted");
+    assert_eq!(stack[0].location.line(), again[0].location.line());
+    assert_eq!(
+        debug,
+        format!(
+            "Error: Cached failure\nVirtual Stack Trace:\n  0: {}\n  1: {}\n",
+            stack[0], stack[1]
+        )
+    );
+}
+
+#[test]
+fn unit_variants_are_cached() {
+    let (error, line) = (UnitSnafu.build(), line!());
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(stack[0].variant, Some("Unit"));
+}
+
+#[test]
+fn debug_formats_each_message_once_without_cache() {
+    let error = Err::<(), _>(Counting::default())
+        .context(UncachedSnafu)
+        .unwrap_err();
+    let UncachedError::Uncached { source, .. } = &error;
+
+    let _ = format!("{error:?}");
+    assert_eq!(formatted(source), 1);
+
+    let _ = error.virtual_stack();
+    assert_eq!(formatted(source), 2);
+}
@@ -0,0 +1,142 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug(clean_messages)]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to read {path}: {source}"))]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Invalid config ({source})"))]
+    Invalid { source: std::io::Error },
+}
+
+#[stack_trace_debug(clean_messages)]
+#[derive(Snafu)]
+#[snafu(display("Failed to start: {source}"))]
+struct StartError {
+    source: ConfigError,
+}
+
+#[stack_trace_debug(clean_messages, root_first)]
+#[derive(Snafu)]
+#[snafu(display("Failed to start: {source}"))]
+struct RootFirstStartError {
+    source: ConfigError,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to start: {source}"))]
+struct PlainStartError {
+    source: ConfigError,
+}
+
+fn not_found() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::NotFound, "No such file or directory")
+}
+
+fn read_error() -> ConfigError {
+    Err::<(), _>(not_found())
+        .context(ReadSnafu { path: "app.toml" })
+        .unwrap_err()
+}
+
+/// Returns the messages of the numbered frame lines of `rendered`.
+fn rendered_messages(rendered: &str) -> Vec<&str> {
+    rendered
+        .lines()
+        .filter_map(|line| line.trim_start().split_once(": "))
+        .filter(|(index, _)| index.parse::<usize>().is_ok())
+        .map(|(_, frame)| frame.rsplit_once(" at ").unwrap().0)
+        .collect()
+}
+
+#[test]
+fn debug_output_strips_nested_source_messages() {
+    let error = StartSnafu.into_error(read_error());
+
+    let debug = format!("{error:?}");
+    assert!(debug.starts_with(
+        "Error: Failed to start: Failed to read app.toml: No such file or directory\n"
+    ));
+    assert_eq!(
+        rendered_messages(&debug),
+        [
+            "Failed to start",
+            "[Read] Failed to read app.toml",
+            "No such file or directory"
+        ]
+    );
+}
+
+#[test]
+fn virtual_stack_keeps_full_messages() {
+    let error = StartSnafu.into_error(read_error());
+
+    let stack = error.virtual_stack();
+    assert_eq!(
+        stack[0].message,
+        "Failed to start: Failed to read app.toml: No such file or directory"
+    );
+    assert_eq!(
+        stack[1].message,
+        "Failed to read app.toml: No such file or directory"
+    );
+}
+
+#[test]
+fn messages_not_ending_with_their_source_are_kept() {
+    let error = Err::<(), _>(not_found()).context(InvalidSnafu).unwrap_err();
+
+    assert_eq!(
+        rendered_messages(&format!("{error:?}")),
+        [
+            "[Invalid] Invalid config (No such file or directory)",
+            "No such file or directory"
+        ]
+    );
+}
+
+#[test]
+fn root_first_output_is_cleaned_too() {
+    let error = RootFirstStartSnafu.into_error(read_error());
+
+    let debug = format!("{error:?}");
+    assert!(debug.starts_with("Error: Failed to start: Failed to read app.toml: "));
+    assert_eq!(
+        rendered_messages(&debug),
+        [
+            "No such file or directory",
+            "[Read] Failed to read app.toml",
+            "Failed to start"
+        ]
+    );
+}
+
+#[test]
+fn process_wide_setting_cleans_all_rendering() {
+    let error = PlainStartSnafu.into_error(read_error());
+    let full = "Failed to start: Failed to read app.toml: No such file or directory";
+    assert_eq!(rendered_messages(&format!("{error:?}"))[0], full);
+
+    snafu_virtstack::set_clean_messages(true);
+    let debug = format!("{error:?}");
+    let display = error.stack_display().to_string();
+    let rendered = error.render_with(&snafu_virtstack::CompactFormatter);
+    snafu_virtstack::set_clean_messages(false);
+
+    let clean = [
+        "Failed to start",
+        "[Read] Failed to read app.toml",
+        "No such file or directory",
+    ];
+    assert!(debug.starts_with(&format!("Error: {full}\n")));
+    assert_eq!(rendered_messages(&debug), clean);
+    assert_eq!(rendered_messages(&display), clean);
+    assert_eq!(rendered_messages(&rendered), clean);
+}
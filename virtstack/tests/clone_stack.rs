@@ -0,0 +1,45 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{StackFrame, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to read config"))]
+    Read { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Port {port} is taken"))]
+struct BindError {
+    port: u16,
+}
+
+#[test]
+fn copies_the_frames_of_a_boxed_error() {
+    let line = line!() + 1;
+    let error = ReadSnafu.into_error(std::io::Error::other("disk on fire"));
+    let boxed: Box<dyn VirtualStackTrace> = Box::new(error);
+
+    let frames: Vec<StackFrame> = boxed.clone_stack();
+    drop(boxed);
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].message, "Failed to read config");
+    assert_eq!(frames[0].variant, Some("Read"));
+    assert_eq!(frames[1].message, "disk on fire");
+    assert_eq!(frames[1].location.line(), line);
+}
+
+#[test]
+fn matches_the_virtual_stack() {
+    let errors: Vec<Box<dyn VirtualStackTrace>> = vec![
+        Box::new(ReadSnafu.into_error(std::io::Error::other("denied"))),
+        Box::new(BindSnafu { port: 8080u16 }.build()),
+    ];
+
+    for error in &errors {
+        assert_eq!(error.clone_stack().as_slice(), &error.virtual_stack()[..]);
+    }
+}
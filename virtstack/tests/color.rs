@@ -0,0 +1,45 @@
+#![cfg(feature = "color")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Failed to read file"))]
+    ReadFile { source: std::io::Error },
+
+    #[snafu(display("Retrying"))]
+    #[level(Warn)]
+    Retry,
+
+    #[snafu(display("Corrupted"))]
+    #[level(Fatal)]
+    Corrupted,
+}
+
+// A single test since the color decision is cached for the whole process
+#[test]
+fn clicolor_force_colors_piped_output() {
+    // SAFETY: no other thread of this test binary reads the environment
+    unsafe {
+        std::env::remove_var("NO_COLOR");
+        std::env::set_var("CLICOLOR_FORCE", "1");
+    }
+
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    let error = Err::<(), _>(io).context(ReadFileSnafu).unwrap_err();
+    let stack = error.virtual_stack();
+
+    let expected = format!(
+        "Error: Failed to read file\nVirtual Stack Trace:\n  \x1b[33m0\x1b[0m: [ReadFile] \x1b[1mFailed to read file\x1b[0m at \x1b[36m{}\x1b[0m\n  \x1b[33m1\x1b[0m: \x1b[1mmissing\x1b[0m at \x1b[36m{}\x1b[0m\n",
+        stack[0].location, stack[1].location
+    );
+    assert_eq!(format!("{error:?}"), expected);
+
+    // The label is colored after the level of the error
+    let warn = format!("{:?}", RetrySnafu.build());
+    assert!(warn.starts_with("\x1b[33mError\x1b[0m: Retrying\n"));
+    let fatal = format!("{:?}", CorruptedSnafu.build());
+    assert!(fatal.starts_with("\x1b[31mError\x1b[0m: Corrupted\n"));
+}
@@ -0,0 +1,71 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug(compact_leaf)]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Config not found"))]
+    NotFound,
+
+    #[snafu(display("Failed to read config"))]
+    Read { source: std::io::Error },
+}
+
+#[stack_trace_debug(compact_leaf, error_label = "Failure")]
+#[derive(Snafu)]
+#[snafu(display("Service unavailable"))]
+struct ServiceError;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Queue is empty"))]
+struct QueueError;
+
+#[test]
+fn leaf_errors_are_written_on_one_line() {
+    let error = NotFoundSnafu.build();
+    let line = line!() - 1;
+
+    let rendered = format!("{error:?}");
+    assert_eq!(
+        rendered,
+        format!("Error: Config not found at {}:{line}:31", file!())
+    );
+}
+
+#[test]
+fn compact_leaf_uses_the_error_label() {
+    let rendered = format!("{:?}", ServiceSnafu.build());
+    assert!(rendered.starts_with("Failure: Service unavailable at "));
+    assert!(!rendered.contains('\n'));
+}
+
+#[test]
+fn errors_with_sources_keep_the_full_trace() {
+    let error = ReadSnafu.into_error(std::io::Error::other("disk full"));
+
+    let rendered = format!("{error:?}");
+    assert!(rendered.starts_with("Error: Failed to read config\nVirtual Stack Trace:\n"));
+    assert!(rendered.contains("  1: disk full"));
+}
+
+#[test]
+fn alternate_debug_keeps_the_full_trace() {
+    let rendered = format!("{:#?}", NotFoundSnafu.build());
+    assert!(rendered.starts_with("Error: Config not found\nVirtual Stack Trace:\n"));
+    assert!(rendered.contains("  0: [NotFound] Config not found at "));
+}
+
+#[test]
+fn process_wide_setting_applies_to_all_types() {
+    let error = QueueSnafu.build();
+    assert!(format!("{error:?}").contains('\n'));
+
+    snafu_virtstack::set_compact_leaf(true);
+    let rendered = format!("{error:?}");
+    snafu_virtstack::set_compact_leaf(false);
+
+    assert!(rendered.starts_with("Error: Queue is empty at "));
+    assert!(!rendered.contains('\n'));
+}
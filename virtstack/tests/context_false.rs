@@ -0,0 +1,56 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Inner failure"))]
+    Failure,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum AppError {
+    #[snafu(context(false))]
+    #[snafu(display("I/O failed"))]
+    Io { source: std::io::Error },
+
+    #[snafu(context(false), display("Inner step failed"))]
+    Inner { source: InnerError },
+}
+
+fn read() -> (Result<(), AppError>, u32) {
+    let read = || -> Result<(), AppError> {
+        Err(std::io::Error::other("disk full"))?;
+        Ok(())
+    };
+    (read(), line!() - 3)
+}
+
+#[test]
+fn context_false_variants_capture_the_question_mark() {
+    let (result, line) = read();
+    let error = result.unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "I/O failed");
+    assert_eq!(stack[0].variant, Some("Io"));
+    assert_eq!(stack[0].location.file(), file!());
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(stack[1].message, "disk full");
+    assert_eq!(error.location().unwrap().line(), line);
+}
+
+#[test]
+fn context_false_variants_keep_nested_stacks() {
+    let (inner, inner_line) = (FailureSnafu.build(), line!());
+    let (error, line) = (AppError::from(inner), line!());
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "Inner step failed");
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(stack[1].message, "Inner failure");
+    assert_eq!(stack[1].location.line(), inner_line);
+}
@@ -0,0 +1,24 @@
+mod errors {
+    pub use snafu_virtstack as virtstack;
+}
+
+use errors::virtstack::{ErrorExt, StatusCode, VirtualStackTrace, stack_trace_debug};
+use snafu::prelude::*;
+
+#[stack_trace_debug(crate = "crate::errors::virtstack", cache)]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Failed to read config"))]
+    #[status_code(NotFound)]
+    ReadConfig { source: std::io::Error },
+}
+
+#[test]
+fn generated_code_resolves_through_crate_path() {
+    let io = std::io::Error::other("missing");
+    let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+
+    assert_eq!(error.status_code(), StatusCode::NotFound);
+    assert_eq!(error.virtual_stack().len(), 2);
+    assert!(format!("{error:?}").starts_with("Error: Failed to read config\n"));
+}
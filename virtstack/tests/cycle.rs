@@ -0,0 +1,76 @@
+use snafu::prelude::*;
+use snafu_virtstack::{DEFAULT_MAX_DEPTH, TRUNCATED_MESSAGE, VirtualStackTrace, stack_trace_debug};
+
+/// An error whose source chain points back at itself.
+#[derive(Debug)]
+struct Cyclic;
+
+static CYCLIC: Cyclic = Cyclic;
+
+impl std::fmt::Display for Cyclic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cyclic")
+    }
+}
+
+impl std::error::Error for Cyclic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&CYCLIC)
+    }
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum DefaultError {
+    #[snafu(display("Wrapped a cycle"))]
+    Wrapped { source: Cyclic },
+}
+
+#[stack_trace_debug(max_depth = 4)]
+#[derive(Snafu)]
+enum ShallowError {
+    #[snafu(display("Wrapped a cycle"))]
+    ShallowWrapped { source: Cyclic },
+
+    #[snafu(display("Leaf"))]
+    Leaf,
+}
+
+#[test]
+fn cyclic_chain_is_truncated_at_default_depth() {
+    let error = Err::<(), _>(Cyclic).context(WrappedSnafu).unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), DEFAULT_MAX_DEPTH + 1);
+    assert_eq!(stack.last().unwrap().message, TRUNCATED_MESSAGE);
+}
+
+#[test]
+fn max_depth_argument_is_honored() {
+    let error = Err::<(), _>(Cyclic)
+        .context(ShallowWrappedSnafu)
+        .unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 5);
+    assert_eq!(stack[3].message, "cyclic");
+    assert_eq!(stack[4].message, TRUNCATED_MESSAGE);
+}
+
+#[test]
+fn short_chains_are_not_truncated() {
+    let stack = LeafSnafu.build().virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "Leaf");
+}
+
+#[test]
+fn root_cause_skips_the_truncation_frame() {
+    let error = Err::<(), _>(Cyclic)
+        .context(ShallowWrappedSnafu)
+        .unwrap_err();
+
+    let root = error.root_cause().unwrap();
+    assert_eq!(root.message, "cyclic");
+    assert_eq!(error.root_message().as_deref(), Some("cyclic"));
+}
@@ -0,0 +1,159 @@
+use snafu::prelude::*;
+use snafu_virtstack::{StackDisplay, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Failed to read file"))]
+    ReadFile { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Failed to load settings"))]
+    LoadSettings { source: InnerError },
+}
+
+fn nested_error() -> OuterError {
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    Err::<(), _>(io)
+        .context(ReadFileSnafu)
+        .context(LoadSettingsSnafu)
+        .unwrap_err()
+}
+
+#[test]
+fn compact_debug_lists_numbered_frames() {
+    let error = nested_error();
+    let stack = error.virtual_stack();
+
+    let expected = format!(
+        "Error: Failed to load settings\nVirtual Stack Trace:\n  0: {}\n  1: {}\n  2: {}\n",
+        stack[0], stack[1], stack[2]
+    );
+    assert_eq!(format!("{error:?}"), expected);
+}
+
+#[test]
+fn alternate_debug_indents_sources_as_tree() {
+    let error = nested_error();
+    let stack = error.virtual_stack();
+
+    let expected = format!(
+        "Error: Failed to load settings\nVirtual Stack Trace:\n  0: {}\n  └─ 1: {}\n     └─ 2: {}\n",
+        stack[0], stack[1], stack[2]
+    );
+    assert_eq!(format!("{error:#?}"), expected);
+}
+
+#[test]
+fn stack_display_renders_frames_without_header() {
+    let error = nested_error();
+    let stack = error.virtual_stack();
+
+    let expected = format!("  0: {}\n  1: {}\n  2: {}\n", stack[0], stack[1], stack[2]);
+    assert_eq!(StackDisplay(&error).to_string(), expected);
+    assert_eq!(error.stack_display().to_string(), expected);
+
+    let debug = format!("{error:?}");
+    assert!(debug.ends_with(&expected));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn write_stack_matches_debug_output() {
+    let error = nested_error();
+
+    let mut out = Vec::new();
+    error.write_stack(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), format!("{error:?}"));
+
+    let mut out = Vec::new();
+    let sink: &mut dyn std::io::Write = &mut out;
+    error.write_stack(sink).unwrap();
+    assert!(!out.is_empty());
+}
+
+#[stack_trace_debug(error_label = "ERR", trace_label = "Chain")]
+#[derive(Snafu)]
+enum LabeledError {
+    #[snafu(display("Something went wrong"))]
+    SomethingWrong,
+}
+
+#[test]
+fn labels_can_be_customized() {
+    let error = SomethingWrongSnafu.build();
+    let stack = error.virtual_stack();
+
+    let expected = format!("ERR: Something went wrong\nChain:\n  0: {}\n", stack[0]);
+    assert_eq!(format!("{error:?}"), expected);
+}
+
+#[stack_trace_debug(no_debug)]
+#[derive(Snafu)]
+enum HandWrittenError {
+    #[snafu(display("Hand written"))]
+    HandWritten,
+}
+
+impl std::fmt::Debug for HandWrittenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HandWrittenError")
+    }
+}
+
+#[test]
+fn no_debug_keeps_own_debug_impl() {
+    let error = HandWrittenSnafu.build();
+
+    assert_eq!(format!("{error:?}"), "HandWrittenError");
+    assert_eq!(error.virtual_stack()[0].message, "Hand written");
+}
+
+/// Checks that `line` is `  {index}: {message} at {file}:{line}:{column}` with ASCII digits.
+fn assert_frame_grammar(line: &str, index: usize) {
+    let rest = line
+        .strip_prefix(&format!("  {index}: "))
+        .unwrap_or_else(|| panic!("bad frame index in {line:?}"));
+    let (_, location) = rest.rsplit_once(" at ").unwrap();
+    let mut parts = location.rsplitn(3, ':');
+    let (column, line_number, file) = (parts.next(), parts.next(), parts.next());
+    assert_eq!(file, Some(file!()));
+    for number in [line_number.unwrap(), column.unwrap()] {
+        assert!(!number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()));
+    }
+}
+
+#[test]
+fn numbers_and_separators_ignore_format_flags() {
+    let error = nested_error();
+    let plain = format!("{error:?}");
+
+    for flagged in [
+        format!("{error:+?}"),
+        format!("{error:x?}"),
+        format!("{error:X?}"),
+        format!("{error:08?}"),
+        format!("{error:>40?}"),
+        format!("{error:.1?}"),
+    ] {
+        assert_eq!(flagged, plain);
+    }
+
+    let lines: Vec<&str> = plain.lines().skip(2).collect();
+    assert_eq!(lines.len(), 3);
+    for (index, line) in lines.iter().enumerate() {
+        assert_frame_grammar(line, index);
+    }
+}
+
+#[test]
+fn locations_ignore_format_flags() {
+    let location = snafu_virtstack::Location::new("src/main.rs", 1234, 56);
+    assert_eq!(location.to_string(), "src/main.rs:1234:56");
+    assert_eq!(format!("{location:+}"), "src/main.rs:1234:56");
+    assert_eq!(format!("{location:>30}"), "src/main.rs:1234:56");
+    assert_eq!(format!("{location:08}"), "src/main.rs:1234:56");
+}
@@ -0,0 +1,123 @@
+//! Full `Debug` output, locked in as literal strings since log parsers depend on it.
+
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Failed to read file {path}"))]
+    ReadFile {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Failed to load settings"))]
+    LoadSettings { source: InnerError },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Service is shutting down"))]
+struct ShutdownError;
+
+#[stack_trace_debug(max_depth = 2, error_label = "ERR", trace_label = "Chain")]
+#[derive(Snafu)]
+enum ShallowError {
+    #[snafu(display("Request failed"))]
+    Request { source: OuterError },
+}
+
+fn nested_error() -> OuterError {
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    Err::<(), _>(io)
+        .context(ReadFileSnafu {
+            path: "settings.toml",
+        })
+        .context(LoadSettingsSnafu)
+        .unwrap_err()
+}
+
+/// Replaces the `file:line:column` ending each frame with `[location]`, so the snapshots
+/// don't depend on where in this file the errors are created.
+fn normalize(debug: &str) -> String {
+    debug
+        .lines()
+        .map(|line| match line.rsplit_once(" at ") {
+            Some((frame, location)) if is_location(location) => format!("{frame} at [location]\n"),
+            _ => format!("{line}\n"),
+        })
+        .collect()
+}
+
+fn is_location(location: &str) -> bool {
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next().unwrap_or_default();
+    let line = parts.next().unwrap_or_default();
+    let file = parts.next().unwrap_or_default();
+    file == file!()
+        && [line, column]
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[test]
+fn nested_chain() {
+    assert_eq!(
+        normalize(&format!("{:?}", nested_error())),
+        "\
+Error: Failed to load settings
+Virtual Stack Trace:
+  0: [LoadSettings] Failed to load settings at [location]
+  1: [ReadFile] Failed to read file settings.toml at [location]
+  2: missing at [location]
+"
+    );
+}
+
+#[test]
+fn nested_chain_as_tree() {
+    assert_eq!(
+        normalize(&format!("{:#?}", nested_error())),
+        "\
+Error: Failed to load settings
+Virtual Stack Trace:
+  0: [LoadSettings] Failed to load settings at [location]
+  └─ 1: [ReadFile] Failed to read file settings.toml at [location]
+     └─ 2: missing at [location]
+"
+    );
+}
+
+#[test]
+fn struct_without_source() {
+    assert_eq!(
+        normalize(&format!("{:?}", ShutdownSnafu.build())),
+        "\
+Error: Service is shutting down
+Virtual Stack Trace:
+  0: Service is shutting down at [location]
+"
+    );
+}
+
+#[test]
+fn truncated_chain_with_custom_labels() {
+    let error = Err::<(), _>(nested_error())
+        .context(RequestSnafu)
+        .unwrap_err();
+    assert_eq!(
+        normalize(&format!("{error:?}")),
+        "\
+ERR: Request failed
+Chain:
+  0: [Request] Request failed at [location]
+  1: [LoadSettings] Failed to load settings at [location]
+  2: ... source chain truncated (possible cycle) at [location]
+"
+    );
+}
@@ -0,0 +1,63 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug(dedup)]
+#[derive(Snafu)]
+enum DedupError {
+    #[snafu(display("Failed to read config: {source}"))]
+    ReadConfig { source: std::io::Error },
+
+    #[snafu(display("Failed to open socket"))]
+    OpenSocket { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum PlainError {
+    #[snafu(display("Failed to read config: {source}"))]
+    PlainReadConfig { source: std::io::Error },
+}
+
+fn not_found() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::NotFound, "No such file or directory")
+}
+
+#[test]
+fn embedded_source_message_is_collapsed() {
+    let error = Err::<(), _>(not_found())
+        .context(ReadConfigSnafu)
+        .unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(
+        stack[0].message,
+        "Failed to read config: No such file or directory"
+    );
+}
+
+#[test]
+fn distinct_messages_are_kept() {
+    let error = Err::<(), _>(not_found())
+        .context(OpenSocketSnafu)
+        .unwrap_err();
+
+    let messages: Vec<_> = error
+        .virtual_stack()
+        .into_iter()
+        .map(|f| f.message)
+        .collect();
+    assert_eq!(
+        messages,
+        ["Failed to open socket", "No such file or directory"]
+    );
+}
+
+#[test]
+fn dedup_is_opt_in() {
+    let error = Err::<(), _>(not_found())
+        .context(PlainReadConfigSnafu)
+        .unwrap_err();
+
+    assert_eq!(error.virtual_stack().len(), 2);
+}
@@ -0,0 +1,139 @@
+use snafu::prelude::*;
+use snafu_virtstack::{Location, StackFrame, StackIter, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug(dedup_os_errors)]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to open {path}: {source}"))]
+    Open {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Failed to load config"))]
+    Load { source: std::io::Error },
+}
+
+#[stack_trace_debug(dedup_os_errors)]
+#[derive(Snafu)]
+#[snafu(display("Startup failed: {source}"))]
+struct StartupError {
+    source: ConfigError,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to open {path}: {source}"))]
+struct PlainError {
+    path: String,
+    source: std::io::Error,
+}
+
+const MISSING: &str = "/nonexistent/virtstack/config.toml";
+
+/// Opens a file that doesn't exist, returning the OS error and its display.
+fn not_found() -> (std::io::Error, String) {
+    let error = std::fs::File::open(MISSING).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    let code = format!(" (os error {})", error.raw_os_error().unwrap());
+    let display = error.to_string();
+    assert!(display.ends_with(&code));
+    let stripped = display.strip_suffix(&code).unwrap().to_string();
+    (error, stripped)
+}
+
+#[test]
+fn repeated_os_error_code_is_stripped_from_the_source() {
+    let (io, description) = not_found();
+    let full = io.to_string();
+    let error = Err::<(), _>(io)
+        .context(OpenSnafu { path: MISSING })
+        .unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(
+        stack[0].message,
+        format!("Failed to open {MISSING}: {full}")
+    );
+    assert_eq!(stack[1].message, description);
+}
+
+#[test]
+fn code_is_kept_when_the_parent_does_not_mention_it() {
+    let (io, _) = not_found();
+    let full = io.to_string();
+    let error = Err::<(), _>(io).context(LoadSnafu).unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack[1].message, full);
+}
+
+#[test]
+fn only_the_outermost_mention_is_kept() {
+    let (io, description) = not_found();
+    let full = io.to_string();
+    let error = Err::<(), _>(io)
+        .context(OpenSnafu { path: MISSING })
+        .context(StartupSnafu)
+        .unwrap_err();
+
+    let messages: Vec<_> = error
+        .virtual_stack()
+        .into_iter()
+        .map(|frame| frame.message.into_owned())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            format!("Startup failed: Failed to open {MISSING}: {full}"),
+            format!("Failed to open {MISSING}: {description}"),
+            description,
+        ]
+    );
+}
+
+#[test]
+fn disabled_by_default() {
+    let (io, _) = not_found();
+    let full = io.to_string();
+    let error = Err::<(), _>(io)
+        .context(PlainSnafu { path: MISSING })
+        .unwrap_err();
+
+    assert_eq!(error.virtual_stack()[1].message, full);
+}
+
+#[test]
+fn different_codes_are_kept() {
+    let location = Location::new("src/lib.rs", 1, 1);
+    let frames = vec![
+        StackFrame::new(location, "Failed to connect: refused (os error 111)"),
+        StackFrame::new(location, "Not found (os error 2)"),
+        StackFrame::new(location, "Not a code (os error two)"),
+    ];
+
+    let messages: Vec<_> = StackIter::from_frames(frames)
+        .dedup_os_errors()
+        .map(|frame| frame.message)
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "Failed to connect: refused (os error 111)",
+            "Not found (os error 2)",
+            "Not a code (os error two)",
+        ]
+    );
+}
+
+#[test]
+fn frames_are_counted_without_changes() {
+    let (io, _) = not_found();
+    let error = Err::<(), _>(io)
+        .context(OpenSnafu { path: MISSING })
+        .context(StartupSnafu)
+        .unwrap_err();
+
+    assert_eq!(error.virtual_stack_iter().count(), 3);
+}
@@ -0,0 +1,117 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{
+    ErrorExt, StackTraceDebug, StatusCode, VirtualStackTrace, stack_trace_debug,
+};
+
+#[derive(Snafu, StackTraceDebug)]
+enum DerivedError {
+    #[snafu(display("Failed to read {path}"))]
+    #[status_code(NotFound)]
+    Read {
+        path: String,
+        source: std::io::Error,
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display("Service unavailable"))]
+    #[external_msg("Please try again later")]
+    Unavailable,
+}
+
+mod attribute {
+    use super::*;
+
+    #[stack_trace_debug]
+    #[derive(Snafu)]
+    #[snafu(visibility(pub))]
+    pub enum AttributeError {
+        #[snafu(display("Failed to read {path}"))]
+        #[status_code(NotFound)]
+        Read {
+            path: String,
+            source: std::io::Error,
+        },
+    }
+}
+
+#[derive(Snafu, StackTraceDebug)]
+#[virtstack(error_label = "Failure", max_depth = 2)]
+#[snafu(display("Request failed"))]
+struct RequestError {
+    source: DerivedError,
+    #[snafu(implicit)]
+    location: snafu::Location,
+}
+
+fn not_found() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::NotFound, "No such file or directory")
+}
+
+/// Returns the lines of a `Debug` output without the locations of the frames.
+fn without_locations(debug: &str) -> Vec<&str> {
+    debug
+        .lines()
+        .map(|line| line.rsplit_once(" at ").map_or(line, |(frame, _)| frame))
+        .collect()
+}
+
+#[test]
+fn debug_output_matches_the_attribute_form() {
+    let line = line!() + 1;
+    let derived = Err::<(), _>(not_found()).context(ReadSnafu { path: "app.toml" });
+    let attribute = Err::<(), _>(not_found()).context(attribute::ReadSnafu { path: "app.toml" });
+    let derived = derived.unwrap_err();
+    let attribute = attribute.unwrap_err();
+
+    let debug = format!("{derived:?}");
+    assert_eq!(
+        without_locations(&debug),
+        without_locations(&format!("{attribute:?}"))
+    );
+    assert!(debug.contains(&format!(
+        "0: [Read] Failed to read app.toml at {}:{line}:",
+        file!()
+    )));
+}
+
+#[test]
+fn status_codes_and_external_messages_are_generated() {
+    let read = Err::<(), _>(not_found())
+        .context(ReadSnafu { path: "app.toml" })
+        .unwrap_err();
+    let unavailable = UnavailableSnafu.build();
+
+    assert_eq!(read.status_code(), StatusCode::NotFound);
+    assert_eq!(read.output_msg(), "Failed to read app.toml");
+    assert_eq!(unavailable.status_code(), StatusCode::Internal);
+    assert_eq!(unavailable.output_msg(), "Please try again later");
+}
+
+#[test]
+fn variants_without_location_are_located_at_the_caller() {
+    let error = UnavailableSnafu.build();
+    let line = line!() + 1;
+    let stack = error.virtual_stack();
+
+    assert_eq!(stack[0].variant, Some("Unavailable"));
+    assert_eq!(stack[0].location.line(), line);
+}
+
+#[test]
+fn arguments_are_read_from_the_virtstack_attribute() {
+    let read = Err::<(), _>(not_found())
+        .context(ReadSnafu { path: "app.toml" })
+        .unwrap_err();
+    let error = RequestSnafu.into_error(read);
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack[1].variant, Some("Read"));
+    assert_eq!(
+        stack[2].message,
+        "... source chain truncated (possible cycle)"
+    );
+    assert!(format!("{error:?}").starts_with("Failure: Request failed\n"));
+}
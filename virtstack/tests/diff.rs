@@ -0,0 +1,107 @@
+#![cfg(feature = "testing")]
+
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{FrameDiff, VirtualStackTrace, render_diff, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to read {path}"))]
+    FileRead {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Config is empty"))]
+    Empty { location: snafu::Location },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to start"))]
+struct StartError {
+    source: ConfigError,
+}
+
+fn empty_at(line: u32) -> ConfigError {
+    let location = snafu::Location::new("src/config.rs", line, 15);
+    EmptySnafu { location }.build()
+}
+
+fn empty() -> ConfigError {
+    empty_at(42)
+}
+
+fn read(message: &str) -> ConfigError {
+    let io = std::io::Error::other(message.to_string());
+    Err::<(), _>(io)
+        .context(FileReadSnafu { path: "app.toml" })
+        .unwrap_err()
+}
+
+#[test]
+fn equal_stacks_have_no_differences() {
+    let error = StartSnafu.into_error(empty());
+    assert_eq!(error.diff(&error), []);
+    assert_eq!(render_diff(&error.diff(&error)), "stacks are equal");
+}
+
+#[test]
+fn reports_changed_messages() {
+    let (left, right) = (read("disk full"), read("permission denied"));
+
+    let diffs = left.diff(&right);
+    assert_eq!(diffs.len(), 1);
+    let FrameDiff::Changed { index, left, right } = &diffs[0] else {
+        panic!("expected a changed frame, got {diffs:?}");
+    };
+    assert_eq!(*index, 1);
+    assert_eq!(left.message, "disk full");
+    assert_eq!(right.message, "permission denied");
+}
+
+#[test]
+fn reports_changed_locations() {
+    let (left, right) = (empty(), empty_at(43));
+
+    let diffs = left.diff(&right);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].index(), 0);
+    assert!(matches!(diffs[0], FrameDiff::Changed { .. }));
+}
+
+#[test]
+fn reports_extra_frames_on_either_side() {
+    let short = empty();
+    let long = StartSnafu.into_error(empty());
+
+    let removed = long.diff(&short);
+    assert_eq!(removed.len(), 2);
+    assert!(
+        matches!(&removed[1], FrameDiff::Removed { index: 1, frame } if frame.message == "Config is empty")
+    );
+
+    let added = short.diff(&long);
+    assert!(matches!(&added[1], FrameDiff::Added { index: 1, .. }));
+}
+
+#[test]
+fn compares_trait_objects() {
+    let left: Box<dyn VirtualStackTrace> = Box::new(read("disk full"));
+    let right: &dyn VirtualStackTrace = &read("disk full");
+
+    // Both frames are located where their errors were wrapped, inside `read`
+    assert_eq!(left.diff(right), []);
+}
+
+#[test]
+fn renders_each_difference() {
+    let left = StartSnafu.into_error(empty());
+    let right = empty();
+
+    let rendered = render_diff(&left.diff(&right));
+    assert!(rendered.starts_with("frame 0:\n  - Failed to start at "));
+    assert!(rendered.contains("\n  + [Empty] Config is empty at src/config.rs:42:15\n"));
+    assert!(rendered.ends_with("frame 1:\n  - [Empty] Config is empty at src/config.rs:42:15"));
+}
@@ -0,0 +1,106 @@
+use snafu::prelude::*;
+use snafu_virtstack::{StackDisplay, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to read config"))]
+    Read { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Port {port} is taken"))]
+struct BindError {
+    port: u16,
+}
+
+fn read_error() -> ConfigError {
+    let io = std::io::Error::other("disk on fire");
+    Err::<(), _>(io).context(ReadSnafu).unwrap_err()
+}
+
+fn messages(error: impl VirtualStackTrace) -> Vec<String> {
+    error
+        .virtual_stack()
+        .into_iter()
+        .map(|frame| frame.message.into_owned())
+        .collect()
+}
+
+fn first_line(error: impl VirtualStackTrace) -> u32 {
+    error.first_frame().unwrap().location.line()
+}
+
+#[test]
+fn heterogeneous_errors_can_be_stored_as_trait_objects() {
+    let errors: Vec<Box<dyn VirtualStackTrace>> = vec![
+        Box::new(read_error()),
+        Box::new(BindSnafu { port: 8080u16 }.build()),
+    ];
+
+    let counts: Vec<_> = errors.iter().map(|error| error.frame_count()).collect();
+    assert_eq!(counts, [2, 1]);
+    assert_eq!(
+        messages(&errors[0]),
+        ["Failed to read config", "disk on fire"]
+    );
+    assert_eq!(messages(&*errors[1]), ["Port 8080 is taken"]);
+    assert_eq!(messages(errors.into_iter().next().unwrap()).len(), 2);
+}
+
+#[test]
+fn references_forward_to_the_error() {
+    let error = read_error();
+    let by_ref: &dyn VirtualStackTrace = &error;
+
+    assert_eq!(messages(&error), messages(by_ref));
+    assert_eq!(
+        StackDisplay(&by_ref).to_string(),
+        error.stack_display().to_string()
+    );
+}
+
+#[test]
+fn forwarded_stacks_keep_the_original_locations() {
+    let (error, line) = (BindSnafu { port: 80u16 }.build(), line!());
+    let boxed: Box<dyn VirtualStackTrace + Send + Sync> = Box::new(error);
+
+    assert_eq!(first_line(&boxed), line);
+    assert_eq!(first_line(boxed), line);
+}
+
+/// A hand-written implementation that isn't an `Error`.
+struct Manual;
+
+impl VirtualStackTrace for Manual {
+    fn virtual_stack(&self) -> snafu_virtstack::Stack {
+        snafu_virtstack::Stack::new()
+    }
+}
+
+fn error_chain(error: &(dyn VirtualStackTrace + 'static)) -> Vec<String> {
+    let Some(error) = error.as_error() else {
+        return Vec::new();
+    };
+    std::iter::successors(Some(error), |error| error.source())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn trait_objects_can_be_used_as_errors() {
+    let errors: Vec<Box<dyn VirtualStackTrace>> = vec![
+        Box::new(read_error()),
+        Box::new(snafu_virtstack::MultiError::new("Invalid input")),
+        Box::new(Manual),
+    ];
+
+    assert_eq!(
+        error_chain(&*errors[0]),
+        ["Failed to read config", "disk on fire"]
+    );
+    assert_eq!(error_chain(&errors[0]), error_chain(&*errors[0]));
+    assert_eq!(error_chain(&*errors[1]), ["Invalid input"]);
+    assert!(error_chain(&*errors[2]).is_empty());
+}
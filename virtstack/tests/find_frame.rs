@@ -0,0 +1,50 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Failed to read file"))]
+    ReadFile { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Failed to load settings"))]
+    LoadSettings { source: InnerError },
+}
+
+fn nested_error() -> OuterError {
+    let io = std::io::Error::other("missing");
+    Err::<(), _>(io)
+        .context(ReadFileSnafu)
+        .context(LoadSettingsSnafu)
+        .unwrap_err()
+}
+
+#[test]
+fn find_frame_returns_first_match() {
+    let error = nested_error();
+
+    let frame = error.find_frame(|frame| frame.message.starts_with("Failed"));
+    assert_eq!(frame.unwrap().message, "Failed to load settings");
+
+    let frame = error.find_frame(|frame| frame.variant == Some("ReadFile"));
+    assert_eq!(frame.unwrap().message, "Failed to read file");
+
+    assert!(error.find_frame(|frame| frame.message.is_empty()).is_none());
+}
+
+#[test]
+fn first_frame_in_file_matches_path_substring() {
+    let error = nested_error();
+
+    let frame = error.first_frame_in_file("tests/find_frame.rs").unwrap();
+    assert_eq!(frame.message, "Failed to load settings");
+    assert!(error.first_frame_in_file("src/vendor/").is_none());
+
+    // Also usable through a trait object
+    let dynamic: &dyn VirtualStackTrace = &error;
+    assert!(dynamic.first_frame_in_file(file!()).is_some());
+}
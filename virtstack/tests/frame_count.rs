@@ -0,0 +1,73 @@
+use snafu::prelude::*;
+use snafu_virtstack::{StackIter, VirtualStackTrace, stack_trace_debug};
+
+/// Foreign error that must never be formatted while counting.
+#[derive(Debug)]
+struct Unprintable;
+
+impl std::fmt::Display for Unprintable {
+    fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        panic!("frame_count formatted a message")
+    }
+}
+
+impl std::error::Error for Unprintable {}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum StorageError {
+    #[snafu(display("Failed to read block"))]
+    ReadBlock { source: Unprintable },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ServiceError {
+    #[snafu(display("Request failed"))]
+    Request { source: StorageError },
+}
+
+#[stack_trace_debug(max_depth = 2)]
+#[derive(Snafu)]
+enum ShallowError {
+    #[snafu(display("Shallow"))]
+    Shallow { source: ServiceError },
+}
+
+#[stack_trace_debug(dedup)]
+#[derive(Snafu)]
+enum ReadError {
+    #[snafu(display("Failed to read: {source}"))]
+    Read { source: std::io::Error },
+}
+
+fn service_error() -> ServiceError {
+    Err::<(), _>(Unprintable)
+        .context(ReadBlockSnafu)
+        .context(RequestSnafu)
+        .unwrap_err()
+}
+
+#[test]
+fn frame_count_does_not_format_messages() {
+    let error = service_error();
+    assert_eq!(error.frame_count(), 3);
+
+    // Two frames plus the truncation frame
+    let error = Err::<(), _>(service_error())
+        .context(ShallowSnafu)
+        .unwrap_err();
+    assert_eq!(error.frame_count(), 3);
+}
+
+#[test]
+fn frame_count_matches_the_materialized_stack() {
+    let io = std::io::Error::other("missing");
+    let error = Err::<(), _>(io).context(ReadSnafu).unwrap_err();
+    assert_eq!(error.frame_count(), 1);
+    assert_eq!(error.frame_count(), error.virtual_stack().len());
+
+    let frames = error.virtual_stack().to_vec();
+    assert_eq!(StackIter::from_cached(&frames).count(), 1);
+    assert_eq!(StackIter::from_frames(frames).count(), 1);
+}
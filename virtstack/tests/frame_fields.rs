@@ -0,0 +1,95 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{StackTraceDebug, VerboseFormatter, VirtualStackTrace, stack_trace_debug};
+
+#[derive(Debug)]
+enum Method {
+    Get,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum FetchError {
+    #[snafu(display("Request to {url} failed"))]
+    Request {
+        url: String,
+        #[frame]
+        method: Method,
+        #[frame]
+        attempt: u32,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Timed out"))]
+    Timeout { elapsed_ms: u64 },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Sync failed"))]
+struct SyncError {
+    #[frame]
+    peer: &'static str,
+    source: FetchError,
+}
+
+#[derive(Snafu, StackTraceDebug)]
+#[snafu(display("Lookup failed"))]
+struct LookupError {
+    #[frame]
+    key: Option<u8>,
+    #[snafu(implicit)]
+    location: snafu::Location,
+}
+
+fn request_error() -> FetchError {
+    let io = std::io::Error::other("connection reset");
+    RequestSnafu {
+        url: "https://example.com",
+        method: Method::Get,
+        attempt: 3u32,
+    }
+    .into_error(io)
+}
+
+fn pairs(frame: &snafu_virtstack::StackFrame) -> Vec<(&str, &str)> {
+    frame
+        .metadata
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect()
+}
+
+#[test]
+fn marked_fields_are_recorded_with_their_debug_output() {
+    let stack = request_error().virtual_stack();
+
+    assert_eq!(pairs(&stack[0]), [("method", "Get"), ("attempt", "3")]);
+    // Only the frame of the error itself carries its fields
+    assert!(stack[1].metadata.is_empty());
+}
+
+#[test]
+fn unmarked_variants_and_fields_are_left_out() {
+    let error = TimeoutSnafu { elapsed_ms: 10u64 }.build();
+    assert!(error.virtual_stack()[0].metadata.is_empty());
+}
+
+#[test]
+fn struct_fields_are_recorded_on_their_own_frame() {
+    let error = SyncSnafu { peer: "eu-1" }.into_error(request_error());
+    let stack = error.virtual_stack();
+
+    assert_eq!(pairs(&stack[0]), [("peer", "\"eu-1\"")]);
+    assert_eq!(pairs(&stack[1]), [("method", "Get"), ("attempt", "3")]);
+
+    let rendered = error.render_with(&VerboseFormatter);
+    assert!(rendered.contains("\n      with peer=\"eu-1\"\n"));
+    assert!(rendered.contains("\n      with method=Get attempt=3\n"));
+}
+
+#[test]
+fn derived_impls_record_marked_fields() {
+    let error = LookupSnafu { key: Some(7u8) }.build();
+    assert_eq!(pairs(&error.virtual_stack()[0]), [("key", "Some(7)")]);
+}
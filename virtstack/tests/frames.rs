@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{FrameView, Location, StackFrame, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug(cache)]
+#[derive(Snafu)]
+enum CachedError {
+    #[snafu(display("Failed to read {path}"))]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum PlainError {
+    #[snafu(display("Config is empty"))]
+    Empty,
+
+    #[snafu(display("Failed to parse {path}"))]
+    Parse { path: String },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ServiceError {
+    #[snafu(display("Service unavailable"))]
+    Unavailable { source: PlainError },
+
+    #[snafu(display("Request failed"))]
+    Request { source: std::io::Error },
+}
+
+fn cached_error() -> CachedError {
+    ReadSnafu { path: "app.toml" }.into_error(std::io::Error::other("disk full"))
+}
+
+#[test]
+fn views_match_the_owned_frames() {
+    let error = cached_error();
+    let stack = error.virtual_stack();
+
+    let views: Vec<FrameView<'_>> = error.frames().collect();
+    assert_eq!(views.len(), stack.len());
+    for (view, frame) in views.iter().zip(&stack) {
+        assert_eq!(view.location, frame.location);
+        assert_eq!(view.message, frame.message);
+        assert_eq!(view.variant, frame.variant);
+        assert_eq!(view.to_string(), frame.to_string());
+    }
+}
+
+#[test]
+fn cached_frames_are_borrowed() {
+    let error = cached_error();
+    // Fill the cache
+    error.virtual_stack();
+
+    for view in error.frames() {
+        assert!(matches!(view.message, Cow::Borrowed(_)), "{view:?}");
+    }
+}
+
+#[test]
+fn constant_messages_are_borrowed() {
+    let error = EmptySnafu.build();
+    let view = error.frames().next().unwrap();
+    assert!(matches!(view.message, Cow::Borrowed("Config is empty")));
+
+    let error = ParseSnafu { path: "app.toml" }.build();
+    let view = error.frames().next().unwrap();
+    assert!(matches!(view.message, Cow::Owned(_)));
+}
+
+#[test]
+fn constant_messages_of_nested_errors_are_borrowed() {
+    let error = UnavailableSnafu.into_error(EmptySnafu.build());
+
+    let views: Vec<_> = error.frames().collect();
+    assert!(matches!(
+        views[0].message,
+        Cow::Borrowed("Service unavailable")
+    ));
+    assert!(matches!(views[1].message, Cow::Borrowed("Config is empty")));
+}
+
+#[test]
+fn foreign_messages_are_owned() {
+    let error = RequestSnafu.into_error(std::io::Error::other("connection reset"));
+
+    let views: Vec<_> = error.frames().collect();
+    assert!(matches!(views[0].message, Cow::Borrowed("Request failed")));
+    assert!(matches!(&views[1].message, Cow::Owned(message) if message == "connection reset"));
+}
+
+#[test]
+fn counts_frames() {
+    assert_eq!(cached_error().frames().count(), 2);
+    assert_eq!(EmptySnafu.build().frames().count(), 1);
+}
+
+#[test]
+fn views_convert_into_owned_frames() {
+    let frame = StackFrame::new(Location::new("src/lib.rs", 3, 7), "Failed").with_variant("Load");
+
+    let view = FrameView::from(&frame);
+    assert_eq!(view.to_string(), "[Load] Failed at src/lib.rs:3:7");
+    assert_eq!(view.into_owned(), frame);
+}
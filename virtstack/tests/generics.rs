@@ -0,0 +1,58 @@
+use std::fmt::Display;
+
+use snafu::prelude::*;
+use snafu_virtstack::{ErrorExt, StatusCode, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum LookupError<K: Display> {
+    #[snafu(display("No entry for key {key}"))]
+    #[status_code(NotFound)]
+    Missing { key: K },
+
+    #[snafu(display("Failed to read entry for key {key}"))]
+    Read { key: K, source: std::io::Error },
+}
+
+// `T` never shows up in a display string, so it needs no `Display` bound
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Rejected payload"))]
+struct RejectedError<T> {
+    payload: T,
+}
+
+#[test]
+fn generic_payloads_render_their_display() {
+    let error = MissingSnafu { key: 42 }.build::<i32>();
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "No entry for key 42");
+    assert_eq!(error.status_code(), StatusCode::NotFound);
+
+    let io = std::io::Error::other("disk on fire");
+    let error: LookupError<&str> = Err::<(), _>(io)
+        .context(ReadSnafu { key: "users" })
+        .unwrap_err();
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "Failed to read entry for key users");
+    assert_eq!(stack[1].message, "disk on fire");
+
+    let debug = format!("{error:?}");
+    assert!(debug.starts_with("Error: Failed to read entry for key users\n"));
+}
+
+#[test]
+fn undisplayed_type_parameters_need_no_bounds() {
+    let error: RejectedError<Vec<u8>> = RejectedSnafu {
+        payload: vec![0u8; 4],
+    }
+    .build();
+    assert_eq!(error.payload.len(), 4);
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "Rejected payload");
+    assert!(format!("{error:?}").starts_with("Error: Rejected payload\n"));
+}
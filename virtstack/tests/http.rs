@@ -0,0 +1,85 @@
+#![cfg(feature = "http")]
+
+use axum_core::response::IntoResponse;
+use http_body_util::BodyExt;
+use snafu::prelude::*;
+use snafu_virtstack::{HttpError, StatusCode, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("User {id} not found"))]
+    #[status_code(NotFound)]
+    UserNotFound { id: u64, source: std::io::Error },
+
+    #[snafu(display("Something went wrong"))]
+    SomethingWrong,
+
+    #[snafu(display("Replica {replica} timed out"))]
+    #[status_code(Unavailable)]
+    #[external_msg("Service temporarily unavailable")]
+    ReplicaTimeout { replica: String },
+}
+
+fn not_found() -> MyError {
+    let io = std::io::Error::other("row missing in users table");
+    Err::<(), _>(io)
+        .context(UserNotFoundSnafu { id: 7u64 })
+        .unwrap_err()
+}
+
+fn into_parts(error: HttpError<MyError>) -> (http::StatusCode, String, serde_json::Value) {
+    let response = error.into_response();
+    let status = response.status();
+    let content_type = response.headers()[http::header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = pollster::block_on(response.into_body().collect())
+        .unwrap()
+        .to_bytes();
+    (status, content_type, serde_json::from_slice(&body).unwrap())
+}
+
+#[test]
+fn response_exposes_only_public_message() {
+    let (status, content_type, body) = into_parts(HttpError::from(not_found()));
+
+    assert_eq!(status, http::StatusCode::NOT_FOUND);
+    assert_eq!(content_type, "application/json");
+    assert_eq!(
+        body,
+        serde_json::json!({ "code": "NotFound", "message": "User 7 not found" })
+    );
+}
+
+#[test]
+fn stack_can_be_included_in_body() {
+    let (_, _, body) = into_parts(HttpError::new(not_found()).include_stack(true));
+
+    let stack = body["stack"].as_array().unwrap();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0]["variant"], "UserNotFound");
+    assert_eq!(stack[1]["message"], "row missing in users table");
+    assert_eq!(stack[1]["file"], file!());
+}
+
+#[test]
+fn unannotated_variants_are_internal_server_errors() {
+    let (status, _, body) = into_parts(SomethingWrongSnafu.build().into());
+
+    assert_eq!(status, http::StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["code"], "Internal");
+    assert_eq!(
+        http::StatusCode::from(StatusCode::RateLimited),
+        http::StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+#[test]
+fn external_messages_replace_the_display_string() {
+    let error = ReplicaTimeoutSnafu { replica: "db-3" }.build();
+    let (_, _, body) = into_parts(error.into());
+
+    assert_eq!(body["message"], "Service temporarily unavailable");
+}
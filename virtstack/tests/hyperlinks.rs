@@ -0,0 +1,37 @@
+#![cfg(feature = "hyperlinks")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Something went wrong"))]
+    SomethingWrong,
+}
+
+#[test]
+fn force_hyperlink_links_locations_to_files() {
+    // SAFETY: no other thread of this test binary reads the environment
+    unsafe {
+        std::env::set_var("FORCE_HYPERLINK", "1");
+        std::env::set_var("NO_COLOR", "1");
+    }
+
+    let error = SomethingWrongSnafu.build();
+    let location = error.virtual_stack()[0].location;
+
+    // `file!()` is relative to the workspace root, one level above this package
+    let workspace = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap();
+    let path = workspace.join(file!());
+    let expected = format!(
+        "  0: [SomethingWrong] Something went wrong at \x1b]8;;file://{}\x1b\\{location}\x1b]8;;\x1b\\\n",
+        path.display()
+    );
+    assert!(format!("{error:?}").ends_with(&expected));
+
+    // Only the rendered trace is linked, not the plain `Display` of a frame
+    assert!(!error.virtual_stack()[0].to_string().contains('\x1b'));
+}
@@ -0,0 +1,91 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, iter_chain, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum LoadError {
+    #[snafu(display("Failed to load settings"))]
+    LoadSettings { source: std::io::Error },
+}
+
+/// An error that implements neither `VirtualStackTrace` nor Snafu's traits.
+#[derive(Debug)]
+struct Wrapped<'a> {
+    context: &'a str,
+    source: LoadError,
+}
+
+impl std::fmt::Display for Wrapped<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "While {}", self.context)
+    }
+}
+
+impl std::error::Error for Wrapped<'_> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+fn load_error() -> LoadError {
+    let io = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+    Err::<(), _>(io).context(LoadSettingsSnafu).unwrap_err()
+}
+
+#[test]
+fn the_error_itself_comes_first() {
+    let error = load_error();
+
+    let chain: Vec<_> = iter_chain(&error).collect();
+    assert_eq!(chain.len(), 2);
+    assert!(chain[0].is::<LoadError>());
+    assert_eq!(chain[1].to_string(), "denied");
+}
+
+#[test]
+fn walks_errors_without_a_virtual_stack() {
+    let context = String::from("starting up");
+    let error = Wrapped {
+        context: &context,
+        source: load_error(),
+    };
+
+    let messages: Vec<_> = iter_chain(&error).map(|e| e.to_string()).collect();
+    assert_eq!(
+        messages,
+        ["While starting up", "Failed to load settings", "denied"]
+    );
+}
+
+#[test]
+fn matches_snafu_and_sources() {
+    let error = load_error();
+
+    let ours: Vec<_> = iter_chain(&error).map(|e| e.to_string()).collect();
+    let snafus: Vec<_> = snafu::ErrorCompat::iter_chain(&error)
+        .map(|e| e.to_string())
+        .collect();
+    let sources: Vec<_> = error.sources().map(|e| e.to_string()).collect();
+    assert_eq!(ours, snafus);
+    assert_eq!(ours, sources);
+}
+
+#[test]
+fn yields_typed_errors_for_downcasting() {
+    let boxed: Box<dyn std::error::Error> = Box::new(load_error());
+
+    let io = iter_chain(boxed.as_ref())
+        .find_map(|e| e.downcast_ref::<std::io::Error>())
+        .unwrap();
+    assert_eq!(io.kind(), std::io::ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn ends_after_the_root_cause() {
+    let error = std::io::Error::other("leaf");
+
+    let mut chain = iter_chain(&error);
+    assert!(chain.next().is_some());
+    assert!(chain.next().is_none());
+    assert!(chain.next().is_none());
+}
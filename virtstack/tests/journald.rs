@@ -0,0 +1,84 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{Location, Stack, StackFrame, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ImportError {
+    #[snafu(display("Failed to import {name}"))]
+    Import {
+        name: String,
+        source: std::io::Error,
+    },
+}
+
+/// A stack with fixed locations and messages.
+struct Frames(Vec<(&'static str, &'static str)>);
+
+impl VirtualStackTrace for Frames {
+    fn virtual_stack(&self) -> Stack {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, (file, message))| {
+                StackFrame::new(Location::new(file, index as u32 + 1, 1), *message)
+            })
+            .collect()
+    }
+}
+
+fn field(key: &str, value: impl ToString) -> (String, String) {
+    (key.to_string(), value.to_string())
+}
+
+#[test]
+fn lists_the_outermost_error_and_each_frame() {
+    let io = std::io::Error::other("denied");
+    let line = line!() + 1;
+    let error = ImportSnafu { name: "users" }.into_error(io);
+
+    assert_eq!(
+        error.journald_fields(),
+        [
+            field("MESSAGE", "Failed to import users"),
+            field("CODE_FILE", file!()),
+            field("CODE_LINE", line),
+            field(
+                "VIRTSTACK_0",
+                format!("[Import] Failed to import users at {}:{line}:47", file!())
+            ),
+            field("VIRTSTACK_1", format!("denied at {}:{line}:47", file!())),
+        ]
+    );
+}
+
+#[test]
+fn keys_follow_the_journald_convention() {
+    let frames = Frames(vec![("src/a.rs", "failed"), ("src/b.rs", "root")]);
+
+    for (key, _) in frames.journald_fields() {
+        assert!(
+            key.chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'),
+            "invalid journald field name {key:?}"
+        );
+        assert!(!key.starts_with('_'));
+    }
+}
+
+#[test]
+fn multiline_messages_are_kept_intact() {
+    let frames = Frames(vec![("src/a.rs", "failed\nwith details")]);
+
+    let fields = frames.journald_fields();
+    assert_eq!(fields[0], field("MESSAGE", "failed\nwith details"));
+    assert_eq!(
+        fields[3],
+        field("VIRTSTACK_0", "failed\nwith details at src/a.rs:1:1")
+    );
+}
+
+#[test]
+fn empty_stacks_have_no_fields() {
+    assert_eq!(Frames(Vec::new()).journald_fields(), []);
+}
@@ -0,0 +1,54 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static FORMATTED: AtomicUsize = AtomicUsize::new(0);
+
+/// A source that counts how often it gets formatted.
+#[derive(Debug)]
+struct Expensive;
+
+impl std::fmt::Display for Expensive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        FORMATTED.fetch_add(1, Ordering::SeqCst);
+        write!(f, "expensive")
+    }
+}
+
+impl std::error::Error for Expensive {}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Outer"))]
+    Outer { source: Expensive },
+}
+
+#[test]
+fn iterator_formats_frames_on_demand() {
+    let error = Err::<(), _>(Expensive).context(OuterSnafu).unwrap_err();
+
+    let first = error.first_frame().unwrap();
+    assert_eq!(first.message, "Outer");
+    assert_eq!(first.variant, Some("Outer"));
+    assert_eq!(FORMATTED.load(Ordering::SeqCst), 0);
+
+    let mut frames = error.virtual_stack_iter();
+    assert_eq!(frames.next().unwrap().message, "Outer");
+    assert_eq!(FORMATTED.load(Ordering::SeqCst), 0);
+
+    assert_eq!(frames.next().unwrap().message, "expensive");
+    assert_eq!(FORMATTED.load(Ordering::SeqCst), 1);
+    assert!(frames.next().is_none());
+
+    let collected: Vec<_> = error.virtual_stack_iter().map(|f| f.message).collect();
+    let stack: Vec<_> = error
+        .virtual_stack()
+        .into_iter()
+        .map(|f| f.message)
+        .collect();
+    assert_eq!(collected, stack);
+
+    assert_eq!(error.last_frame().unwrap().message, "expensive");
+    assert_eq!(error.last_frame(), error.root_cause());
+}
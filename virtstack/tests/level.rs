@@ -0,0 +1,83 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{ErrorExt, Level, StackTraceDebug, StatusCode, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum JobError {
+    #[snafu(display("Retrying job {id}"))]
+    #[level(Warn)]
+    Retry { id: u64 },
+
+    #[snafu(display("Job {id} failed"))]
+    Failed { id: u64, source: std::io::Error },
+
+    #[snafu(display("Scheduler crashed"))]
+    #[status_code(Unavailable)]
+    #[level(Fatal)]
+    SchedulerCrashed,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Disk is full"))]
+#[level(Fatal)]
+struct DiskFullError;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Queue is empty"))]
+struct QueueEmptyError;
+
+#[derive(Snafu, StackTraceDebug)]
+enum CacheError {
+    #[snafu(display("Cache miss"))]
+    #[level(Warn)]
+    Miss,
+}
+
+#[test]
+fn variants_pick_their_level() {
+    assert_eq!(RetrySnafu { id: 1u64 }.build().level(), Level::Warn);
+    assert_eq!(SchedulerCrashedSnafu.build().level(), Level::Fatal);
+
+    let error = FailedSnafu { id: 2u64 }.into_error(std::io::Error::other("timeout"));
+    assert_eq!(error.level(), Level::Error);
+}
+
+#[test]
+fn levels_and_status_codes_are_independent() {
+    let error = SchedulerCrashedSnafu.build();
+    assert_eq!(error.status_code(), StatusCode::Unavailable);
+    assert_eq!(error.level(), Level::Fatal);
+    assert_eq!(
+        RetrySnafu { id: 1u64 }.build().status_code(),
+        StatusCode::Internal
+    );
+}
+
+#[test]
+fn structs_pick_their_level() {
+    assert_eq!(DiskFullSnafu.build().level(), Level::Fatal);
+    assert_eq!(QueueEmptySnafu.build().level(), Level::Error);
+}
+
+#[test]
+fn derive_form_reads_levels() {
+    assert_eq!(MissSnafu.build().level(), Level::Warn);
+}
+
+#[test]
+fn levels_are_ordered_by_severity() {
+    assert!(Level::Warn < Level::Error);
+    assert!(Level::Error < Level::Fatal);
+    assert_eq!(Level::default(), Level::Error);
+    assert_eq!(Level::Fatal.to_string(), "Fatal");
+    assert_eq!(Level::Warn.as_str(), "Warn");
+}
+
+#[test]
+fn debug_output_is_unchanged_without_colors() {
+    let rendered = format!("{:?}", SchedulerCrashedSnafu.build());
+    assert!(rendered.starts_with("Error: Scheduler crashed\nVirtual Stack Trace:\n"));
+}
@@ -0,0 +1,90 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{
+    ErrorExt, StackTraceDebug, StatusCode, VirtualStackTrace, stack_trace_debug,
+};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ParseError<'a> {
+    #[snafu(display("Unexpected token {token:?}"))]
+    #[status_code(InvalidArguments)]
+    Unexpected { token: &'a str },
+
+    #[snafu(display("Failed to read {path}"))]
+    Read {
+        path: &'a str,
+        source: std::io::Error,
+    },
+}
+
+// `Error::source` returns a `'static` trait object, so borrowed errors can only be sources
+// when they borrow `'static` data
+#[stack_trace_debug(cache)]
+#[derive(Snafu)]
+#[snafu(display("Invalid config line {line}"))]
+struct ConfigError<'a> {
+    line: usize,
+    #[frame]
+    origin: &'a str,
+    source: ParseError<'static>,
+}
+
+#[derive(Snafu, StackTraceDebug)]
+#[snafu(display("Unknown key {key}"))]
+struct KeyError<'k> {
+    key: &'k str,
+    #[snafu(implicit)]
+    location: snafu::Location,
+}
+
+#[test]
+fn borrowed_fields_render_their_display() {
+    let input = String::from("fn main");
+    let error = UnexpectedSnafu { token: &input[3..] }.build();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "Unexpected token \"main\"");
+    assert_eq!(stack[0].variant, Some("Unexpected"));
+    assert_eq!(error.status_code(), StatusCode::InvalidArguments);
+    assert!(format!("{error:?}").starts_with("Error: Unexpected token \"main\"\n"));
+}
+
+#[test]
+fn borrowed_errors_nest_static_sources() {
+    let origin = String::from("cli");
+    let io = std::io::Error::other("missing");
+    let parse = Err::<(), _>(io)
+        .context(ReadSnafu { path: "app.toml" })
+        .unwrap_err();
+    let error = ConfigSnafu {
+        line: 3usize,
+        origin: origin.as_str(),
+    }
+    .into_error(parse);
+
+    let messages: Vec<_> = error
+        .virtual_stack()
+        .iter()
+        .map(|frame| frame.message.to_string())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "Invalid config line 3",
+            "Failed to read app.toml",
+            "missing"
+        ]
+    );
+    assert_eq!(error.virtual_stack()[0].metadata[0].1, "\"cli\"");
+    assert_eq!(error.render(), format!("{error:?}"));
+}
+
+#[test]
+fn derived_impls_accept_lifetimes() {
+    let key = String::from("timeout");
+    let error = KeySnafu { key: key.as_str() }.build();
+    assert_eq!(error.virtual_stack()[0].message, "Unknown key timeout");
+    assert_eq!(error.render(), format!("{error:?}"));
+}
@@ -0,0 +1,172 @@
+use snafu::prelude::*;
+use snafu_virtstack::{StackTraceDebug, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Inner failure"))]
+    Failure,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Outer failure"))]
+    Wrapped { source: InnerError },
+
+    #[snafu(display("Failed to read {path}"))]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+#[derive(Snafu, StackTraceDebug)]
+enum DerivedError {
+    #[snafu(display("Not located"))]
+    Unlocated,
+
+    #[snafu(display("Located"))]
+    Located {
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+}
+
+fn inner() -> (Result<(), InnerError>, u32) {
+    (FailureSnafu.fail(), line!())
+}
+
+#[test]
+fn root_frame_uses_variant_location() {
+    let (result, line) = inner();
+    let error = result.unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].location.file(), file!());
+    assert_eq!(stack[0].location.line(), line);
+}
+
+#[test]
+fn location_is_independent_of_inspection_site() {
+    let (result, _) = inner();
+    let error = result.unwrap_err();
+
+    let first = error.virtual_stack();
+    let second = error.virtual_stack();
+    assert_eq!(first[0].location.line(), second[0].location.line());
+}
+
+#[test]
+fn foreign_sources_use_wrapping_location() {
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    let selector = ReadSnafu {
+        path: "config.json",
+    };
+    let (result, line) = (Err::<(), _>(io).context(selector), line!());
+    let error = result.unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "Failed to read config.json");
+    assert_eq!(stack[1].message, "missing");
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(stack[1].location.line(), line);
+}
+
+#[test]
+fn wrapped_errors_capture_context_site() {
+    let (inner_result, _) = inner();
+    let (result, line) = (inner_result.context(WrappedSnafu), line!());
+    let error = result.unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack[0].message, "Outer failure");
+    assert_eq!(stack[0].location.line(), line);
+}
+
+#[test]
+fn nested_sources_keep_their_own_location() {
+    let (inner_result, inner_line) = inner();
+    let (result, outer_line) = (inner_result.context(WrappedSnafu), line!());
+    let error = result.unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].location.line(), outer_line);
+    assert_eq!(stack[1].message, "Inner failure");
+    assert_eq!(stack[1].location.line(), inner_line);
+}
+
+#[test]
+fn frames_compare_and_hash_by_value() {
+    let (inner_result, _) = inner();
+    let error = inner_result.context(WrappedSnafu).unwrap_err();
+
+    let first = error.virtual_stack();
+    let second = error.virtual_stack();
+    assert_eq!(first, second);
+    assert_ne!(first[0], first[1]);
+
+    let unique: std::collections::HashSet<_> = first.iter().chain(second.iter()).collect();
+    assert_eq!(unique.len(), 2);
+}
+
+/// Builds a frame from a `snafu::Location` stored in, and borrowed from, an error.
+fn frame_at(location: &snafu::Location) -> snafu_virtstack::StackFrame {
+    snafu_virtstack::StackFrame::new(location, "Failed to read config")
+}
+
+#[test]
+fn frames_can_be_built_from_snafu_locations() {
+    let location = snafu::Location::new("src/config.rs", 42, 15);
+
+    let frame = snafu_virtstack::StackFrame::new(location, "Failed to read config");
+    assert_eq!(frame, frame_at(&location));
+    assert_eq!(frame.location.file(), "src/config.rs");
+    assert_eq!(frame.location.line(), 42);
+    assert_eq!(frame.location.column(), 15);
+}
+
+fn read_config() -> (Result<(), OuterError>, u32) {
+    let read = || -> Result<(), OuterError> {
+        let selector = ReadSnafu {
+            path: "config.json",
+        };
+        Err(std::io::Error::other("missing")).context(selector)?;
+        Ok(())
+    };
+    (read(), line!() - 3)
+}
+
+#[test]
+fn location_returns_the_context_call_site() {
+    let (result, line) = read_config();
+    let error = result.unwrap_err();
+
+    let location = error.location().unwrap();
+    assert_eq!(location.file(), file!());
+    assert_eq!(location.line(), line);
+    assert_eq!(location, error.virtual_stack()[0].location);
+}
+
+#[test]
+fn location_is_the_outermost_one() {
+    let (inner_result, _) = inner();
+    let (result, line) = (inner_result.context(WrappedSnafu), line!());
+    let error = result.unwrap_err();
+
+    assert_eq!(error.location().unwrap().line(), line);
+
+    let boxed: Box<dyn VirtualStackTrace> = Box::new(error);
+    assert_eq!(boxed.location().unwrap().line(), line);
+}
+
+#[test]
+fn variants_without_a_location_field_return_none() {
+    assert_eq!(UnlocatedSnafu.build().location(), None);
+
+    let (error, line) = (LocatedSnafu.build(), line!());
+    assert_eq!(error.location().unwrap().line(), line);
+}
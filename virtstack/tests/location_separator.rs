@@ -0,0 +1,54 @@
+#![cfg(feature = "std")]
+
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{
+    DEFAULT_LOCATION_SEPARATOR, Location, StackFrame, location_separator, set_location_separator,
+    stack_trace_debug,
+};
+
+#[stack_trace_debug(location_separator = " | ")]
+#[derive(Snafu)]
+enum PipeError {
+    #[snafu(display("Failed to load settings"))]
+    Load { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Port {port} is taken"))]
+struct BindError {
+    port: u16,
+}
+
+#[test]
+fn argument_replaces_the_separator_in_debug_output() {
+    let line = line!() + 1;
+    let error = LoadSnafu.into_error(std::io::Error::other("denied"));
+
+    let location = format!("{}:{line}:27", file!());
+    assert_eq!(
+        format!("{error:?}"),
+        format!(
+            "Error: Failed to load settings\nVirtual Stack Trace:\n  \
+             0: [Load] Failed to load settings | {location}\n  1: denied | {location}\n"
+        )
+    );
+}
+
+// The default and the override are checked in one test, as the separator is process-wide
+#[test]
+fn global_separator_applies_to_frames_and_debug_output() {
+    let frame = StackFrame::new(Location::new("src/net.rs", 3, 9), "Port 80 is taken");
+    let error = BindSnafu { port: 80u16 }.build();
+    assert_eq!(location_separator(), DEFAULT_LOCATION_SEPARATOR);
+    assert_eq!(frame.to_string(), "Port 80 is taken at src/net.rs:3:9");
+
+    set_location_separator(" @ ");
+    let rendered = frame.to_string();
+    let debug = format!("{error:?}");
+    set_location_separator(DEFAULT_LOCATION_SEPARATOR);
+
+    assert_eq!(rendered, "Port 80 is taken @ src/net.rs:3:9");
+    assert!(debug.contains("\n  0: Port 80 is taken @ "));
+}
@@ -0,0 +1,88 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{Location, Stack, StackFrame, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ImportError {
+    #[snafu(display("Failed to import {name}"))]
+    Import {
+        name: String,
+        source: std::io::Error,
+    },
+}
+
+/// A stack with fixed locations and messages.
+struct Frames(Vec<(&'static str, &'static str)>);
+
+impl VirtualStackTrace for Frames {
+    fn virtual_stack(&self) -> Stack {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, (file, message))| {
+                StackFrame::new(Location::new(file, index as u32 + 1, 1), *message)
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn renders_the_message_and_each_frame() {
+    let io = std::io::Error::other("denied");
+    let line = line!() + 1;
+    let error = ImportSnafu { name: "users" }.into_error(io);
+
+    assert_eq!(
+        error.to_logfmt(),
+        format!(
+            "msg=\"Failed to import users\" \
+             frame0_file={file} frame0_line={line} frame0_msg=\"Failed to import users\" \
+             frame1_file={file} frame1_line={line} frame1_msg=denied",
+            file = file!()
+        )
+    );
+}
+
+#[test]
+fn plain_words_are_left_unquoted() {
+    let frames = Frames(vec![("src/a.rs", "failed"), ("src/b.rs", "root")]);
+    assert_eq!(
+        frames.to_logfmt(),
+        "msg=failed frame0_file=src/a.rs frame0_line=1 frame0_msg=failed \
+         frame1_file=src/b.rs frame1_line=2 frame1_msg=root"
+    );
+}
+
+#[test]
+fn quotes_and_backslashes_are_escaped() {
+    let frames = Frames(vec![(r"C:\src\a.rs", r#"bad "name" in a\b"#)]);
+    assert_eq!(
+        frames.to_logfmt(),
+        r#"msg="bad \"name\" in a\\b" frame0_file="C:\\src\\a.rs" frame0_line=1 frame0_msg="bad \"name\" in a\\b""#
+    );
+}
+
+#[test]
+fn control_characters_are_escaped() {
+    let frames = Frames(vec![("src/a.rs", "first line\nsecond\tcolumn\r\u{1b}[0m")]);
+    let line = frames.to_logfmt();
+
+    assert!(!line.contains(['\n', '\t', '\r', '\u{1b}']));
+    assert!(line.starts_with(r#"msg="first line\nsecond\tcolumn\r\u{001b}[0m" "#));
+}
+
+#[test]
+fn empty_values_and_equal_signs_are_quoted() {
+    let frames = Frames(vec![("src/a.rs", ""), ("src/b.rs", "retries=3")]);
+    assert_eq!(
+        frames.to_logfmt(),
+        "msg=\"\" frame0_file=src/a.rs frame0_line=1 frame0_msg=\"\" \
+         frame1_file=src/b.rs frame1_line=2 frame1_msg=\"retries=3\""
+    );
+}
+
+#[test]
+fn empty_stacks_render_nothing() {
+    assert_eq!(Frames(Vec::new()).to_logfmt(), "");
+}
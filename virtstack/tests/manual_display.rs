@@ -0,0 +1,101 @@
+use snafu_virtstack::{StackTraceDebug, VirtualStackTrace};
+
+/// An error without Snafu, with hand-written `Display` and `Error` implementations.
+#[derive(StackTraceDebug)]
+enum FetchError {
+    Timeout {
+        seconds: u64,
+        location: snafu::Location,
+    },
+    Decode {
+        source: std::io::Error,
+        location: snafu::Location,
+    },
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout { seconds, .. } => write!(f, "Timed out after {seconds}s"),
+            Self::Decode { .. } => write!(f, "Failed to decode response"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Timeout { .. } => None,
+            Self::Decode { source, .. } => Some(source),
+        }
+    }
+}
+
+#[derive(StackTraceDebug)]
+struct ParseError {
+    line: usize,
+    source: FetchError,
+    location: snafu::Location,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Parse error on line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+fn here(line: u32) -> snafu::Location {
+    snafu::Location::new(file!(), line, 1)
+}
+
+#[test]
+fn frames_use_the_manual_display() {
+    let error = FetchError::Timeout {
+        seconds: 30,
+        location: here(7),
+    };
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "Timed out after 30s");
+    assert_eq!(stack[0].variant, Some("Timeout"));
+    assert_eq!(stack[0].location.line(), 7);
+}
+
+#[test]
+fn sources_are_walked_through_the_manual_error_impl() {
+    let error = ParseError {
+        line: 3,
+        source: FetchError::Decode {
+            source: std::io::Error::other("unexpected end of file"),
+            location: here(12),
+        },
+        location: here(20),
+    };
+
+    let messages: Vec<_> = error
+        .frames()
+        .map(|frame| frame.message.into_owned())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "Parse error on line 3",
+            "Failed to decode response",
+            "unexpected end of file"
+        ]
+    );
+
+    let rendered = format!("{error:?}");
+    assert!(rendered.starts_with("Error: Parse error on line 3\nVirtual Stack Trace:\n"));
+    assert!(rendered.contains(&format!(
+        "  1: [Decode] Failed to decode response at {}:12:1\n",
+        file!()
+    )));
+}
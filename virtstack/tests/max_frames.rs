@@ -0,0 +1,103 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{StackDisplay, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug(max_frames = 2)]
+#[derive(Snafu)]
+enum ServiceError {
+    #[snafu(display("Request failed"))]
+    Request { source: QueryError },
+
+    #[snafu(display("Health check failed"))]
+    Health { source: std::io::Error },
+}
+
+#[stack_trace_debug(max_frames = 1)]
+#[derive(Snafu)]
+#[snafu(display("Query failed"))]
+struct QueryError {
+    source: PoolError,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Pool exhausted"))]
+struct PoolError {
+    source: std::io::Error,
+}
+
+#[stack_trace_debug(max_frames = 1)]
+#[derive(Snafu)]
+#[snafu(display("Connect failed"))]
+struct ConnectError {
+    source: std::io::Error,
+}
+
+fn request_error() -> ServiceError {
+    let pool = PoolSnafu.into_error(std::io::Error::other("too many connections"));
+    RequestSnafu.into_error(QuerySnafu.into_error(pool))
+}
+
+#[test]
+fn frames_past_the_limit_are_summarized() {
+    let rendered = format!("{:?}", request_error());
+
+    assert!(rendered.contains("  0: [Request] Request failed at "));
+    assert!(rendered.contains("  1: Query failed at "));
+    assert!(!rendered.contains("Pool exhausted"));
+    assert!(rendered.ends_with("\n  ... (2 more frames)\n"));
+}
+
+#[test]
+fn a_single_hidden_frame_is_singular() {
+    let error = ConnectSnafu.into_error(std::io::Error::other("refused"));
+
+    let rendered = format!("{error:?}");
+    assert!(rendered.contains("  0: Connect failed at "));
+    assert!(rendered.ends_with("\n  ... (1 more frame)\n"));
+}
+
+#[test]
+fn stacks_within_the_limit_are_unchanged() {
+    let error = HealthSnafu.into_error(std::io::Error::other("unreachable"));
+
+    let rendered = format!("{error:?}");
+    assert!(rendered.contains("  1: unreachable at "));
+    assert!(!rendered.contains("more frame"));
+}
+
+#[test]
+fn alternate_debug_is_limited_too() {
+    let rendered = format!("{:#?}", request_error());
+
+    assert!(rendered.contains("  └─ 1: Query failed at "));
+    assert!(!rendered.contains("Pool exhausted"));
+    assert!(rendered.ends_with("\n  ... (2 more frames)\n"));
+}
+
+#[test]
+fn the_stack_keeps_every_frame() {
+    let error = request_error();
+
+    assert_eq!(error.virtual_stack().len(), 4);
+    assert_eq!(error.frames().count(), 4);
+}
+
+#[test]
+fn process_wide_setting_applies_to_display_and_render() {
+    let error = PoolSnafu.into_error(std::io::Error::other("timeout"));
+    assert!(!StackDisplay(&error).to_string().contains("more frame"));
+
+    snafu_virtstack::set_max_frames(Some(1));
+    let debug = format!("{error:?}");
+    let display = StackDisplay(&error).to_string();
+    let rendered = error.render_with(&snafu_virtstack::CompactFormatter);
+    let explicit = format!("{:?}", request_error());
+    snafu_virtstack::set_max_frames(None);
+
+    assert!(debug.ends_with("\n  ... (1 more frame)\n"));
+    assert!(display.starts_with("  0: Pool exhausted at "));
+    assert!(display.ends_with("\n  ... (1 more frame)\n"));
+    assert_eq!(rendered, display);
+    assert!(explicit.contains("  1: Query failed at "));
+}
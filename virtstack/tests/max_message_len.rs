@@ -0,0 +1,52 @@
+use snafu::prelude::*;
+use snafu_virtstack::{OwnedFrame, StackDisplay, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ParseError {
+    #[snafu(display("Failed to parse {input}"))]
+    Parse { input: String },
+}
+
+#[stack_trace_debug(max_message_len = 11)]
+#[derive(Snafu)]
+enum CappedError {
+    #[snafu(display("Invalid größe given"))]
+    Invalid,
+}
+
+#[test]
+fn long_messages_are_cut_off_when_rendered() {
+    let error = ParseSnafu {
+        input: "x".repeat(50),
+    }
+    .build();
+    let cut = format!("Failed to parse {}…", "x".repeat(4));
+
+    // Single test so the process-wide setting can't race with other tests
+    snafu_virtstack::set_max_message_len(Some(20));
+    assert_eq!(snafu_virtstack::max_message_len(), Some(20));
+
+    let debug = format!("{error:?}");
+    assert!(debug.starts_with(&format!("Error: {cut}\nVirtual Stack Trace:\n")));
+    assert!(debug.contains(&format!("  0: [Parse] {cut} at ")));
+    assert!(
+        StackDisplay(&error)
+            .to_string()
+            .contains(&format!("] {cut} at "))
+    );
+    let frame = &error.virtual_stack()[0];
+    assert!(frame.to_string().starts_with(&format!("[Parse] {cut} at ")));
+    assert_eq!(OwnedFrame::from(frame).to_string(), frame.to_string());
+    assert_eq!(frame.message.chars().count(), 66);
+
+    // The per-type limit wins over the global one and cuts between characters
+    let capped = format!("{:?}", InvalidSnafu.build());
+    assert!(capped.starts_with("Error: Invalid grö…\n"));
+    assert!(capped.contains("  0: [Invalid] Invalid grö… at "));
+
+    snafu_virtstack::set_max_message_len(None);
+    assert_eq!(snafu_virtstack::max_message_len(), None);
+    assert!(format!("{error:?}").contains(&"x".repeat(50)));
+    assert!(format!("{:?}", InvalidSnafu.build()).contains("Invalid grö…"));
+}
@@ -0,0 +1,50 @@
+use snafu_virtstack::{Location, OwnedFrame, StackFrame, VerboseFormatter};
+
+struct Verbose<'a>(&'a StackFrame);
+
+impl std::fmt::Display for Verbose<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use snafu_virtstack::FrameFormatter;
+        VerboseFormatter.format_frame(f, self.0)
+    }
+}
+
+fn frame() -> StackFrame {
+    StackFrame::new(Location::new("src/api.rs", 12, 9), "Request failed")
+}
+
+#[test]
+fn frames_have_no_metadata_by_default() {
+    let frame = frame();
+    assert!(frame.metadata.is_empty());
+    assert_eq!(
+        Verbose(&frame).to_string(),
+        "Request failed\n      at src/api.rs:12:9"
+    );
+}
+
+#[test]
+fn metadata_keeps_insertion_order() {
+    let frame = frame()
+        .with_meta("request_id", "f3a9")
+        .with_meta("attempt", 2)
+        .with_meta("request_id", "b71c");
+
+    let keys: Vec<_> = frame.metadata.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, ["request_id", "attempt", "request_id"]);
+    assert_eq!(OwnedFrame::from(&frame).metadata, frame.metadata);
+}
+
+#[test]
+fn verbose_formatter_shows_metadata_pairs() {
+    let frame = frame()
+        .with_meta("request_id", "f3a9")
+        .with_meta("attempt", 2);
+
+    assert_eq!(
+        Verbose(&frame).to_string(),
+        "Request failed\n      at src/api.rs:12:9\n      with request_id=f3a9 attempt=2"
+    );
+    // The compact form stays on one line
+    assert_eq!(frame.to_string(), "Request failed at src/api.rs:12:9");
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "miette")]
+
+use miette::Diagnostic;
+use snafu::prelude::*;
+use snafu_virtstack::{StackDiagnostic, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to load configuration"))]
+    Load { source: std::io::Error },
+}
+
+fn load() -> (Result<(), ConfigError>, u32) {
+    let io = std::io::Error::other("permission denied");
+    (Err(io).context(LoadSnafu), line!())
+}
+
+#[test]
+fn help_renders_the_virtual_stack() {
+    let (result, line) = load();
+    let diagnostic = StackDiagnostic::new(result.unwrap_err());
+
+    assert_eq!(diagnostic.to_string(), "Failed to load configuration");
+    let source = std::error::Error::source(&diagnostic).unwrap();
+    assert_eq!(source.to_string(), "permission denied");
+
+    let help = diagnostic.help().unwrap().to_string();
+    let lines: Vec<&str> = help.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("  0: [Load] Failed to load configuration at "));
+    assert!(lines[0].ends_with(&format!("tests/miette.rs:{line}:14")));
+    assert!(lines[1].starts_with("  1: permission denied at "));
+
+    let report = miette::Report::new(diagnostic);
+    assert_eq!(report.to_string(), "Failed to load configuration");
+    assert!(report.help().is_some());
+}
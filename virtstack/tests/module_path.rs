@@ -0,0 +1,51 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VerboseFormatter, VirtualStackTrace};
+
+mod storage {
+    use snafu::prelude::*;
+    use snafu_virtstack::stack_trace_debug;
+
+    #[stack_trace_debug]
+    #[derive(Snafu)]
+    #[snafu(visibility(pub), display("Failed to read block"))]
+    pub struct ReadBlockError {
+        source: std::io::Error,
+    }
+}
+
+mod service {
+    use snafu::prelude::*;
+    use snafu_virtstack::stack_trace_debug;
+
+    #[stack_trace_debug]
+    #[derive(Snafu)]
+    #[snafu(visibility(pub))]
+    pub enum ServiceError {
+        #[snafu(display("Request failed"))]
+        Request {
+            source: super::storage::ReadBlockError,
+        },
+    }
+}
+
+#[test]
+fn frames_record_the_module_of_their_error_type() {
+    let io = std::io::Error::other("device not ready");
+    let error = Err::<(), _>(io)
+        .context(storage::ReadBlockSnafu)
+        .context(service::RequestSnafu)
+        .unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack[0].module, Some("module_path::service"));
+    assert_eq!(stack[1].module, Some("module_path::storage"));
+    assert_eq!(stack[2].module, None);
+
+    let rendered = error.render_with(&VerboseFormatter);
+    assert!(rendered.contains("Request failed\n      at "));
+    assert!(rendered.contains("\n      in module_path::service\n"));
+    assert!(rendered.contains("\n      in module_path::storage\n"));
+    // Foreign sources have no module line
+    let last = rendered.lines().last().unwrap();
+    assert!(last.starts_with("      at "));
+}
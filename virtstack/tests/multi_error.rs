@@ -0,0 +1,74 @@
+use snafu::prelude::*;
+use snafu_virtstack::{MultiError, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum FieldError {
+    #[snafu(display("Invalid email {email:?}"))]
+    InvalidEmail {
+        email: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Name is required"))]
+    MissingName,
+}
+
+fn invalid_email() -> (FieldError, u32) {
+    let io = std::io::Error::other("missing @");
+    let line = line!() + 1;
+    let error = Err::<(), _>(io).context(InvalidEmailSnafu { email: "bob" });
+    (error.unwrap_err(), line)
+}
+
+#[test]
+fn empty_aggregates_are_ok() {
+    assert!(MultiError::new("Invalid user").into_result().is_ok());
+}
+
+#[test]
+fn children_keep_their_own_locations() {
+    let (email, email_line) = invalid_email();
+    let (mut errors, line) = (MultiError::new("Invalid user"), line!());
+    errors.push(email);
+    let name_line = line!() + 1;
+    errors.push(MissingNameSnafu.build());
+
+    let error = errors.into_result().unwrap_err();
+    assert_eq!(error.len(), 2);
+    assert_eq!(error.to_string(), "Invalid user");
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 4);
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(stack[1].variant, Some("InvalidEmail"));
+    assert_eq!(stack[1].location.line(), email_line);
+    assert_eq!(stack[2].message, "missing @");
+    assert_eq!(stack[3].variant, Some("MissingName"));
+    assert_eq!(stack[3].location.line(), name_line);
+
+    let counts: Vec<_> = error.errors().map(|child| child.frame_count()).collect();
+    assert_eq!(counts, [2, 1]);
+}
+
+#[test]
+fn debug_output_draws_a_branch_per_child() {
+    let (email, email_line) = invalid_email();
+    let (mut errors, line) = (MultiError::new("Invalid user"), line!());
+    errors.push(email);
+    let name_line = line!() + 1;
+    errors.push(MissingNameSnafu.build());
+
+    let file = file!();
+    assert_eq!(
+        format!("{errors:?}"),
+        format!(
+            "Error: Invalid user\n\
+             Virtual Stack Trace:\n  \
+             0: Invalid user at {file}:{line}:31\n  \
+             ├─ 0: [InvalidEmail] Invalid email \"bob\" at {file}:{email_line}:34\n  \
+             │  1: missing @ at {file}:{email_line}:34\n  \
+             └─ 0: [MissingName] Name is required at {file}:{name_line}:34\n"
+        )
+    );
+}
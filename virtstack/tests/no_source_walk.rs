@@ -0,0 +1,103 @@
+use core::fmt;
+
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+/// A source that must never be formatted or walked.
+#[derive(Debug)]
+struct Untouchable;
+
+impl fmt::Display for Untouchable {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        panic!("the source was formatted");
+    }
+}
+
+impl std::error::Error for Untouchable {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        panic!("the source was walked");
+    }
+}
+
+#[stack_trace_debug(no_source_walk)]
+#[derive(Snafu)]
+enum RenderError {
+    #[snafu(display("Failed to render template"))]
+    Template { source: Untouchable },
+
+    #[snafu(display("Failed to load {name}"))]
+    Load { name: String, source: InnerError },
+}
+
+#[stack_trace_debug(no_source_walk, max_depth = 1, cache, dedup)]
+#[derive(Snafu)]
+#[snafu(display("Failed to render page"))]
+struct PageError {
+    source: Untouchable,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Template missing"))]
+struct InnerError;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Request failed"))]
+struct RequestError {
+    source: RenderError,
+}
+
+#[test]
+fn only_the_root_frame_is_reported() {
+    let (error, line) = (TemplateSnafu.into_error(Untouchable), line!());
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "Failed to render template");
+    assert_eq!(stack[0].variant, Some("Template"));
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(error.frame_count(), 1);
+}
+
+#[test]
+fn debug_output_has_a_single_frame() {
+    let rendered = format!("{:?}", TemplateSnafu.into_error(Untouchable));
+    assert!(rendered.starts_with("Error: Failed to render template\nVirtual Stack Trace:\n"));
+    assert_eq!(rendered.matches(" at ").count(), 1);
+}
+
+#[test]
+fn nested_virtual_stacks_are_skipped() {
+    let error = LoadSnafu { name: "index" }.into_error(InnerSnafu.build());
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "Failed to load index");
+}
+
+#[test]
+fn combines_with_max_depth_and_cache() {
+    let error = PageSnafu.into_error(Untouchable);
+
+    assert_eq!(error.virtual_stack().len(), 1);
+    assert_eq!(error.virtual_stack()[0].message, "Failed to render page");
+}
+
+#[test]
+fn outer_errors_still_include_the_frame() {
+    let error = RequestSnafu.into_error(TemplateSnafu.into_error(Untouchable));
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "Request failed");
+    assert_eq!(stack[1].message, "Failed to render template");
+}
+
+#[test]
+fn error_source_is_unchanged() {
+    let error = TemplateSnafu.into_error(Untouchable);
+    let source = std::error::Error::source(&error).unwrap();
+    assert!(source.is::<Untouchable>());
+}
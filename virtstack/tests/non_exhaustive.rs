@@ -0,0 +1,50 @@
+use snafu::prelude::*;
+use snafu_virtstack::{ErrorExt, StatusCode, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug(cache, dedup)]
+#[derive(Snafu)]
+#[non_exhaustive]
+pub enum PublicError {
+    #[snafu(display("Request timed out"))]
+    #[status_code(Timeout)]
+    Timeout,
+
+    #[snafu(display("Failed to open {path}: {source}"))]
+    #[non_exhaustive]
+    Open {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Service unavailable"))]
+#[non_exhaustive]
+pub struct UnavailableError;
+
+#[test]
+fn non_exhaustive_enums_generate_exhaustive_matches() {
+    let error = TimeoutSnafu.build();
+    assert_eq!(error.status_code(), StatusCode::Timeout);
+    let stack = error.virtual_stack();
+    assert_eq!(stack[0].variant, Some("Timeout"));
+
+    let io = std::io::Error::other("permission denied");
+    let error = Err::<(), _>(io)
+        .context(OpenSnafu {
+            path: "/etc/shadow",
+        })
+        .unwrap_err();
+    assert_eq!(error.status_code(), StatusCode::Internal);
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].variant, Some("Open"));
+    assert_eq!(
+        stack[0].message,
+        "Failed to open /etc/shadow: permission denied"
+    );
+
+    let error = UnavailableSnafu.build();
+    assert_eq!(error.virtual_stack()[0].message, "Service unavailable");
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "std")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+use std::sync::Mutex;
+
+static OBSERVED: Mutex<Vec<(Option<&'static str>, usize)>> = Mutex::new(Vec::new());
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Failed to read file"))]
+    ReadFile { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Failed to load settings"))]
+    LoadSettings { source: InnerError },
+}
+
+#[test]
+fn observer_sees_every_materialized_stack() {
+    let io = std::io::Error::other("missing");
+    let error = Err::<(), _>(io)
+        .context(ReadFileSnafu)
+        .context(LoadSettingsSnafu)
+        .unwrap_err();
+
+    // Nothing is observed before registration
+    error.virtual_stack();
+
+    let registered = snafu_virtstack::set_error_observer(|frames| {
+        OBSERVED
+            .lock()
+            .unwrap()
+            .push((frames[0].variant, frames.len()));
+    });
+    assert!(registered);
+    assert!(!snafu_virtstack::set_error_observer(|_| {}));
+
+    let stack = error.virtual_stack();
+    let debug = format!("{error:?}");
+    // Iterating lazily doesn't materialize the stack
+    let _ = error.virtual_stack_iter().next();
+
+    let observed = OBSERVED.lock().unwrap();
+    assert_eq!(
+        *observed,
+        [(Some("LoadSettings"), 3), (Some("LoadSettings"), 3)]
+    );
+    assert!(debug.ends_with(&format!("  2: {}\n", stack[2])));
+}
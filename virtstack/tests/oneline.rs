@@ -0,0 +1,46 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum StorageError {
+    #[snafu(display("Failed to read block {block}"))]
+    ReadBlock { block: u64, source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ServiceError {
+    #[snafu(display("Request failed"))]
+    Request { source: StorageError },
+}
+
+fn read_block() -> (Result<(), StorageError>, u32) {
+    let io = std::io::Error::other("device not ready");
+    (Err(io).context(ReadBlockSnafu { block: 7u64 }), line!())
+}
+
+fn handle() -> (Result<(), ServiceError>, u32, u32) {
+    let (result, inner) = read_block();
+    (result.context(RequestSnafu), inner, line!())
+}
+
+#[test]
+fn chains_render_on_one_line() {
+    let (result, inner, outer) = handle();
+    let error = result.unwrap_err();
+
+    assert_eq!(
+        error.chain_oneline(),
+        "Request failed: Failed to read block 7: device not ready"
+    );
+
+    let file = file!();
+    assert_eq!(
+        error.chain_oneline_with_locations(),
+        format!(
+            "Request failed ({file}:{outer}): Failed to read block 7 ({file}:{inner}): \
+             device not ready ({file}:{inner})"
+        )
+    );
+}
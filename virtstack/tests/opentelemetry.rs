@@ -0,0 +1,114 @@
+#![cfg(feature = "opentelemetry")]
+
+use std::borrow::Cow;
+use std::time::SystemTime;
+
+use opentelemetry::trace::{Span, SpanContext, Status};
+use opentelemetry::{KeyValue, Value};
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum StorageError {
+    #[snafu(display("Failed to open {path}"))]
+    Open {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// A span keeping the events added to it.
+struct RecordingSpan {
+    recording: bool,
+    events: Vec<(Cow<'static, str>, Vec<KeyValue>)>,
+}
+
+impl RecordingSpan {
+    fn new(recording: bool) -> Self {
+        Self {
+            recording,
+            events: Vec::new(),
+        }
+    }
+}
+
+impl Span for RecordingSpan {
+    fn add_event_with_timestamp<T>(&mut self, name: T, _: SystemTime, attributes: Vec<KeyValue>)
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.events.push((name.into(), attributes));
+    }
+
+    fn span_context(&self) -> &SpanContext {
+        &SpanContext::NONE
+    }
+
+    fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    fn set_attribute(&mut self, _: KeyValue) {}
+
+    fn set_status(&mut self, _: Status) {}
+
+    fn update_name<T>(&mut self, _: T)
+    where
+        T: Into<Cow<'static, str>>,
+    {
+    }
+
+    fn add_link(&mut self, _: SpanContext, _: Vec<KeyValue>) {}
+
+    fn end_with_timestamp(&mut self, _: SystemTime) {}
+}
+
+fn attribute<'a>(attributes: &'a [KeyValue], key: &str) -> &'a Value {
+    &attributes
+        .iter()
+        .find(|attribute| attribute.key.as_str() == key)
+        .unwrap_or_else(|| panic!("missing {key}"))
+        .value
+}
+
+#[test]
+fn records_an_exception_event() {
+    let io = std::io::Error::other("permission denied");
+    let line = line!() + 1;
+    let error = OpenSnafu { path: "users.db" }.into_error(io);
+
+    let mut span = RecordingSpan::new(true);
+    error.record_otel(&mut span);
+
+    assert_eq!(span.events.len(), 1);
+    let (name, attributes) = &span.events[0];
+    assert_eq!(name, "exception");
+    assert_eq!(attributes.len(), 3);
+    assert_eq!(
+        attribute(attributes, "exception.message").as_str(),
+        "Failed to open users.db"
+    );
+    assert_eq!(
+        attribute(attributes, "exception.type").as_str(),
+        "opentelemetry::StorageError"
+    );
+    let location = format!("{}:{line}:48", file!());
+    assert_eq!(
+        attribute(attributes, "exception.stacktrace").as_str(),
+        format!(
+            "0: [Open] Failed to open users.db at {location}\n1: permission denied at {location}"
+        )
+    );
+}
+
+#[test]
+fn skips_spans_that_are_not_recording() {
+    let error = OpenSnafu { path: "users.db" }.into_error(std::io::Error::other("denied"));
+
+    let mut span = RecordingSpan::new(false);
+    error.record_otel(&mut span);
+
+    assert!(span.events.is_empty());
+}
@@ -0,0 +1,66 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{ErrorExt, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum StorageError {
+    #[snafu(display("Failed to open {path}"))]
+    #[external_msg("Storage unavailable")]
+    Open {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Disk full"))]
+    DiskFull,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Token {token} expired"))]
+#[external_msg("Session expired")]
+struct SessionError {
+    token: String,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to write block {block}"))]
+struct WriteError {
+    block: u64,
+    source: StorageError,
+}
+
+#[test]
+fn output_msg_defaults_to_display() {
+    assert_eq!(DiskFullSnafu.build().output_msg(), "Disk full");
+
+    let error = WriteSnafu { block: 3u64 }.into_error(DiskFullSnafu.build());
+    assert_eq!(error.output_msg(), "Failed to write block 3");
+}
+
+#[test]
+fn external_msg_overrides_only_the_client_message() {
+    let io = std::io::Error::other("permission denied");
+    let error = Err::<(), _>(io)
+        .context(OpenSnafu {
+            path: "/var/lib/db/0001.sst",
+        })
+        .unwrap_err();
+
+    assert_eq!(error.output_msg(), "Storage unavailable");
+    assert_eq!(error.to_string(), "Failed to open /var/lib/db/0001.sst");
+    assert_eq!(
+        error.virtual_stack()[0].message,
+        "Failed to open /var/lib/db/0001.sst"
+    );
+}
+
+#[test]
+fn structs_support_external_msg() {
+    let error = SessionSnafu { token: "abc123" }.build();
+
+    assert_eq!(error.output_msg(), "Session expired");
+    assert_eq!(error.to_string(), "Token abc123 expired");
+}
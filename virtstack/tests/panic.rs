@@ -0,0 +1,62 @@
+#![cfg(feature = "std")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{
+    ErrorExt, PanicError, StatusCode, VirtualStackTrace, catch_panic, stack_trace_debug,
+};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum JobError {
+    #[snafu(display("Job {id} crashed"))]
+    Crashed { id: u32, source: PanicError },
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn panic_messages_are_extracted_from_the_payload() {
+    let formatted = catch_panic(|| panic!("index {} out of range", 3)).unwrap_err();
+    let literal = catch_panic(|| panic!("boom")).unwrap_err();
+    let any = catch_panic(|| std::panic::panic_any(42)).unwrap_err();
+
+    assert_eq!(formatted.message(), "index 3 out of range");
+    assert_eq!(literal.message(), "boom");
+    assert_eq!(any.message(), "Box<dyn Any>");
+    assert_eq!(literal.to_string(), "Panicked: boom");
+}
+
+#[test]
+fn caught_panics_become_sources_located_where_they_were_caught() {
+    let line = line!() + 2;
+    let result: Result<(), JobError> =
+        catch_panic(|| panic!("disk on fire")).context(CrashedSnafu { id: 7u32 });
+    let error = result.unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "Job 7 crashed");
+    assert_eq!(stack[1].message, "Panicked: disk on fire");
+    assert_eq!(stack[1].location.file(), file!());
+    assert_eq!(stack[1].location.line(), line);
+}
+
+#[test]
+fn successful_closures_return_their_value() {
+    let mut calls = 0;
+    let value = catch_panic(|| {
+        calls += 1;
+        "done"
+    });
+
+    assert_eq!(value.unwrap(), "done");
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn panic_errors_are_internal_errors() {
+    let error = catch_panic(|| panic!("boom")).unwrap_err();
+
+    assert_eq!(error.status_code(), StatusCode::Internal);
+    assert_send_sync::<PanicError>();
+}
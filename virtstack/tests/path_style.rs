@@ -0,0 +1,54 @@
+use snafu::prelude::*;
+use snafu_virtstack::{OwnedFrame, PathStyle, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Something went wrong"))]
+    SomethingWrong,
+}
+
+#[test]
+fn path_style_trims_displayed_locations() {
+    let (error, line) = (SomethingWrongSnafu.build(), line!());
+    let frame = &error.virtual_stack()[0];
+    let column = frame.location.column();
+
+    // Single test so the process-wide setting can't race with other tests
+    snafu_virtstack::set_path_style(PathStyle::FileName);
+    assert_eq!(snafu_virtstack::path_style(), PathStyle::FileName);
+    assert_eq!(
+        frame.to_string(),
+        format!("[SomethingWrong] Something went wrong at path_style.rs:{line}:{column}")
+    );
+    assert_eq!(OwnedFrame::from(frame).to_string(), frame.to_string());
+    assert!(format!("{error:?}").contains(&format!(" at path_style.rs:{line}:")));
+    assert_eq!(frame.location.file(), file!());
+
+    snafu_virtstack::set_path_style(PathStyle::Relative);
+    assert_eq!(
+        frame.location.to_string(),
+        format!("tests/path_style.rs:{line}:{column}")
+    );
+
+    snafu_virtstack::set_path_style(PathStyle::Full);
+    assert_eq!(
+        frame.location.to_string(),
+        format!("{}:{line}:{column}", file!())
+    );
+}
+
+#[test]
+fn path_style_trim_handles_unusual_paths() {
+    assert_eq!(PathStyle::Relative.trim("build.rs"), "build.rs");
+    assert_eq!(
+        PathStyle::Relative.trim("/a/src/gen/src/lib.rs"),
+        "src/lib.rs"
+    );
+    assert_eq!(
+        PathStyle::Relative.trim(r"C:\work\app\src\main.rs"),
+        r"src\main.rs"
+    );
+    assert_eq!(PathStyle::FileName.trim(r"C:\work\main.rs"), "main.rs");
+    assert_eq!(PathStyle::FileName.trim("main.rs"), "main.rs");
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "std")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{Location, OwnedFrame, PathStyle, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Something went wrong"))]
+    SomethingWrong,
+}
+
+#[test]
+fn path_trimmer_rewrites_rendered_locations() {
+    // Single test, since the trimmer can only be installed once per process
+    let installed = snafu_virtstack::set_path_trimmer(|file| file.replace("tests/", "it/"));
+    assert!(installed);
+    assert!(!snafu_virtstack::set_path_trimmer(|_| String::new()));
+
+    let (error, line) = (SomethingWrongSnafu.build(), line!());
+    let frame = &error.virtual_stack()[0];
+    let column = frame.location.column();
+    let trimmed = file!().replace("tests/", "it/");
+
+    let rendered = format!("{trimmed}:{line}:{column}");
+    assert_eq!(frame.location.to_string(), rendered);
+    assert_eq!(OwnedFrame::from(frame).to_string(), frame.to_string());
+    assert!(format!("{error:?}").contains(&format!(" at {rendered}\n")));
+    assert!(
+        error
+            .to_logfmt()
+            .contains(&format!("frame0_file={trimmed} "))
+    );
+    assert_eq!(error.journald_fields()[1].1, trimmed);
+
+    // Captured locations keep the original path
+    assert_eq!(frame.location.file(), file!());
+    assert_eq!(OwnedFrame::from(frame).file, file!());
+
+    // The path style applies to the rewritten path, in which `it` isn't a crate directory
+    snafu_virtstack::set_path_style(PathStyle::Relative);
+    let location = Location::new("/build/app/tests/api.rs", 3, 7);
+    let relative = location.to_string();
+    snafu_virtstack::set_path_style(PathStyle::Full);
+    assert_eq!(relative, "/build/app/it/api.rs:3:7");
+}
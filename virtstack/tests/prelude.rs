@@ -0,0 +1,40 @@
+use snafu::prelude::*;
+use snafu_virtstack::prelude::*;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum AttributeError {
+    #[snafu(display("Failed to read config"))]
+    #[status_code(NotFound)]
+    ReadConfig { source: std::io::Error },
+}
+
+#[derive(Snafu, StackTraceDebug)]
+#[snafu(display("Invalid config"))]
+struct DerivedError {
+    source: AttributeError,
+}
+
+fn give_up(tries: u32) -> Result<(), snafu_virtstack::VirtWhatever> {
+    virt_whatever!("Gave up after {tries} tries")
+}
+
+fn first_frame(error: &impl VirtualStackTrace) -> StackFrame {
+    error.virtual_stack().remove(0)
+}
+
+#[test]
+fn prelude_brings_in_the_macros_traits_and_types() {
+    let io = std::io::Error::other("missing");
+    let error = Err::<(), _>(io)
+        .context(ReadConfigSnafu)
+        .context(DerivedSnafu)
+        .unwrap_err();
+
+    assert_eq!(first_frame(&error).message, "Invalid config");
+    assert_eq!(error.status_code(), StatusCode::Internal);
+    assert_eq!(error.source.status_code(), StatusCode::NotFound);
+
+    let error = give_up(3).unwrap_err();
+    assert_eq!(first_frame(&error).message, "Gave up after 3 tries");
+}
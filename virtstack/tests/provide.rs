@@ -0,0 +1,47 @@
+//! Run with `RUSTFLAGS="--cfg virtstack_nightly" cargo +nightly test -p snafu-virtstack
+//! --features snafu/unstable-provider-api --test provide`.
+#![cfg(virtstack_nightly)]
+#![feature(error_generic_member_access)]
+
+use core::error::{self, Error};
+
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum AppError {
+    #[snafu(display("Config missing"))]
+    ConfigMissing,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Startup failed"))]
+struct StartupError {
+    source: AppError,
+}
+
+#[test]
+fn location_is_available_through_request_ref() {
+    let expected_line = line!() + 1;
+    let error = ConfigMissingSnafu.build();
+    let location = error::request_ref::<snafu::Location>(&error).expect("must provide a location");
+    assert_eq!(location.file, file!());
+    assert_eq!(location.line, expected_line);
+}
+
+#[test]
+fn location_is_available_through_dyn_error() {
+    let error: Box<dyn Error> = Box::new(ConfigMissingSnafu.build());
+    assert!(error::request_ref::<snafu::Location>(&*error).is_some());
+}
+
+#[test]
+fn root_cause_location_is_provided_first() {
+    let root_line = line!() + 1;
+    let result = ConfigMissingSnafu.fail::<()>();
+    let error = result.context(StartupSnafu).unwrap_err();
+    let location = error::request_ref::<snafu::Location>(&error).unwrap();
+    assert_eq!(location.line, root_line);
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "regex")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, redact_secrets, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ApiError {
+    #[snafu(display("Request by {email} was rejected"))]
+    Rejected {
+        email: String,
+        source: std::io::Error,
+    },
+}
+
+#[test]
+fn secrets_are_masked_in_every_frame() {
+    let io = std::io::Error::other("invalid key 3f9a2c41d8e7b6a5c4d3e2f1a0b9c8d7");
+    let error = Err::<(), _>(io)
+        .context(RejectedSnafu {
+            email: "first.last+tag@mail.example.co.uk",
+        })
+        .unwrap_err();
+
+    let stack = error.redacted(redact_secrets);
+    assert_eq!(stack[0].message, "Request by [REDACTED] was rejected");
+    assert_eq!(stack[1].message, "invalid key [REDACTED]");
+}
+
+#[test]
+fn ordinary_text_is_left_alone() {
+    for message in [
+        "Failed to read config.toml at line 42",
+        "Counterrevolutionaries_without_digits_xyz",
+        "short-token-123",
+        "",
+    ] {
+        assert_eq!(redact_secrets(message), message);
+    }
+}
@@ -0,0 +1,32 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum LoginError {
+    #[snafu(display("Failed to verify token {token}"))]
+    Verify {
+        token: String,
+        source: std::io::Error,
+    },
+}
+
+#[test]
+fn redaction_keeps_locations_and_variants() {
+    let io = std::io::Error::other("token abc123 expired");
+    let error = Err::<(), _>(io)
+        .context(VerifySnafu { token: "abc123" })
+        .unwrap_err();
+
+    let original = error.virtual_stack();
+    let redacted = error.redacted(|message| message.replace("abc123", "***"));
+
+    assert_eq!(redacted.len(), original.len());
+    assert_eq!(redacted[0].message, "Failed to verify token ***");
+    assert_eq!(redacted[1].message, "token *** expired");
+    for (redacted, original) in redacted.iter().zip(original.iter()) {
+        assert_eq!(redacted.location, original.location);
+        assert_eq!(redacted.variant, original.variant);
+    }
+    assert_eq!(redacted[0].variant, Some("Verify"));
+}
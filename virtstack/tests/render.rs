@@ -0,0 +1,102 @@
+use snafu::prelude::*;
+use snafu_virtstack::{
+    Location, MultiError, Stack, StackFrame, VirtualStackTrace, stack_trace_debug,
+};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Failed to read file"))]
+    ReadFile { source: std::io::Error },
+}
+
+#[stack_trace_debug(error_label = "Failure", trace_label = "Trace")]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Failed to load settings"))]
+    LoadSettings { source: InnerError },
+}
+
+#[stack_trace_debug(no_debug)]
+#[derive(Snafu)]
+#[snafu(display("Custom"))]
+struct CustomError;
+
+impl std::fmt::Debug for CustomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("custom debug")
+    }
+}
+
+/// A hand-written implementation relying on the provided `render`.
+struct Manual;
+
+impl VirtualStackTrace for Manual {
+    fn virtual_stack(&self) -> Stack {
+        [
+            StackFrame::new(Location::new("src/app.rs", 3, 5), "Outer"),
+            StackFrame::new(Location::new("src/db.rs", 8, 1), "Inner"),
+        ]
+        .into_iter()
+        .collect()
+    }
+}
+
+fn nested_error() -> OuterError {
+    let io = std::io::Error::other("missing");
+    Err::<(), _>(io)
+        .context(ReadFileSnafu)
+        .context(LoadSettingsSnafu)
+        .unwrap_err()
+}
+
+fn render_any(error: impl VirtualStackTrace) -> String {
+    error.render()
+}
+
+fn takes_str(trace: &str) -> usize {
+    trace.lines().count()
+}
+
+#[test]
+fn render_matches_the_debug_output() {
+    let error = nested_error();
+    let rendered = error.render();
+
+    assert_eq!(rendered, format!("{error:?}"));
+    assert!(rendered.starts_with("Failure: Failed to load settings\nTrace:\n"));
+    assert_eq!(takes_str(&rendered), 5);
+}
+
+#[test]
+fn boxed_and_borrowed_errors_render_like_the_original() {
+    let error = nested_error();
+    let expected = format!("{error:?}");
+
+    assert_eq!(render_any(&error), expected);
+    let boxed: Box<dyn VirtualStackTrace> = Box::new(error);
+    assert_eq!(boxed.render(), expected);
+}
+
+#[test]
+fn render_keeps_a_custom_debug() {
+    assert_eq!(CustomSnafu.build().render(), "custom debug");
+}
+
+#[test]
+fn multi_errors_render_their_tree() {
+    let mut errors = MultiError::new("Invalid input");
+    errors.push(nested_error());
+    assert_eq!(errors.render(), format!("{errors:?}"));
+}
+
+#[test]
+fn other_implementations_get_the_default_layout() {
+    assert_eq!(
+        Manual.render(),
+        "Error: Outer\n\
+         Virtual Stack Trace:\n  \
+         0: Outer at src/app.rs:3:5\n  \
+         1: Inner at src/db.rs:8:1\n"
+    );
+}
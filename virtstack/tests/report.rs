@@ -0,0 +1,43 @@
+#![cfg(feature = "std")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{Report, VirtualStackTrace, stack_trace_debug};
+use std::process::{ExitCode, Termination};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Something went wrong"))]
+    SomethingWrong,
+}
+
+fn fails() -> Result<(), MyError> {
+    SomethingWrongSnafu.fail()?;
+    Ok(())
+}
+
+#[test]
+fn failed_report_exits_with_failure() {
+    let report = Report::capture(fails);
+    assert!(report.error().is_some());
+    assert_eq!(
+        format!("{report:?}"),
+        format!("{:?}", report.error().unwrap())
+    );
+    assert_eq!(report.report(), ExitCode::FAILURE);
+
+    assert_eq!(
+        SomethingWrongSnafu.build().report().report(),
+        ExitCode::FAILURE
+    );
+}
+
+#[test]
+fn successful_report_exits_with_success() {
+    assert_eq!(Report::<MyError>::ok().report(), ExitCode::SUCCESS);
+    assert_eq!(
+        Report::from(Ok::<(), MyError>(())).report(),
+        ExitCode::SUCCESS
+    );
+    assert!(Report::<MyError>::ok().into_result().is_ok());
+}
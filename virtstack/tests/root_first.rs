@@ -0,0 +1,64 @@
+use snafu::prelude::*;
+use snafu_virtstack::{CompactFormatter, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to load configuration"))]
+    Load { source: std::io::Error },
+}
+
+#[stack_trace_debug(root_first)]
+#[derive(Snafu)]
+enum RequestError {
+    #[snafu(display("Request failed"))]
+    Request { source: ConfigError },
+}
+
+fn load() -> Result<(), ConfigError> {
+    let io = std::io::Error::other("permission denied");
+    Err(io).context(LoadSnafu)
+}
+
+fn frame_lines(rendered: &str) -> Vec<&str> {
+    rendered
+        .lines()
+        .filter(|line| line.starts_with("  "))
+        .collect()
+}
+
+#[test]
+fn root_first_reverses_rendered_frames_only() {
+    let error = load().unwrap_err();
+
+    // Single test so the process-wide setting can't race with other tests
+    snafu_virtstack::set_root_first(true);
+    assert!(snafu_virtstack::root_first());
+
+    let debug = format!("{error:?}");
+    assert!(debug.starts_with("Error: Failed to load configuration\nVirtual Stack Trace:\n"));
+    let lines = frame_lines(&debug);
+    assert!(lines[0].starts_with("  0: permission denied at "));
+    assert!(lines[1].starts_with("  1: [Load] Failed to load configuration at "));
+    assert_eq!(frame_lines(&error.stack_display().to_string()), lines);
+    assert_eq!(frame_lines(&error.render_with(&CompactFormatter)), lines);
+
+    // Canonical order is unaffected
+    assert_eq!(
+        error.virtual_stack()[0].message,
+        "Failed to load configuration"
+    );
+
+    snafu_virtstack::set_root_first(false);
+    let outer_first = format!("{error:?}");
+    assert!(frame_lines(&outer_first)[0].starts_with("  0: [Load] "));
+
+    // The per-type argument applies regardless of the global setting
+    let error = load().context(RequestSnafu).unwrap_err();
+    let debug = format!("{error:?}");
+    assert!(debug.starts_with("Error: Request failed\n"));
+    let lines = frame_lines(&debug);
+    assert!(lines[0].starts_with("  0: permission denied at "));
+    assert!(lines[1].starts_with("  1: [Load] Failed to load configuration at "));
+    assert!(lines[2].starts_with("  2: [Request] Request failed at "));
+}
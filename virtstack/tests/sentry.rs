@@ -0,0 +1,71 @@
+#![cfg(feature = "sentry")]
+
+use sentry_core::protocol::Level;
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum StorageError {
+    #[snafu(display("Failed to open {path}"))]
+    Open {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to load user {id}"))]
+struct LoadError<T: std::fmt::Debug + std::fmt::Display + 'static> {
+    id: T,
+    source: StorageError,
+}
+
+fn load_error() -> (LoadError<u64>, u32) {
+    let io = std::io::Error::other("permission denied");
+    let line = line!() + 1;
+    let storage = Err::<(), _>(io).context(OpenSnafu { path: "users.db" });
+    (
+        LoadSnafu { id: 7u64 }.into_error(storage.unwrap_err()),
+        line,
+    )
+}
+
+#[test]
+fn events_hold_one_error_exception_named_after_the_type() {
+    let (error, _) = load_error();
+    let event = error.to_sentry_event();
+
+    assert_eq!(event.level, Level::Error);
+    assert_eq!(event.exception.len(), 1);
+    let exception = &event.exception[0];
+    assert_eq!(exception.ty, "LoadError");
+    assert_eq!(exception.value.as_deref(), Some("Failed to load user 7"));
+    assert_eq!(exception.module.as_deref(), Some(module_path!()));
+}
+
+#[test]
+fn frames_end_with_the_root_cause() {
+    let (error, line) = load_error();
+    let event = error.to_sentry_event();
+
+    let frames = &event.exception[0].stacktrace.as_ref().unwrap().frames;
+    let functions: Vec<_> = frames.iter().map(|f| f.function.as_deref()).collect();
+    assert_eq!(
+        functions,
+        [
+            Some("Failed to load user 7"),
+            Some("Failed to open users.db"),
+            Some("permission denied"),
+        ]
+    );
+
+    let open = &frames[1];
+    assert_eq!(open.filename.as_deref(), Some(file!()));
+    assert_eq!(open.lineno, Some(line.into()));
+    assert_eq!(open.colno, Some(36));
+    assert_eq!(open.module.as_deref(), Some(module_path!()));
+    assert_eq!(frames[2].module, None);
+}
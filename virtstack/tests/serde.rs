@@ -0,0 +1,167 @@
+#![cfg(feature = "serde")]
+
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{OwnedFrame, StackFrame, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Failed to load {name}"))]
+    Load { name: String },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Request rejected"))]
+#[status_code(InvalidArguments)]
+struct RequestError {
+    source: MyError,
+}
+
+/// Expected serialization of the frame of a `MyError::Load` created on this thread, at the
+/// time recorded in `frame`.
+fn load_frame_json(frame: &StackFrame) -> serde_json::Value {
+    #[allow(unused_mut)]
+    let mut json = serde_json::json!({
+        "message": "Failed to load config",
+        "file": file!(),
+        "line": frame.location.line(),
+        "column": frame.location.column(),
+        "variant": "Load",
+        "module": module_path!(),
+    });
+    #[cfg(feature = "thread")]
+    {
+        json["thread"] = serde_json::json!(std::thread::current().name());
+    }
+    #[cfg(feature = "timestamp")]
+    {
+        json["timestamp"] = serde_json::json!(OwnedFrame::from(frame).timestamp);
+    }
+    json
+}
+
+#[test]
+fn serializes_flattened_location() {
+    let frame = StackFrame::new(
+        snafu_virtstack::Location::new("src/config.rs", 42, 15),
+        "Failed to read configuration file".to_string(),
+    );
+
+    let value = serde_json::to_value(&frame).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "message": "Failed to read configuration file",
+            "file": "src/config.rs",
+            "line": 42,
+            "column": 15,
+        })
+    );
+}
+
+#[test]
+fn round_trips_through_owned_frame() {
+    let error = LoadSnafu { name: "config" }.build();
+    let stack = error.virtual_stack();
+
+    let json = serde_json::to_string(&stack).unwrap();
+    let frames: Vec<OwnedFrame> = serde_json::from_str(&json).unwrap();
+
+    let expected: Vec<OwnedFrame> = stack.iter().map(OwnedFrame::from).collect();
+    assert_eq!(frames, expected);
+    assert_eq!(frames[0].message, "Failed to load config");
+    assert_eq!(frames[0].file, file!());
+    assert_eq!(frames[0].variant.as_deref(), Some("Load"));
+    assert!(json.contains(r#""variant":"Load""#));
+    assert_eq!(frames[0].module.as_deref(), Some(module_path!()));
+}
+
+#[test]
+fn problem_json_lists_trace_unless_opted_out() {
+    let error = LoadSnafu { name: "config" }.build();
+    let frame = &error.virtual_stack()[0];
+
+    assert_eq!(
+        error.to_problem_json(),
+        serde_json::json!({
+            "type": "about:blank",
+            "title": "Failed to load config",
+            "detail": "Failed to load config",
+            "trace": [load_frame_json(frame)],
+        })
+    );
+
+    let public = error.to_problem_json_without_trace();
+    assert!(public.get("trace").is_none());
+    assert_eq!(public["title"], "Failed to load config");
+}
+
+#[test]
+fn to_json_includes_code_variant_and_stack() {
+    let error = LoadSnafu { name: "config" }.build();
+    let frame = &error.virtual_stack()[0];
+
+    let json: serde_json::Value = serde_json::from_str(&error.to_json().unwrap()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "code": "Internal",
+            "message": "Failed to load config",
+            "variant": "Load",
+            "stack": [load_frame_json(frame)],
+        })
+    );
+}
+
+#[test]
+fn to_json_omits_variant_of_structs() {
+    let error = RequestSnafu.into_error(LoadSnafu { name: "config" }.build());
+
+    let compact = error.to_json().unwrap();
+    let pretty = error.to_json_pretty().unwrap();
+    assert!(!compact.contains('\n'));
+    assert!(pretty.contains("\n  \"code\": \"InvalidArguments\""));
+
+    let json: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+    assert_eq!(
+        json,
+        serde_json::from_str::<serde_json::Value>(&compact).unwrap()
+    );
+    assert!(json.get("variant").is_none());
+    assert_eq!(json["message"], "Request rejected");
+    assert_eq!(json["stack"][1]["variant"], "Load");
+}
+
+#[test]
+fn metadata_serializes_as_an_object() {
+    let frame = StackFrame::new(
+        snafu_virtstack::Location::new("src/api.rs", 12, 9),
+        "Failed",
+    )
+    .with_meta("request_id", "f3a9")
+    .with_meta("attempt", 2);
+
+    let json = serde_json::to_string(&frame).unwrap();
+    assert!(json.ends_with(r#""metadata":{"request_id":"f3a9","attempt":"2"}}"#));
+
+    let owned: OwnedFrame = serde_json::from_str(&json).unwrap();
+    assert_eq!(owned, OwnedFrame::from(&frame));
+}
+
+#[test]
+fn stack_report_round_trips() {
+    let error = RequestSnafu.into_error(LoadSnafu { name: "config" }.build());
+    let report = error.to_report();
+    drop(error);
+
+    let value = serde_json::to_value(&report).unwrap();
+    assert_eq!(value["message"], "Request rejected");
+    assert_eq!(value["frames"].as_array().unwrap().len(), 2);
+    assert_eq!(value["frames"][1]["message"], "Failed to load config");
+    assert_eq!(value["frames"][1]["variant"], "Load");
+
+    let read: snafu_virtstack::StackReport = serde_json::from_value(value).unwrap();
+    assert_eq!(read, report);
+}
@@ -0,0 +1,46 @@
+use snafu::prelude::*;
+use snafu_virtstack::{Location, StackFrame, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Config not found"))]
+struct ConfigError;
+
+fn frame(file: &'static str) -> StackFrame {
+    StackFrame::new(Location::new(file, 42, 15), "Failed to read config")
+}
+
+#[test]
+fn unix_paths_keep_only_the_file_name() {
+    assert_eq!(
+        frame("/home/ci/app/src/config.rs").short_location(),
+        "config.rs:42:15"
+    );
+    assert_eq!(frame("src/config.rs").short_location(), "config.rs:42:15");
+}
+
+#[test]
+fn windows_paths_keep_only_the_file_name() {
+    assert_eq!(
+        frame(r"C:\Users\ci\app\src\config.rs").short_location(),
+        "config.rs:42:15"
+    );
+    assert_eq!(frame(r"src\config.rs").short_location(), "config.rs:42:15");
+}
+
+#[test]
+fn bare_file_names_are_kept() {
+    assert_eq!(frame("config.rs").short_location(), "config.rs:42:15");
+}
+
+#[test]
+fn captured_locations_are_shortened() {
+    let line = line!() + 1;
+    let error = ConfigSnafu.build();
+
+    let stack = error.virtual_stack();
+    assert_eq!(
+        stack[0].short_location(),
+        format!("short_location.rs:{line}:29")
+    );
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "color")]
+
+use std::fs::File;
+use std::io::IsTerminal;
+use std::sync::Mutex;
+
+use snafu_virtstack::should_colorize;
+
+/// Serializes the tests, which all change the same environment variables.
+static ENV: Mutex<()> = Mutex::new(());
+
+const VARS: [&str; 3] = ["NO_COLOR", "CLICOLOR", "CLICOLOR_FORCE"];
+
+/// A stream that is never a terminal.
+fn piped() -> File {
+    File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")).unwrap()
+}
+
+/// Runs `f` with exactly the color variables in `vars` set.
+fn with_env(vars: &[(&str, &str)], f: impl FnOnce()) {
+    let _guard = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // SAFETY: the lock keeps the other tests of this binary from touching the environment
+    unsafe {
+        for name in VARS {
+            std::env::remove_var(name);
+        }
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+    }
+    f();
+}
+
+#[test]
+fn piped_output_is_plain_by_default() {
+    with_env(&[], || assert!(!should_colorize(&piped())));
+    with_env(&[("CLICOLOR", "1")], || assert!(!should_colorize(&piped())));
+}
+
+#[test]
+fn clicolor_force_colors_piped_output() {
+    with_env(&[("CLICOLOR_FORCE", "1")], || {
+        assert!(should_colorize(&piped()));
+    });
+    with_env(&[("CLICOLOR_FORCE", "1"), ("CLICOLOR", "0")], || {
+        assert!(should_colorize(&piped()));
+    });
+    with_env(&[("CLICOLOR_FORCE", "0")], || {
+        assert!(!should_colorize(&piped()));
+    });
+}
+
+#[test]
+fn no_color_wins_over_clicolor_force() {
+    with_env(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")], || {
+        assert!(!should_colorize(&piped()));
+    });
+    // Any value counts, but an empty one is ignored
+    with_env(&[("NO_COLOR", "0"), ("CLICOLOR_FORCE", "1")], || {
+        assert!(!should_colorize(&piped()));
+    });
+    with_env(&[("NO_COLOR", ""), ("CLICOLOR_FORCE", "1")], || {
+        assert!(should_colorize(&piped()));
+    });
+}
+
+#[test]
+fn clicolor_zero_disables_colors_on_terminals() {
+    // Terminals can't be faked, so this only runs where stderr is one
+    let stderr = std::io::stderr();
+    if !stderr.is_terminal() {
+        return;
+    }
+    with_env(&[], || assert!(should_colorize(&stderr)));
+    with_env(&[("CLICOLOR", "0")], || assert!(!should_colorize(&stderr)));
+    with_env(&[("NO_COLOR", "1")], || assert!(!should_colorize(&stderr)));
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "std")]
+
+use std::fmt;
+
+use snafu::prelude::*;
+use snafu_virtstack::{
+    CompactFormatter, Location, Stack, StackFrame, VirtualStackTrace, stack_trace_debug,
+};
+
+const REGISTRY: &str = "/home/me/.cargo/registry/src/index.crates.io-0000/pool-1.0.0/src/lib.rs";
+
+/// Error of a third-party crate reporting frames located in the cargo registry.
+#[derive(Debug)]
+struct PoolError;
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Pool exhausted")
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+impl VirtualStackTrace for PoolError {
+    fn virtual_stack(&self) -> Stack {
+        [
+            ("Pool exhausted", 120),
+            ("Checkout timed out", 80),
+            ("Connection refused", 42),
+        ]
+        .into_iter()
+        .map(|(message, line)| StackFrame::new(Location::new(REGISTRY, line, 9), message))
+        .collect()
+    }
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum QueryError {
+    #[snafu(display("Failed to get a connection"))]
+    Connect { source: PoolError },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to load user"))]
+struct LoadError {
+    source: QueryError,
+}
+
+fn load_error() -> LoadError {
+    let query = Err::<(), _>(PoolError).context(ConnectSnafu).unwrap_err();
+    Err::<(), _>(query).context(LoadSnafu).unwrap_err()
+}
+
+/// Returns the lines of `rendered` without the locations of the frames.
+fn without_locations(rendered: &str) -> Vec<&str> {
+    rendered
+        .lines()
+        .map(|line| line.rsplit_once(" at ").map_or(line, |(frame, _)| frame))
+        .collect()
+}
+
+#[test]
+fn frames_of_skipped_files_are_hidden_when_rendered() {
+    let error = load_error();
+
+    // Single test so the process-wide setting can't race with other tests
+    snafu_virtstack::set_skip_files(["/rustc/", ".cargo/registry"]);
+    assert_eq!(
+        snafu_virtstack::skip_files(),
+        ["/rustc/", ".cargo/registry"]
+    );
+    let debug = format!("{error:?}");
+    let display = error.stack_display().to_string();
+    let rendered = error.render_with(&CompactFormatter);
+
+    snafu_virtstack::set_root_first(true);
+    let root_first = error.stack_display().to_string();
+    snafu_virtstack::set_root_first(false);
+
+    snafu_virtstack::set_skip_files(["argo/registry", "e/.cargo"]);
+    let partial = error.stack_display().to_string();
+    snafu_virtstack::set_skip_files([file!()]);
+    let tail = error.stack_display().to_string();
+    snafu_virtstack::set_skip_files(Vec::<String>::new());
+    let unfiltered = error.stack_display().to_string();
+
+    assert_eq!(
+        without_locations(&debug),
+        [
+            "Error: Failed to load user",
+            "Virtual Stack Trace:",
+            "  0: Failed to load user",
+            "  1: [Connect] Failed to get a connection",
+            "  (3 frames hidden)",
+        ]
+    );
+    assert_eq!(display, rendered);
+    assert_eq!(without_locations(&display), &without_locations(&debug)[2..]);
+    assert_eq!(
+        without_locations(&root_first),
+        [
+            "  (3 frames hidden)",
+            "  3: [Connect] Failed to get a connection",
+            "  4: Failed to load user",
+        ]
+    );
+    assert_eq!(
+        without_locations(&tail),
+        [
+            "  (2 frames hidden)",
+            "  2: Pool exhausted",
+            "  3: Checkout timed out",
+            "  4: Connection refused",
+        ]
+    );
+    // Prefixes only match whole path components at their start
+    assert_eq!(partial, unfiltered);
+    assert_eq!(unfiltered.lines().count(), 5);
+    assert_eq!(error.virtual_stack().len(), 5);
+}
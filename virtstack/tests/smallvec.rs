@@ -0,0 +1,36 @@
+#![cfg(feature = "smallvec")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{Stack, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Failed to read config"))]
+    ReadConfig { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum UnitError {
+    #[snafu(display("Unit failure"))]
+    Unit,
+}
+
+#[test]
+fn shallow_stacks_stay_inline() {
+    let io = std::io::Error::other("disk on fire");
+    let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+
+    let stack: Stack = error.virtual_stack();
+    assert!(!stack.spilled());
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.last().unwrap().message, "disk on fire");
+}
+
+#[test]
+fn stacks_convert_to_vec() {
+    let stack = UnitSnafu.build().virtual_stack();
+    let frames: Vec<_> = stack.into_vec();
+    assert_eq!(frames[0].message, "Unit failure");
+}
@@ -0,0 +1,95 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Inner failure"))]
+    Failure,
+}
+
+fn inner() -> (InnerError, u32) {
+    (FailureSnafu.build(), line!())
+}
+
+fn wrap(inner: InnerError) -> Box<InnerError> {
+    Box::new(inner)
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Named source"))]
+    Named { source: InnerError },
+
+    #[snafu(display("Renamed source"))]
+    Renamed {
+        #[snafu(source)]
+        cause: InnerError,
+    },
+
+    #[snafu(display("Converted source"))]
+    Converted {
+        #[snafu(source(from(InnerError, wrap)))]
+        inner: Box<InnerError>,
+    },
+
+    #[snafu(display("Not a source: {source}"))]
+    NotSource {
+        #[snafu(source(false))]
+        source: String,
+    },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Struct failure"))]
+struct StructError {
+    #[snafu(source)]
+    reason: InnerError,
+}
+
+/// Asserts that `stack` splices in the frame of the inner error created on `line`.
+fn assert_spliced(stack: &[snafu_virtstack::StackFrame], line: u32) {
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[1].message, "Inner failure");
+    assert_eq!(stack[1].variant, Some("Failure"));
+    assert_eq!(stack[1].location.line(), line);
+}
+
+#[test]
+fn field_named_source_is_spliced() {
+    let (error, line) = inner();
+    let error = Err::<(), _>(error).context(NamedSnafu).unwrap_err();
+    assert_spliced(&error.virtual_stack(), line);
+}
+
+#[test]
+fn source_attribute_marks_renamed_field() {
+    let (error, line) = inner();
+    let error = Err::<(), _>(error).context(RenamedSnafu).unwrap_err();
+    assert_spliced(&error.virtual_stack(), line);
+}
+
+#[test]
+fn source_from_attribute_marks_converted_field() {
+    let (error, line) = inner();
+    let error = Err::<(), _>(error).context(ConvertedSnafu).unwrap_err();
+    assert_spliced(&error.virtual_stack(), line);
+}
+
+#[test]
+fn source_false_excludes_field_named_source() {
+    let error = NotSourceSnafu { source: "plain" }.build();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "Not a source: plain");
+}
+
+#[test]
+fn source_attribute_marks_struct_field() {
+    let (error, line) = inner();
+    let error = Err::<(), _>(error).context(StructSnafu).unwrap_err();
+    assert_spliced(&error.virtual_stack(), line);
+}
@@ -0,0 +1,73 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Failed to read file"))]
+    ReadFile { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Failed to load settings"))]
+    LoadSettings { source: InnerError },
+}
+
+#[test]
+fn sources_yield_typed_errors_in_order() {
+    let io = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+    let error = Err::<(), _>(io)
+        .context(ReadFileSnafu)
+        .context(LoadSettingsSnafu)
+        .unwrap_err();
+
+    let sources: Vec<_> = error.sources().collect();
+    assert_eq!(sources.len(), 3);
+    assert!(sources[0].is::<OuterError>());
+    assert!(sources[1].is::<InnerError>());
+
+    let io = error
+        .sources()
+        .find_map(|e| e.downcast_ref::<std::io::Error>())
+        .unwrap();
+    assert_eq!(io.kind(), std::io::ErrorKind::PermissionDenied);
+
+    let messages: Vec<_> = error.sources().map(|e| e.to_string()).collect();
+    let stack: Vec<_> = error
+        .virtual_stack()
+        .into_iter()
+        .map(|f| f.message)
+        .collect();
+    assert_eq!(messages, stack);
+}
+
+#[test]
+fn sources_of_type_collects_every_layer_of_a_type() {
+    // An io::Error wrapping another error reports that error's source as its own
+    let denied = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+    let inner = Err::<(), _>(denied).context(ReadFileSnafu).unwrap_err();
+    let io = std::io::Error::other(inner);
+    let error = Err::<(), _>(io)
+        .context(ReadFileSnafu)
+        .context(LoadSettingsSnafu)
+        .unwrap_err();
+
+    let kinds: Vec<_> = error
+        .sources_of_type::<std::io::Error>()
+        .iter()
+        .map(|io| io.kind())
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            std::io::ErrorKind::Other,
+            std::io::ErrorKind::PermissionDenied
+        ]
+    );
+
+    assert_eq!(error.sources_of_type::<OuterError>().len(), 1);
+    assert_eq!(error.sources_of_type::<InnerError>().len(), 1);
+    assert!(error.sources_of_type::<std::fmt::Error>().is_empty());
+}
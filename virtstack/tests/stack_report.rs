@@ -0,0 +1,78 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{StackReport, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum StorageError {
+    #[snafu(display("Failed to open {path}"))]
+    Open {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// Borrows its path, so it can't leave the scope it was created in.
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Invalid path {path}"))]
+struct PathError<'a> {
+    path: &'a str,
+}
+
+fn open_error() -> StorageError {
+    OpenSnafu { path: "users.db" }.into_error(std::io::Error::other("permission denied"))
+}
+
+#[test]
+fn report_owns_the_message_and_frames() {
+    let error = open_error();
+    let stack = error.virtual_stack();
+
+    let report = error.to_report();
+    assert_eq!(report.message, "Failed to open users.db");
+    assert_eq!(report.frames.len(), stack.len());
+    for (owned, frame) in report.frames.iter().zip(stack.iter()) {
+        assert_eq!(owned.message, frame.message);
+        assert_eq!(owned.file, frame.location.file());
+        assert_eq!(owned.line, frame.location.line());
+        assert_eq!(owned.column, frame.location.column());
+    }
+    assert_eq!(report.frames[0].variant.as_deref(), Some("Open"));
+}
+
+#[test]
+fn report_outlives_borrowing_errors() {
+    let report = {
+        let path = String::from("/tmp/../etc");
+        PathSnafu {
+            path: path.as_str(),
+        }
+        .build()
+        .to_report()
+    };
+
+    assert_eq!(report.message, "Invalid path /tmp/../etc");
+    assert_eq!(report.frames.len(), 1);
+}
+
+#[test]
+fn report_crosses_threads_and_channels() {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<StackReport>();
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || sender.send(open_error().to_report()).unwrap())
+        .join()
+        .unwrap();
+
+    let report = receiver.recv().unwrap();
+    assert_eq!(report.frames[1].message, "permission denied");
+}
+
+#[test]
+fn report_displays_like_the_debug_output() {
+    let error = open_error();
+
+    assert_eq!(error.to_report().to_string(), format!("{error:?}"));
+}
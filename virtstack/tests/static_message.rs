@@ -0,0 +1,59 @@
+use std::borrow::Cow;
+
+use snafu::prelude::*;
+use snafu_virtstack::{Location, StackFrame, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Something went wrong"))]
+    SomethingWrong,
+
+    #[snafu(display("Failed to open {path}"))]
+    Open {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Braces {{escaped}}"))]
+    Escaped,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Service is shutting down"))]
+struct ShutdownError;
+
+#[test]
+fn constant_messages_are_borrowed() {
+    let stack = SomethingWrongSnafu.build().virtual_stack();
+    assert!(matches!(
+        stack[0].message,
+        Cow::Borrowed("Something went wrong")
+    ));
+
+    let stack = ShutdownSnafu.build().virtual_stack();
+    assert!(matches!(
+        stack[0].message,
+        Cow::Borrowed("Service is shutting down")
+    ));
+
+    let frame = StackFrame::new(Location::new("src/lib.rs", 1, 1), "static");
+    assert!(matches!(frame.message, Cow::Borrowed("static")));
+}
+
+#[test]
+fn formatted_messages_are_owned() {
+    let io = std::io::Error::other("missing");
+    let error = Err::<(), _>(io)
+        .context(OpenSnafu { path: "a.txt" })
+        .unwrap_err();
+    let stack = error.virtual_stack();
+    assert!(matches!(stack[0].message, Cow::Owned(_)));
+    assert_eq!(stack[0].message, "Failed to open a.txt");
+    assert_eq!(stack[1].message, "missing");
+
+    let stack = EscapedSnafu.build().virtual_stack();
+    assert!(matches!(stack[0].message, Cow::Owned(_)));
+    assert_eq!(stack[0].message, "Braces {escaped}");
+}
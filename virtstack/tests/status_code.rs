@@ -0,0 +1,48 @@
+use snafu::prelude::*;
+use snafu_virtstack::{ErrorExt, StatusCode, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ServiceError {
+    #[snafu(display("Invalid page size {size}"))]
+    #[status_code(InvalidArguments)]
+    InvalidPageSize { size: usize },
+
+    #[status_code(Unauthorized)]
+    #[snafu(display("Missing credentials"))]
+    MissingCredentials,
+
+    #[snafu(display("Failed to query database"))]
+    DatabaseQuery { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Request timed out"))]
+#[status_code(Timeout)]
+struct TimeoutError;
+
+#[test]
+fn variants_use_their_status_code() {
+    assert_eq!(
+        InvalidPageSizeSnafu { size: 0usize }.build().status_code(),
+        StatusCode::InvalidArguments
+    );
+    assert_eq!(
+        MissingCredentialsSnafu.build().status_code(),
+        StatusCode::Unauthorized
+    );
+}
+
+#[test]
+fn unannotated_variants_are_internal() {
+    let io = std::io::Error::other("connection reset");
+    let error = Err::<(), _>(io).context(DatabaseQuerySnafu).unwrap_err();
+    assert_eq!(error.status_code(), StatusCode::Internal);
+}
+
+#[test]
+fn structs_use_type_level_status_code() {
+    assert_eq!(TimeoutSnafu.build().status_code(), StatusCode::Timeout);
+    assert_eq!(StatusCode::Timeout.to_string(), "Timeout");
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "std")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Failed to read config"))]
+    Read { source: std::io::Error },
+
+    #[snafu(display("Invalid port"))]
+    Port { source: std::num::ParseIntError },
+
+    #[snafu(display("Failed to open config"))]
+    Open { source: Box<std::io::Error> },
+}
+
+fn messages(error: &dyn VirtualStackTrace) -> Vec<String> {
+    error
+        .virtual_stack()
+        .iter()
+        .map(|frame| frame.message.to_string())
+        .collect()
+}
+
+#[test]
+fn std_errors_have_a_single_frame_at_the_caller() {
+    let error = std::io::Error::other("disk full");
+
+    let (stack, line) = (error.virtual_stack(), line!());
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "disk full");
+    assert_eq!(stack[0].variant, None);
+    assert_eq!(stack[0].location.file(), file!());
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(error.frame_count(), 1);
+}
+
+#[test]
+fn std_errors_walk_their_sources() {
+    let inner = std::io::Error::other("disk full");
+    let error = std::io::Error::other(Wrapper(inner));
+
+    assert_eq!(messages(&error), ["wrapper", "disk full"]);
+}
+
+#[test]
+fn std_errors_work_as_trait_objects() {
+    let parse = "http".parse::<u16>().unwrap_err();
+    let utf8 = String::from_utf8(vec![0xff]).unwrap_err();
+    let errors: [&dyn VirtualStackTrace; 3] = [&parse, &utf8, &std::fmt::Error];
+
+    for error in errors {
+        assert_eq!(error.virtual_stack().len(), 1);
+        assert!(error.as_error().is_some());
+    }
+    assert_eq!(messages(&parse), ["invalid digit found in string"]);
+}
+
+#[test]
+fn std_sources_keep_the_location_of_their_wrapper() {
+    let io = std::io::Error::other("disk full");
+    let (error, line) = (Err::<(), _>(io).context(ReadSnafu).unwrap_err(), line!());
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[1].message, "disk full");
+    assert_eq!(stack[1].location.line(), line);
+
+    let parse = "x".parse::<u16>().unwrap_err();
+    let (error, line) = (Err::<(), _>(parse).context(PortSnafu).unwrap_err(), line!());
+    assert_eq!(error.virtual_stack()[1].location.line(), line);
+
+    let io = Box::new(std::io::Error::other("missing"));
+    let (error, line) = (Err::<(), _>(io).context(OpenSnafu).unwrap_err(), line!());
+    assert_eq!(error.virtual_stack()[1].location.line(), line);
+}
+
+#[derive(Debug)]
+struct Wrapper(std::io::Error);
+
+impl std::fmt::Display for Wrapper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("wrapper")
+    }
+}
+
+impl std::error::Error for Wrapper {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
@@ -0,0 +1,45 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to load configuration from {path}"))]
+struct ConfigError {
+    path: String,
+    source: std::io::Error,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Service is shutting down"))]
+struct ShutdownError;
+
+fn load(path: &str) -> (Result<(), ConfigError>, u32) {
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    (Err(io).context(ConfigSnafu { path }), line!())
+}
+
+#[test]
+fn struct_errors_capture_location_and_sources() {
+    let (result, line) = load("config.toml");
+    let error = result.unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(
+        stack[0].message,
+        "Failed to load configuration from config.toml"
+    );
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(stack[1].message, "missing");
+}
+
+#[test]
+fn unit_struct_errors_capture_location() {
+    let (error, line) = (ShutdownSnafu.build(), line!());
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].location.line(), line);
+    assert!(format!("{error:?}").starts_with("Error: Service is shutting down\n"));
+}
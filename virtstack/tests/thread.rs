@@ -0,0 +1,85 @@
+#![cfg(feature = "thread")]
+
+use std::thread;
+
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{OwnedFrame, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum WorkerError {
+    #[snafu(display("Job {id} failed"))]
+    Job { id: u32, source: std::io::Error },
+
+    #[snafu(display("Worker stopped"))]
+    Stopped,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Pool shut down"))]
+struct PoolError {
+    source: WorkerError,
+}
+
+fn job_error() -> WorkerError {
+    let io = std::io::Error::other("disk full");
+    Err::<(), _>(io).context(JobSnafu { id: 7u32 }).unwrap_err()
+}
+
+#[test]
+fn frames_record_the_thread_errors_were_created_on() {
+    let error = thread::Builder::new()
+        .name("worker-1".into())
+        .spawn(job_error)
+        .unwrap()
+        .join()
+        .unwrap();
+
+    // Frames are built here, on the test thread, but report the worker
+    let stack = error.virtual_stack();
+    assert_eq!(stack[0].thread.as_deref(), Some("worker-1"));
+    assert_eq!(stack[1].thread, None);
+
+    let owned = OwnedFrame::from(&stack[0]);
+    assert_eq!(owned.thread.as_deref(), Some("worker-1"));
+}
+
+#[test]
+fn nested_frames_keep_their_own_thread() {
+    let inner = thread::Builder::new()
+        .name("worker-2".into())
+        .spawn(|| StoppedSnafu.build())
+        .unwrap()
+        .join()
+        .unwrap();
+    let error = PoolSnafu.into_error(inner);
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack[0].thread.as_deref(), thread::current().name());
+    assert_eq!(stack[1].thread.as_deref(), Some("worker-2"));
+}
+
+#[test]
+fn unnamed_threads_are_reported_by_id() {
+    let (error, id) = thread::spawn(|| (StoppedSnafu.build(), thread::current().id()))
+        .join()
+        .unwrap();
+
+    let thread = error.virtual_stack()[0].thread.clone().unwrap();
+    assert_eq!(thread, format!("{id:?}"));
+}
+
+#[test]
+fn verbose_formatter_shows_the_thread() {
+    let error = thread::Builder::new()
+        .name("worker-3".into())
+        .spawn(|| StoppedSnafu.build())
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let rendered = error.render_with(&snafu_virtstack::VerboseFormatter);
+    assert!(rendered.contains("\n      on thread worker-3"));
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "timestamp")]
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{CapturedTime, OwnedFrame, VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum WorkerError {
+    #[snafu(display("Job {id} failed"))]
+    Job { id: u32, source: std::io::Error },
+
+    #[snafu(display("Worker stopped"))]
+    Stopped,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Pool shut down"))]
+struct PoolError {
+    source: WorkerError,
+}
+
+#[test]
+fn frames_record_the_time_errors_were_created_at() {
+    let before = SystemTime::now();
+    let io = std::io::Error::other("disk full");
+    let error = Err::<(), _>(io).context(JobSnafu { id: 7u32 }).unwrap_err();
+    let after = SystemTime::now();
+
+    let stack = error.virtual_stack();
+    let timestamp = stack[0].timestamp.unwrap();
+    assert!(before <= timestamp && timestamp <= after);
+    assert_eq!(stack[1].timestamp, None);
+}
+
+#[test]
+fn nested_frames_keep_their_own_time() {
+    let inner = StoppedSnafu.build();
+    std::thread::sleep(Duration::from_millis(5));
+    let error = PoolSnafu.into_error(inner);
+
+    let stack = error.virtual_stack();
+    assert!(stack[1].timestamp.unwrap() < stack[0].timestamp.unwrap());
+}
+
+#[test]
+fn timestamps_display_as_rfc3339() {
+    let at = |secs: u64, millis: u64| {
+        CapturedTime::from(UNIX_EPOCH + Duration::from_millis(secs * 1000 + millis))
+    };
+    assert_eq!(at(0, 0).to_string(), "1970-01-01T00:00:00.000Z");
+    assert_eq!(at(951_825_600, 7).to_string(), "2000-02-29T12:00:00.007Z");
+    assert_eq!(
+        at(1_715_938_867, 123).to_string(),
+        "2024-05-17T09:41:07.123Z"
+    );
+    assert_eq!(
+        at(4_107_542_399, 999).to_string(),
+        "2100-02-28T23:59:59.999Z"
+    );
+}
+
+#[test]
+fn owned_frames_and_verbose_output_show_the_time() {
+    let error = StoppedSnafu.build();
+    let frame = &error.virtual_stack()[0];
+    let time = CapturedTime::from(frame.timestamp.unwrap()).to_string();
+
+    assert_eq!(OwnedFrame::from(frame).timestamp, Some(time.clone()));
+    let rendered = error.render_with(&snafu_virtstack::VerboseFormatter);
+    assert!(rendered.contains(&format!("\n      captured {time}")));
+}
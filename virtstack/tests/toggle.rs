@@ -0,0 +1,32 @@
+use snafu::prelude::*;
+use snafu_virtstack::{DISABLE_ENV_VAR, is_enabled, set_enabled, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Failed to read file"))]
+    ReadFile { source: std::io::Error },
+}
+
+// A single test so the process-wide state isn't shared with parallel tests
+#[test]
+fn env_var_and_override_control_debug_output() {
+    // SAFETY: no other thread of this test binary reads the environment
+    unsafe { std::env::set_var(DISABLE_ENV_VAR, "1") };
+
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    let error = Err::<(), _>(io).context(ReadFileSnafu).unwrap_err();
+
+    assert!(!is_enabled());
+    assert_eq!(format!("{error:?}"), "Error: Failed to read file");
+
+    // The environment is only consulted once
+    unsafe { std::env::remove_var(DISABLE_ENV_VAR) };
+    assert!(!is_enabled());
+
+    set_enabled(true);
+    assert!(format!("{error:?}").contains("Virtual Stack Trace:\n  0: "));
+
+    set_enabled(false);
+    assert_eq!(format!("{error:?}"), "Error: Failed to read file");
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "tonic")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{ErrorExt, StatusCode, TONIC_STACK_METADATA_KEY, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Failed to read config"))]
+    #[status_code(Unavailable)]
+    ReadConfig { source: std::io::Error },
+
+    #[snafu(display("Something went wrong"))]
+    SomethingWrong,
+}
+
+fn read_config_error() -> MyError {
+    let io = std::io::Error::other("disk on fire");
+    Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err()
+}
+
+#[test]
+fn status_uses_code_and_top_level_message() {
+    let status = read_config_error().to_tonic_status();
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+    assert_eq!(status.message(), "Failed to read config");
+    assert!(status.metadata().is_empty());
+
+    let status = SomethingWrongSnafu.build().to_tonic_status();
+    assert_eq!(status.code(), tonic::Code::Internal);
+}
+
+#[test]
+fn stack_is_attached_as_binary_metadata() {
+    let error = read_config_error();
+    let status = error.to_tonic_status_with_stack();
+    assert_eq!(status.message(), "Failed to read config");
+
+    let stack = status
+        .metadata()
+        .get_bin(TONIC_STACK_METADATA_KEY)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    let stack = String::from_utf8(stack.to_vec()).unwrap();
+    assert!(stack.starts_with("  0: [ReadConfig] Failed to read config at "));
+    assert!(stack.contains("  1: disk on fire at "));
+}
+
+#[test]
+fn every_status_code_maps_to_a_grpc_code() {
+    assert_eq!(
+        tonic::Code::from(StatusCode::Timeout),
+        tonic::Code::DeadlineExceeded
+    );
+    assert_eq!(
+        tonic::Code::from(StatusCode::Unauthorized),
+        tonic::Code::Unauthenticated
+    );
+    assert_eq!(
+        tonic::Code::from(StatusCode::RateLimited),
+        tonic::Code::ResourceExhausted
+    );
+    assert_eq!(
+        tonic::Code::from(StatusCode::Unsupported),
+        tonic::Code::Unimplemented
+    );
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "tower")]
+
+use std::sync::{Arc, Mutex};
+
+use snafu::prelude::*;
+use snafu_virtstack::{VirtStackLayer, stack_trace_debug};
+use tower::{BoxError, Layer, Service, ServiceBuilder, ServiceExt, service_fn};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ApiError {
+    #[snafu(display("Failed to load user {id}"))]
+    LoadUser { id: u64, source: std::io::Error },
+}
+
+/// Minimal subscriber collecting the level and message of every event it sees.
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Events>>);
+
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{value:?}");
+        }
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        self.0
+            .lock()
+            .unwrap()
+            .push((*event.metadata().level(), message.0));
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+async fn load_user(id: u64) -> Result<String, ApiError> {
+    if id == 0 {
+        return Ok("root".to_string());
+    }
+    Err(std::io::Error::other("disk on fire")).context(LoadUserSnafu { id })
+}
+
+/// Level and message of each logged event.
+type Events = Vec<(Level, String)>;
+
+/// Calls `service` once with `request`, collecting the events logged meanwhile.
+fn call<S, R>(mut service: S, request: R) -> (Result<S::Response, S::Error>, Events)
+where
+    S: Service<R>,
+{
+    let collector = Collector::default();
+    let result = tracing::subscriber::with_default(collector.clone(), || {
+        pollster::block_on(async { service.ready().await?.call(request).await })
+    });
+    let events = collector.0.lock().unwrap().clone();
+    (result, events)
+}
+
+#[test]
+fn errors_are_logged_before_being_returned() {
+    let service = ServiceBuilder::new()
+        .layer(VirtStackLayer::new())
+        .service_fn(load_user);
+
+    let (result, events) = call(service, 7);
+
+    assert_eq!(result.unwrap_err().to_string(), "Failed to load user 7");
+    assert_eq!(
+        events,
+        [
+            (Level::ERROR, "Failed to load user 7".to_string()),
+            (Level::DEBUG, "disk on fire".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn responses_are_not_logged() {
+    let service = VirtStackLayer::new().layer(service_fn(load_user));
+
+    let (result, events) = call(service, 0);
+
+    assert_eq!(result.unwrap(), "root");
+    assert!(events.is_empty());
+}
+
+#[test]
+fn boxed_errors_pass_through_silently() {
+    let service = ServiceBuilder::new()
+        .layer(VirtStackLayer::new())
+        .service_fn(|_: ()| async { Err::<(), BoxError>("boom".into()) });
+
+    let (result, events) = call(service, ());
+
+    assert_eq!(result.unwrap_err().to_string(), "boom");
+    assert!(events.is_empty());
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "tracing")]
+
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Failed to read config"))]
+    ReadConfig { source: std::io::Error },
+}
+
+#[derive(Debug, Default)]
+struct Recorded {
+    level: Option<Level>,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for Recorded {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.fields
+            .push((field.name().to_string(), format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields
+            .push((field.name().to_string(), value.to_string()));
+    }
+}
+
+/// Minimal subscriber collecting every event it sees.
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Vec<Recorded>>>);
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut recorded = Recorded {
+            level: Some(*event.metadata().level()),
+            ..Recorded::default()
+        };
+        event.record(&mut recorded);
+        self.0.lock().unwrap().push(recorded);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn field<'a>(recorded: &'a Recorded, name: &str) -> &'a str {
+    &recorded
+        .fields
+        .iter()
+        .find(|(field, _)| field == name)
+        .unwrap()
+        .1
+}
+
+#[test]
+fn frames_are_emitted_as_structured_events() {
+    let io = std::io::Error::other("disk on fire");
+    let error = Err::<(), _>(io).context(ReadConfigSnafu).unwrap_err();
+    let stack = error.virtual_stack();
+
+    let collector = Collector::default();
+    tracing::subscriber::with_default(collector.clone(), || error.emit_tracing());
+
+    let events = collector.0.lock().unwrap();
+    assert_eq!(events.len(), 2);
+
+    assert_eq!(events[0].level, Some(Level::ERROR));
+    assert_eq!(field(&events[0], "message"), "Failed to read config");
+    assert_eq!(field(&events[0], "file"), file!());
+    assert_eq!(
+        field(&events[0], "line"),
+        stack[0].location.line().to_string()
+    );
+
+    assert_eq!(events[1].level, Some(Level::DEBUG));
+    assert_eq!(field(&events[1], "message"), "disk on fire");
+    assert_eq!(field(&events[1], "index"), "1");
+}
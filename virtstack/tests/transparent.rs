@@ -0,0 +1,77 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum DbError {
+    #[snafu(display("Query failed"))]
+    Query { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ServiceError {
+    #[snafu(display("Database error"))]
+    #[transparent]
+    Db { source: DbError },
+    #[snafu(display("Io error"))]
+    #[transparent]
+    Io { source: std::io::Error },
+    #[snafu(display("Request failed"))]
+    Request { source: DbError },
+}
+
+#[stack_trace_debug(transparent)]
+#[derive(Snafu)]
+#[snafu(display("Wrapped"))]
+struct Wrapper {
+    source: DbError,
+}
+
+fn query_error() -> DbError {
+    QuerySnafu.into_error(std::io::Error::other("connection reset"))
+}
+
+fn messages(error: &impl VirtualStackTrace) -> Vec<String> {
+    error
+        .virtual_stack()
+        .iter()
+        .map(|frame| frame.message.to_string())
+        .collect()
+}
+
+#[test]
+fn transparent_variant_starts_at_its_nested_source() {
+    let error = DbSnafu.into_error(query_error());
+
+    assert_eq!(messages(&error), ["Query failed", "connection reset"]);
+    assert_eq!(error.to_string(), "Database error");
+}
+
+#[test]
+fn transparent_variant_walks_a_foreign_source() {
+    let error = IoSnafu.into_error(std::io::Error::other("disk full"));
+
+    assert_eq!(messages(&error), ["disk full"]);
+}
+
+#[test]
+fn other_variants_keep_their_frame() {
+    let error = RequestSnafu.into_error(query_error());
+
+    assert_eq!(
+        messages(&error),
+        ["Request failed", "Query failed", "connection reset"]
+    );
+}
+
+#[test]
+fn argument_makes_the_struct_transparent() {
+    let error = WrapperSnafu.into_error(query_error());
+
+    assert_eq!(messages(&error), ["Query failed", "connection reset"]);
+    let debug = format!("{error:?}");
+    assert!(debug.starts_with("Error: Query failed\n"));
+    assert!(!debug.contains("Wrapped"));
+}
@@ -0,0 +1,10 @@
+//! Compile errors of the macros, checked against the expected diagnostics in `tests/ui`.
+//!
+//! After an intended change to a diagnostic, regenerate the `.stderr` files with
+//! `TRYBUILD=overwrite cargo test --test ui` and review the diff.
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
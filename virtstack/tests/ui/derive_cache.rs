@@ -0,0 +1,9 @@
+use snafu::prelude::*;
+use snafu_virtstack::StackTraceDebug;
+
+#[derive(Snafu, StackTraceDebug)]
+#[virtstack(cache)]
+#[snafu(display("Something went wrong"))]
+struct MyError;
+
+fn main() {}
@@ -0,0 +1,9 @@
+use snafu_virtstack::StackTraceDebug;
+
+#[derive(StackTraceDebug)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
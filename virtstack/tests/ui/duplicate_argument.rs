@@ -0,0 +1,9 @@
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug(max_depth = 32, max_message_len = 200, max_depth = 8)]
+#[derive(Snafu)]
+#[snafu(display("Something went wrong"))]
+struct MyError;
+
+fn main() {}
@@ -0,0 +1,13 @@
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum MyError {
+    #[snafu(display("Not found"))]
+    #[status_code(NotFound)]
+    #[status_code(Internal)]
+    Missing,
+}
+
+fn main() {}
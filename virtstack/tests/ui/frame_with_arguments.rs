@@ -0,0 +1,12 @@
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to charge card"))]
+struct ChargeError {
+    #[frame(rename = "id")]
+    order_id: u64,
+}
+
+fn main() {}
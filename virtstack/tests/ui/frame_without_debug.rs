@@ -0,0 +1,14 @@
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+struct Card;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Failed to charge card"))]
+struct ChargeError {
+    #[frame]
+    card: Card,
+}
+
+fn main() {}
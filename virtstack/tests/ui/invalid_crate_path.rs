@@ -0,0 +1,9 @@
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug(crate = "not a path")]
+#[derive(Snafu)]
+#[snafu(display("Something went wrong"))]
+struct MyError;
+
+fn main() {}
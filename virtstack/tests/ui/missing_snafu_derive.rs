@@ -0,0 +1,9 @@
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug]
+#[derive(Debug)]
+enum MyError {
+    Failed,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ServiceError {
+    #[snafu(display("Not found"))]
+    #[transparent]
+    NotFound,
+}
+
+fn main() {}
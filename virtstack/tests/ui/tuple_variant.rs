@@ -0,0 +1,14 @@
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum ConfigError {
+    #[snafu(display("Invalid port"))]
+    #[status_code(InvalidArguments)]
+    Parse(std::num::ParseIntError),
+    #[snafu(display("Missing port"))]
+    Missing,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use snafu::prelude::*;
+use snafu_virtstack::stack_trace_debug;
+
+#[stack_trace_debug(max_depth = 0)]
+#[derive(Snafu)]
+#[snafu(display("Something went wrong"))]
+struct MyError;
+
+fn main() {}
@@ -0,0 +1,109 @@
+#![cfg(feature = "valuable")]
+
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{Frames, VirtualStackTrace, stack_trace_debug};
+use valuable::{NamedValues, Valuable, Value, Visit};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum StorageError {
+    #[snafu(display("Failed to open {path}"))]
+    Open {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// Records every value visited as `name=value`, or as a `-` for each list item.
+#[derive(Default)]
+struct Recorder(Vec<String>);
+
+impl Visit for Recorder {
+    fn visit_value(&mut self, value: Value<'_>) {
+        match value {
+            Value::Structable(frame) => {
+                self.0.push(format!("- {}", frame.definition().name()));
+                frame.visit(self);
+            }
+            Value::Listable(list) => list.visit(self),
+            value => self.0.push(format!("{value:?}")),
+        }
+    }
+
+    fn visit_named_fields(&mut self, named_values: &NamedValues<'_>) {
+        for (field, value) in named_values {
+            let value = match value {
+                Value::String(value) => value.to_string(),
+                Value::U32(value) => value.to_string(),
+                value => format!("{value:?}"),
+            };
+            self.0.push(format!("{}={value}", field.name()));
+        }
+    }
+}
+
+fn record(value: &impl Valuable) -> Vec<String> {
+    let mut recorder = Recorder::default();
+    valuable::visit(value, &mut recorder);
+    recorder.0
+}
+
+#[test]
+fn frames_expose_their_fields() {
+    let location = snafu::Location::new("src/storage.rs", 42, 15);
+    let frame = snafu_virtstack::StackFrame::new(location, "Failed to open users.db");
+
+    assert_eq!(
+        record(&frame),
+        [
+            "- StackFrame",
+            "message=Failed to open users.db",
+            "file=src/storage.rs",
+            "line=42",
+            "column=15"
+        ]
+    );
+    assert!(matches!(frame.as_value(), Value::Structable(_)));
+}
+
+#[test]
+fn frames_wrapper_lists_the_whole_stack() {
+    let io = std::io::Error::other("permission denied");
+    let line = line!() + 1;
+    let error = OpenSnafu { path: "users.db" }.into_error(io);
+    let stack = error.virtual_stack();
+
+    let recorded = record(&Frames(&stack));
+    assert_eq!(
+        recorded,
+        [
+            "- StackFrame".to_string(),
+            "message=Failed to open users.db".to_string(),
+            format!("file={}", file!()),
+            format!("line={line}"),
+            "column=48".to_string(),
+            "- StackFrame".to_string(),
+            "message=permission denied".to_string(),
+            format!("file={}", file!()),
+            format!("line={line}"),
+            "column=48".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn frames_wrapper_is_a_list() {
+    let error = OpenSnafu { path: "users.db" }.into_error(std::io::Error::other("denied"));
+    let stack = error.virtual_stack();
+
+    let frames = Frames(&stack);
+    let Value::Listable(list) = frames.as_value() else {
+        panic!("frames should be a list");
+    };
+    assert_eq!(list.size_hint(), (2, Some(2)));
+    assert_eq!(
+        Frames(&[]).as_value().as_listable().unwrap().size_hint(),
+        (0, Some(0))
+    );
+}
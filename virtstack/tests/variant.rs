@@ -0,0 +1,66 @@
+use snafu::prelude::*;
+use snafu_virtstack::{VirtualStackTrace, stack_trace_debug};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum InnerError {
+    #[snafu(display("Operation failed"))]
+    FileRead,
+
+    #[snafu(display("Operation failed"))]
+    FileWrite,
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum OuterError {
+    #[snafu(display("Failed to save"))]
+    Save { source: InnerError },
+
+    #[snafu(display("Failed to open"))]
+    Open { source: std::io::Error },
+}
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+#[snafu(display("Struct failure"))]
+struct StructError;
+
+#[test]
+fn root_frames_name_their_variant() {
+    let read = FileReadSnafu.build().virtual_stack();
+    let write = FileWriteSnafu.build().virtual_stack();
+
+    assert_eq!(read[0].variant, Some("FileRead"));
+    assert_eq!(write[0].variant, Some("FileWrite"));
+    assert!(
+        read[0]
+            .to_string()
+            .starts_with("[FileRead] Operation failed at ")
+    );
+}
+
+#[test]
+fn nested_frames_keep_their_variant() {
+    let error = Err::<(), _>(FileWriteSnafu.build())
+        .context(SaveSnafu)
+        .unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack[0].variant, Some("Save"));
+    assert_eq!(stack[1].variant, Some("FileWrite"));
+}
+
+#[test]
+fn foreign_sources_and_structs_have_no_variant() {
+    let io = std::io::Error::other("missing");
+    let error = Err::<(), _>(io).context(OpenSnafu).unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack[0].variant, Some("Open"));
+    assert_eq!(stack[1].variant, None);
+    assert!(stack[1].to_string().starts_with("missing at "));
+
+    let stack = StructSnafu.build().virtual_stack();
+    assert_eq!(stack[0].variant, None);
+}
@@ -0,0 +1,133 @@
+use snafu::prelude::*;
+use snafu_virtstack::{
+    ErrorExt, StackTraceDebug, StatusCode, VirtualStackTrace, stack_trace_debug,
+};
+
+#[stack_trace_debug(cache)]
+#[derive(Snafu)]
+enum MixedError {
+    #[snafu(display("Service unavailable"))]
+    #[status_code(Unavailable)]
+    Unit,
+
+    #[snafu(display("Failed to read {path}"))]
+    #[status_code(NotFound)]
+    FileRead {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Already located"))]
+    Located {
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+}
+
+#[test]
+fn unit_variants_match() {
+    let (error, line) = (UnitSnafu.build(), line!());
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "Service unavailable");
+    assert_eq!(stack[0].variant, Some("Unit"));
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(error.status_code(), StatusCode::Unavailable);
+}
+
+#[test]
+fn struct_variants_match() {
+    let io = std::io::Error::other("missing");
+    let result = Err::<(), _>(io);
+    let (error, line) = (result.context(FileReadSnafu { path: "a.toml" }), line!());
+    let error = error.unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "Failed to read a.toml");
+    assert_eq!(stack[0].variant, Some("FileRead"));
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(stack[1].message, "missing");
+    assert_eq!(error.status_code(), StatusCode::NotFound);
+}
+
+#[test]
+fn declared_location_fields_match() {
+    let (error, line) = (LocatedSnafu.build(), line!());
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack[0].variant, Some("Located"));
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(error.status_code(), StatusCode::Internal);
+}
+
+/// Snafu rejects tuple variants, so they're covered through the derive with hand-written
+/// `Display` and `Error` implementations.
+#[derive(StackTraceDebug)]
+enum ConfigError {
+    #[status_code(InvalidArguments)]
+    Parse(std::num::ParseIntError),
+    Read {
+        path: &'static str,
+        source: std::io::Error,
+        location: snafu::Location,
+    },
+    Missing,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(_) => write!(f, "Invalid port"),
+            Self::Read { path, .. } => write!(f, "Failed to read {path}"),
+            Self::Missing => write!(f, "Config is missing"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse(source) => Some(source),
+            Self::Read { source, .. } => Some(source),
+            Self::Missing => None,
+        }
+    }
+}
+
+#[test]
+fn tuple_variants_match() {
+    let parse = "http".parse::<u16>().unwrap_err();
+    let (error, line) = (ConfigError::Parse(parse), line!());
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "Invalid port");
+    assert_eq!(stack[0].variant, Some("Parse"));
+    assert_eq!(stack[1].message, "invalid digit found in string");
+    assert_eq!(error.location(), None);
+    assert_ne!(stack[0].location.line(), line);
+    assert_eq!(error.status_code(), StatusCode::InvalidArguments);
+    assert!(format!("{error:?}").contains("0: [Parse] Invalid port at "));
+}
+
+#[test]
+fn derive_matches_all_three_forms() {
+    let location = snafu::Location::new(file!(), 7, 1);
+    let read = ConfigError::Read {
+        path: "app.toml",
+        source: std::io::Error::other("missing"),
+        location,
+    };
+
+    let stack = read.virtual_stack();
+    assert_eq!(stack[0].variant, Some("Read"));
+    assert_eq!(stack[0].location.line(), 7);
+    assert_eq!(stack[1].message, "missing");
+    assert_eq!(read.status_code(), StatusCode::Internal);
+
+    let missing = ConfigError::Missing.virtual_stack();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].variant, Some("Missing"));
+}
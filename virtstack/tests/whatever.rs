@@ -0,0 +1,66 @@
+use snafu::IntoError;
+use snafu::prelude::*;
+use snafu_virtstack::{VirtWhatever, VirtualStackTrace, stack_trace_debug, virt_whatever};
+
+#[stack_trace_debug]
+#[derive(Snafu)]
+enum AppError {
+    #[snafu(display("Failed to start"))]
+    Startup { source: VirtWhatever },
+}
+
+impl From<VirtWhatever> for AppError {
+    fn from(source: VirtWhatever) -> Self {
+        StartupSnafu.into_error(source)
+    }
+}
+
+fn check(ready: bool) -> (Result<(), VirtWhatever>, u32) {
+    let line = line!() + 2;
+    let result = (|| {
+        ensure_whatever!(ready, "Service is not ready");
+        Ok(())
+    })();
+    (result, line)
+}
+
+fn start() -> Result<(), AppError> {
+    virt_whatever!("Port {} is taken", 8080);
+}
+
+#[test]
+fn whatever_errors_capture_location() {
+    let (result, line) = check(false);
+    let error = result.unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].message, "Service is not ready");
+    assert_eq!(stack[0].location.line(), line);
+    assert_eq!(error.message(), "Service is not ready");
+    assert!(format!("{error:?}").starts_with("Error: Service is not ready\n"));
+}
+
+#[test]
+fn whatever_sources_are_walked() {
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    let error = Err::<(), _>(io)
+        .whatever_context::<_, VirtWhatever>("Failed to read config")
+        .unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "Failed to read config");
+    assert_eq!(stack[1].message, "missing");
+}
+
+#[test]
+fn macro_converts_into_the_returned_error() {
+    let error = start().unwrap_err();
+
+    let stack = error.virtual_stack();
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].message, "Failed to start");
+    assert_eq!(stack[1].message, "Port 8080 is taken");
+    assert_eq!(stack[1].location.file(), file!());
+}
@@ -1,100 +1,1381 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{Data, DeriveInput, parse_macro_input};
+use quote::{quote, quote_spanned};
+use syn::parse::Parse;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, parse_macro_input, parse_quote};
 
 /// Proc macro attribute to automatically generate virtual stack traces for SNAFU errors.
 ///
 /// This attribute automatically implements the [`VirtualStackTrace`] trait and provides
 /// a custom [`Debug`] implementation that displays a formatted virtual stack trace.
 ///
+/// It can be applied to both enums and structs, which must also derive `Snafu` below the
+/// attribute. The attribute injects an implicit `location: snafu::Location` field into the
+/// struct, or every struct and unit variant of the enum, that doesn't already declare one, so
+/// each error records where it was propagated. The field is marked `#[snafu(provide)]` for
+/// `request_ref` when SNAFU's nightly `unstable-provider-api` feature is enabled.
+/// With the `thread` and `timestamp` features of `snafu-virtstack`, implicit fields
+/// capturing the current thread and time are injected next to it.
+///
+/// A type without a visible `#[derive(Snafu)]`, including one whose derive is placed above
+/// the attribute, is rejected with a compile error pointing at its name. Types with
+/// hand-written `Display` and `Error` impls use `#[derive(StackTraceDebug)]` instead.
+///
+/// For generic types, the generated impls are bounded by `Self: Display`, so they apply to
+/// exactly the instantiations Snafu can display. Lifetime parameters are kept as declared,
+/// so errors borrowing their fields work too, although `Error::source` only lets them be
+/// the source of another error when they borrow `'static` data.
+///
+/// # Variant Attributes
+///
+/// - `#[status_code(Code)]`: Sets the [`StatusCode`] returned by [`ErrorExt::status_code`]
+///   for a variant (or for a struct). Unannotated variants return `StatusCode::Internal`.
+/// - `#[level(Level)]`: Sets the [`Level`] returned by [`ErrorExt::level`] for a variant (or
+///   for a struct), one of `Warn`, `Error` and `Fatal`. Unannotated variants return
+///   `Level::Error`. With the `color` feature, the label of the generated [`Debug`] output
+///   is yellow for `Warn` and red for `Fatal`.
+/// - `#[external_msg("...")]`: Replaces the display string returned by
+///   [`ErrorExt::output_msg`] for a variant (or for a struct), so clients get a fixed message
+///   while `Display` and the virtual stack keep the detailed one.
+/// - `#[transparent]`: Leaves the frame of a variant that only passes its source through
+///   out of the virtual stack, which then starts at the source. The variant needs a source.
+///
+/// # Field Attributes
+///
+/// - `#[frame]`: Records the field in the [`StackFrame::metadata`] of the error's frame as
+///   `field_name=value`, using the field's [`Debug`] output, so values the display string
+///   leaves out still show up in the trace. The field type must implement [`Debug`].
+///
+/// # Arguments
+///
+/// Arguments are comma-separated, and each one may only be given once. Unknown or repeated
+/// arguments are rejected with a compile error.
+///
+/// - `max_depth = N`: Maximum number of frames collected while walking the source chain
+///   (defaults to [`DEFAULT_MAX_DEPTH`]). Longer chains, including cyclic ones, end with a
+///   synthetic truncation frame.
+/// - `max_message_len = N`: Cuts each message in the generated [`Debug`] output off after
+///   `N` characters, ending it with `…`, in place of the process-wide
+///   [`set_max_message_len`] setting.
+/// - `max_frames = N`: Renders only the first `N` frames in the generated [`Debug`]
+///   output, summarizing the rest in a `  ... (K more frames)` line, in place of the
+///   process-wide [`set_max_frames`] setting. Unlike `max_depth` this only shortens the
+///   output, the stack keeps every frame.
+/// - `root_first`: Lists the frames of the generated [`Debug`] output from the root cause
+///   outwards, as the process-wide [`set_root_first`] setting does for all types.
+/// - `clean_messages`: Strips a trailing `": "` followed by the next frame's message from
+///   each frame of the generated [`Debug`] output, as the process-wide
+///   [`set_clean_messages`] setting does for all types.
+/// - `compact_leaf`: Writes the generated [`Debug`] output of an error without sources on
+///   a single line, `Error: {message} at {location}`, as the process-wide
+///   [`set_compact_leaf`] setting does for all types. `{:#?}` keeps the full layout.
+/// - `dedup`: Collapses a frame whose message is equal to, or a suffix of, the preceding
+///   frame's message, as happens with `#[snafu(display("...: {source}"))]`.
+/// - `dedup_os_errors`: Strips a trailing `(os error N)` from a frame when the preceding
+///   frame already mentions it, as happens when several layers embed the message of an
+///   [`std::io::Error`]. Frames are kept, unlike with `dedup`.
+/// - `crate = "path"`: Path through which the generated code refers to this crate, for
+///   when it is only reachable through a re-export (defaults to `snafu_virtstack`).
+/// - `no_debug`: Skips the generated [`Debug`] implementation, for types that provide their
+///   own. [`VirtualStackTrace`] is still implemented.
+/// - `no_source_walk`: Reports only the error's own frame, without visiting its sources,
+///   for errors wrapping sources whose `Display` or `source()` are expensive or recursive.
+///   `max_depth` has no effect then, and `Error::source` still returns the source.
+/// - `transparent`: Makes every variant, or the struct, `#[transparent]`.
+/// - `cache`: Injects an implicit [`StackCache`] field next to `location`, so the source
+///   chain is only walked and formatted once per error no matter how often its stack or
+///   `Debug` output is requested.
+/// - `error_label = "..."`: Replaces the `Error` label of the first `Debug` line
+///   (`Error: {error}`).
+/// - `trace_label = "..."`: Replaces the `Virtual Stack Trace` label introducing the frames.
+/// - `location_separator = "..."`: Replaces the `" at "` between the message and the
+///   location of each frame of the generated [`Debug`] output, in place of the
+///   process-wide [`set_location_separator`] setting.
+///
 /// See the main [`snafu_virtstack`] crate documentation for comprehensive usage examples
 /// and detailed information about virtual stack traces.
 ///
 /// [`VirtualStackTrace`]: snafu_virtstack::VirtualStackTrace
+/// [`DEFAULT_MAX_DEPTH`]: snafu_virtstack::DEFAULT_MAX_DEPTH
+/// [`StackCache`]: snafu_virtstack::StackCache
+/// [`set_max_message_len`]: snafu_virtstack::set_max_message_len
+/// [`set_max_frames`]: snafu_virtstack::set_max_frames
+/// [`set_root_first`]: snafu_virtstack::set_root_first
+/// [`set_clean_messages`]: snafu_virtstack::set_clean_messages
+/// [`set_compact_leaf`]: snafu_virtstack::set_compact_leaf
+/// [`set_location_separator`]: snafu_virtstack::set_location_separator
+/// [`StatusCode`]: snafu_virtstack::StatusCode
+/// [`StackFrame::metadata`]: snafu_virtstack::StackFrame::metadata
+/// [`ErrorExt::status_code`]: snafu_virtstack::ErrorExt::status_code
+/// [`Level`]: snafu_virtstack::Level
+/// [`ErrorExt::level`]: snafu_virtstack::ErrorExt::level
+/// [`ErrorExt::output_msg`]: snafu_virtstack::ErrorExt::output_msg
 /// [`snafu_virtstack`]: https://docs.rs/snafu_virtstack
 #[proc_macro_attribute]
-pub fn stack_trace_debug(_args: TokenStream, input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
+pub fn stack_trace_debug(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut parsed_args = Args::default();
+    let parser = syn::meta::parser(|meta| parsed_args.parse(meta));
+    parse_macro_input!(args with parser);
+
+    let mut input = parse_macro_input!(input as DeriveInput);
 
     // Generate the enhanced version with virtual stack trace implementation
-    match generate_stack_trace_impl(&input) {
+    match generate_stack_trace_impl(&parsed_args, &mut input) {
         Ok(tokens) => tokens.into(),
         Err(err) => err.to_compile_error().into(),
     }
 }
 
-fn generate_stack_trace_impl(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let name = &input.ident;
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+/// Derive macro generating the same implementations as [`macro@stack_trace_debug`], for
+/// teams that prefer listing it next to `Snafu` in `#[derive(Snafu, StackTraceDebug)]`.
+///
+/// A derive can't add fields to the type, so nothing is injected: declare
+/// `#[snafu(implicit)] location: snafu::Location` in the struct, or in each variant, whose
+/// location should be recorded. Frames of variants without one are located where the
+/// virtual stack is requested. The fields capturing the thread and time are likewise only
+/// read when declared, as `virtstack_thread` and `virtstack_timestamp`.
+///
+/// Messages come from the type's `Display` implementation and sources from
+/// `Error::source`, so `Snafu` is not required: hand-written implementations work as well.
+/// Such enums may also have tuple variants like `Parse(ParseIntError)`, whose frames are
+/// located where the stack is requested and whose sources are walked through
+/// `Error::source`.
+///
+/// The `#[status_code(Code)]`, `#[level(Level)]` and `#[external_msg("...")]` attributes
+/// work as with the attribute form. Arguments go into a `#[virtstack(...)]` attribute on the
+/// type and accept everything `#[stack_trace_debug(...)]` does except `cache`, which
+/// injects a field.
+///
+/// See the "Derive Form" section of the main [`snafu_virtstack`] crate documentation for an
+/// example.
+///
+/// [`snafu_virtstack`]: https://docs.rs/snafu_virtstack
+#[proc_macro_derive(
+    StackTraceDebug,
+    attributes(virtstack, status_code, level, external_msg, transparent, frame)
+)]
+pub fn derive_stack_trace_debug(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
 
-    // Parse the enum to understand its structure
-    let _data = match &input.data {
-        Data::Enum(data) => data,
-        _ => {
-            return Err(syn::Error::new_spanned(
-                input,
-                "stack_trace_debug can only be applied to enums",
-            ));
+    match generate_derive_impl(&mut input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Arguments accepted by `#[stack_trace_debug(...)]`.
+#[derive(Default)]
+struct Args {
+    max_depth: Option<syn::LitInt>,
+    max_message_len: Option<syn::LitInt>,
+    max_frames: Option<syn::LitInt>,
+    dedup: bool,
+    dedup_os_errors: bool,
+    root_first: bool,
+    clean_messages: bool,
+    compact_leaf: bool,
+    error_label: Option<syn::LitStr>,
+    trace_label: Option<syn::LitStr>,
+    location_separator: Option<syn::LitStr>,
+    cache: bool,
+    no_debug: bool,
+    no_source_walk: bool,
+    transparent: bool,
+    krate: Option<syn::Path>,
+    /// Arguments parsed so far, to reject repeated ones.
+    seen: Vec<String>,
+}
+
+impl Args {
+    /// Path through which the generated code refers to `snafu_virtstack`.
+    fn krate(&self) -> syn::Path {
+        match &self.krate {
+            Some(path) => path.clone(),
+            None => parse_quote!(snafu_virtstack),
         }
-    };
+    }
 
-    // Generate VirtualStackTrace implementation
-    let stack_trace_impl =
-        generate_virtual_stack_trace_impl(name, &impl_generics, &ty_generics, where_clause)?;
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        let path = &meta.path;
+        let name = quote!(#path).to_string();
+        if self.seen.contains(&name) {
+            return Err(meta.error("duplicate stack_trace_debug argument"));
+        }
+        self.seen.push(name);
+
+        if meta.path.is_ident("max_depth") {
+            let lit: syn::LitInt = meta.value()?.parse()?;
+            if lit.base10_parse::<usize>()? == 0 {
+                return Err(syn::Error::new_spanned(lit, "max_depth must be at least 1"));
+            }
+            self.max_depth = Some(lit);
+            Ok(())
+        } else if meta.path.is_ident("max_message_len") {
+            let lit: syn::LitInt = meta.value()?.parse()?;
+            lit.base10_parse::<usize>()?;
+            self.max_message_len = Some(lit);
+            Ok(())
+        } else if meta.path.is_ident("max_frames") {
+            let lit: syn::LitInt = meta.value()?.parse()?;
+            if lit.base10_parse::<usize>()? == 0 {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "max_frames must be at least 1",
+                ));
+            }
+            self.max_frames = Some(lit);
+            Ok(())
+        } else if meta.path.is_ident("dedup") {
+            self.dedup = true;
+            Ok(())
+        } else if meta.path.is_ident("dedup_os_errors") {
+            self.dedup_os_errors = true;
+            Ok(())
+        } else if meta.path.is_ident("root_first") {
+            self.root_first = true;
+            Ok(())
+        } else if meta.path.is_ident("clean_messages") {
+            self.clean_messages = true;
+            Ok(())
+        } else if meta.path.is_ident("compact_leaf") {
+            self.compact_leaf = true;
+            Ok(())
+        } else if meta.path.is_ident("no_debug") {
+            self.no_debug = true;
+            Ok(())
+        } else if meta.path.is_ident("no_source_walk") {
+            self.no_source_walk = true;
+            Ok(())
+        } else if meta.path.is_ident("transparent") {
+            self.transparent = true;
+            Ok(())
+        } else if meta.path.is_ident("cache") {
+            self.cache = true;
+            Ok(())
+        } else if meta.path.is_ident("crate") {
+            let lit: syn::LitStr = meta.value()?.parse()?;
+            let path = lit.parse().map_err(|_| {
+                syn::Error::new_spanned(
+                    &lit,
+                    "expected a path, such as `crate = \"::my_virtstack\"`",
+                )
+            })?;
+            self.krate = Some(path);
+            Ok(())
+        } else if meta.path.is_ident("error_label") {
+            self.error_label = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("trace_label") {
+            self.trace_label = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("location_separator") {
+            self.location_separator = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported stack_trace_debug argument"))
+        }
+    }
+}
+
+fn generate_derive_impl(input: &mut DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if let Data::Union(_) = input.data {
+        return Err(syn::Error::new_spanned(
+            &*input,
+            "StackTraceDebug can only be derived for enums and structs",
+        ));
+    }
+
+    let mut args = Args::default();
+    if let Some(attr) = take_attr(&mut input.attrs, "virtstack")? {
+        attr.parse_nested_meta(|meta| args.parse(meta))?;
+    }
+    if args.cache {
+        // Still emit the impls without the cache, so this stays the only error
+        args.cache = false;
+        let error = syn::Error::new_spanned(
+            &input.ident,
+            "the `cache` argument injects a field and requires the #[stack_trace_debug] \
+             attribute",
+        );
+        let mut tokens = error.to_compile_error();
+        tokens.extend(generate_impls(&args, input)?);
+        return Ok(tokens);
+    }
+
+    generate_impls(&args, input)
+}
+
+fn generate_stack_trace_impl(
+    args: &Args,
+    input: &mut DeriveInput,
+) -> syn::Result<proc_macro2::TokenStream> {
+    // Parse the item to understand its structure
+    if let Data::Union(_) = input.data {
+        return Err(syn::Error::new_spanned(
+            &*input,
+            "stack_trace_debug can only be applied to enums and structs",
+        ));
+    }
+
+    // The generated Debug relies on the Display impl and the injected fields
+    // that Snafu provides, which only works while its derive is still pending,
+    // i.e. when this attribute is placed above it
+    if !has_snafu_derive(input) {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "stack_trace_debug requires `#[derive(Snafu)]` on the same type, placed below \
+             the `#[stack_trace_debug]` attribute; for hand-written `Display` and `Error` \
+             impls, use `#[derive(StackTraceDebug)]` instead",
+        ));
+    }
+
+    // Snafu rejects tuple variants with its own error, leave it as the only one instead of
+    // piling up unsatisfied Display and Error bounds from the impls. The derive, which
+    // doesn't need Snafu, supports them
+    if has_tuple_variant(&input.data) {
+        strip_variant_attrs(&mut input.data);
+        strip_frame_attrs(&mut input.data);
+        return Ok(quote!(#input));
+    }
+
+    let krate = args.krate();
+    inject_location_fields(input);
+    if args.cache {
+        inject_cache_fields(input, &krate);
+    }
+    inject_capture_fields(input, &krate);
+
+    // Strip our own attributes before the item is emitted again
+    let impls = generate_impls(args, input)?;
 
     Ok(quote! {
-        // First, emit the original item unchanged
+        // First, emit the original item with any injected fields
         #input
 
-        // Finally, add the VirtualStackTrace implementation
+        // Finally, add the trait implementations
+        #impls
+    })
+}
+
+/// Generates the `VirtualStackTrace`, `Debug` and `ErrorExt` implementations shared by the
+/// attribute and the derive, removing our own attributes from `input`.
+fn generate_impls(args: &Args, input: &mut DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let krate = args.krate();
+    let status_code_impl = generate_status_code_impl(input, &krate)?;
+    let transparent = take_transparent(args, &mut input.data)?;
+
+    let name = &input.ident;
+    let generics = display_bounded(&input.generics);
+
+    // Generate VirtualStackTrace implementation
+    let stack_trace_impl = generate_virtual_stack_trace_impl(
+        args,
+        name,
+        &input.attrs,
+        &input.data,
+        &transparent,
+        &generics,
+    )?;
+    strip_frame_attrs(&mut input.data);
+
+    Ok(quote! {
         #stack_trace_impl
+
+        #status_code_impl
+    })
+}
+
+/// Takes the `#[transparent]` attributes of the variants, returning for each variant, or
+/// for the struct, whether its own frame is left out of the stack.
+///
+/// The `transparent` argument makes every variant transparent. Transparent variants need
+/// a source to start the stack at, which `no_source_walk` would never visit.
+fn take_transparent(args: &Args, data: &mut Data) -> syn::Result<Vec<bool>> {
+    let check = |fields: &Fields, span: &dyn quote::ToTokens| {
+        if args.no_source_walk {
+            Err(syn::Error::new_spanned(
+                span,
+                "transparent errors start the stack at their source and can't be combined \
+                 with `no_source_walk`",
+            ))
+        } else if source_field(fields).is_none() {
+            Err(syn::Error::new_spanned(
+                span,
+                "transparent errors need a source to start the stack at",
+            ))
+        } else {
+            Ok(())
+        }
+    };
+
+    match data {
+        Data::Enum(data) => {
+            let mut transparent = Vec::new();
+            for variant in &mut data.variants {
+                let attr = take_attr(&mut variant.attrs, "transparent")?;
+                match &attr {
+                    Some(attr) => {
+                        attr.meta.require_path_only()?;
+                        check(&variant.fields, attr)?;
+                    }
+                    None if args.transparent => check(&variant.fields, &variant.ident)?,
+                    None => {}
+                }
+                transparent.push(args.transparent || attr.is_some());
+            }
+            Ok(transparent)
+        }
+        Data::Struct(data) => {
+            if args.transparent {
+                check(&data.fields, &data.struct_token)?;
+            }
+            Ok(vec![args.transparent])
+        }
+        Data::Union(_) => Ok(Vec::new()),
+    }
+}
+
+/// Generates an expression evaluating to `Some` with the nested stack of the source of
+/// `self` if it is transparent, or `None` if no variant is transparent.
+fn transparent_stack(
+    data: &Data,
+    transparent: &[bool],
+    krate: &syn::Path,
+) -> Option<proc_macro2::TokenStream> {
+    if !transparent.contains(&true) {
+        return None;
+    }
+    match data {
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .zip(transparent)
+                .map(|(variant, transparent)| {
+                    let ident = &variant.ident;
+                    match source_field(&variant.fields).filter(|_| *transparent) {
+                        Some(source) => quote! {
+                            Self::#ident { #source: source, .. } => {
+                                Some((&&#krate::__private::Source(source)).nested_stack())
+                            }
+                        },
+                        None => quote!(Self::#ident { .. } => None,),
+                    }
+                });
+            Some(quote! {
+                match self {
+                    #(#arms)*
+                }
+            })
+        }
+        Data::Struct(data) => {
+            let source = source_field(&data.fields)?;
+            Some(quote!(Some((&&#krate::__private::Source(&self.#source)).nested_stack())))
+        }
+        Data::Union(_) => None,
+    }
+}
+
+/// Returns true if the item carries a `#[derive(...)]` that includes `Snafu`.
+fn has_snafu_derive(input: &DeriveInput) -> bool {
+    input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .any(|attr| {
+            let mut found = false;
+            // Derive lists we can't parse are simply not ours to inspect
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == "Snafu")
+                {
+                    found = true;
+                }
+                Ok(())
+            });
+            found
+        })
+}
+
+/// Adds `#[snafu(implicit, provide)] location: ::snafu::Location` to the struct, or to every
+/// struct and unit variant of the enum, unless a `location` field already exists.
+///
+/// SNAFU parses `provide` without generating anything unless its `unstable-provider-api`
+/// feature is enabled, so marking the field is free on stable.
+fn inject_location_fields(input: &mut DeriveInput) {
+    match &mut input.data {
+        Data::Enum(data) => {
+            for variant in &mut data.variants {
+                inject_location_field(&mut variant.fields);
+            }
+        }
+        Data::Struct(data) => inject_location_field(&mut data.fields),
+        Data::Union(_) => {}
+    }
+}
+
+fn inject_location_field(fields: &mut Fields) {
+    if has_location_field(fields) {
+        return;
+    }
+
+    match fields {
+        Fields::Named(named) => {
+            named
+                .named
+                .push(parse_quote!(#[snafu(implicit, provide)] location: ::snafu::Location));
+        }
+        Fields::Unit => {
+            *fields = Fields::Named(parse_quote!({
+                #[snafu(implicit, provide)]
+                location: ::snafu::Location
+            }));
+        }
+        // SNAFU doesn't support implicit fields on tuple types, leave them untouched
+        Fields::Unnamed(_) => {}
+    }
+}
+
+/// Adds the `#[snafu(implicit)]` [`CACHE_FIELD`] to the struct, or to every variant with
+/// named fields.
+fn inject_cache_fields(input: &mut DeriveInput, krate: &syn::Path) {
+    match &mut input.data {
+        Data::Enum(data) => {
+            for variant in &mut data.variants {
+                inject_cache_field(&mut variant.fields, krate);
+            }
+        }
+        Data::Struct(data) => inject_cache_field(&mut data.fields, krate),
+        Data::Union(_) => {}
+    }
+}
+
+fn inject_cache_field(fields: &mut Fields, krate: &syn::Path) {
+    // Unit variants have already been turned into named ones by the location injection
+    if let Fields::Named(named) = fields {
+        let ident = syn::Ident::new(CACHE_FIELD, proc_macro2::Span::call_site());
+        named
+            .named
+            .push(parse_quote!(#[snafu(implicit)] #ident: #krate::StackCache));
+    }
+}
+
+/// Name of the field injected by the `cache` argument.
+const CACHE_FIELD: &str = "virtstack_cache";
+
+/// An implicit field injected by an optional feature of `snafu-virtstack`, recording the
+/// circumstances under which an error was created.
+struct Capture {
+    /// Name of the injected field
+    field: &'static str,
+    /// Type of the field, relative to the crate root
+    ty: &'static str,
+    /// `StackIter` method attributing the captured value to the frame of the error
+    method: &'static str,
+}
+
+/// The captures enabled by the features of `snafu-virtstack`, which enable the features of
+/// the same name of this crate.
+const CAPTURES: &[Capture] = &[
+    #[cfg(feature = "thread")]
+    Capture {
+        field: "virtstack_thread",
+        ty: "CapturedThread",
+        method: "with_thread",
+    },
+    #[cfg(feature = "timestamp")]
+    Capture {
+        field: "virtstack_timestamp",
+        ty: "CapturedTime",
+        method: "with_timestamp",
+    },
+];
+
+/// Adds the `#[snafu(implicit)]` fields of all enabled [`CAPTURES`] to the struct, or to
+/// every variant with named fields.
+fn inject_capture_fields(input: &mut DeriveInput, krate: &syn::Path) {
+    match &mut input.data {
+        Data::Enum(data) => {
+            for variant in &mut data.variants {
+                inject_capture_field(&mut variant.fields, krate);
+            }
+        }
+        Data::Struct(data) => inject_capture_field(&mut data.fields, krate),
+        Data::Union(_) => {}
+    }
+}
+
+fn inject_capture_field(fields: &mut Fields, krate: &syn::Path) {
+    if let Fields::Named(named) = fields {
+        for capture in CAPTURES {
+            let ident = syn::Ident::new(capture.field, proc_macro2::Span::call_site());
+            let ty = syn::Ident::new(capture.ty, proc_macro2::Span::call_site());
+            named
+                .named
+                .push(parse_quote!(#[snafu(implicit)] #ident: #krate::#ty));
+        }
+    }
+}
+
+/// Generates statements handing the values of the enabled [`CAPTURES`] of `self` to `stack`.
+fn captures(data: &Data, krate: &syn::Path) -> proc_macro2::TokenStream {
+    CAPTURES
+        .iter()
+        .map(|capture| {
+            let lookup = injected_field(data, capture.field);
+            let ty = syn::Ident::new(capture.ty, proc_macro2::Span::call_site());
+            let method = syn::Ident::new(capture.method, proc_macro2::Span::call_site());
+            quote! {
+                let captured: Option<&#krate::#ty> = #lookup;
+                let stack = match captured {
+                    Some(captured) => stack.#method(captured),
+                    None => stack,
+                };
+            }
+        })
+        .collect()
+}
+
+/// Finds the field SNAFU treats as the error source.
+///
+/// Like SNAFU, this is a field marked `#[snafu(source)]` or `#[snafu(source(from(...)))]`,
+/// or else a field named `source` that isn't marked `#[snafu(source(false))]`.
+fn source_field(fields: &Fields) -> Option<&syn::Ident> {
+    let named = || {
+        fields
+            .iter()
+            .filter_map(|field| Some((field.ident.as_ref()?, field)))
+    };
+
+    named()
+        .find(|(_, field)| source_attr(field) == Some(true))
+        .or_else(|| {
+            named().find(|(ident, field)| *ident == "source" && source_attr(field) != Some(false))
+        })
+        .map(|(ident, _)| ident)
+}
+
+/// Reads the `source` option of the `#[snafu(...)]` attributes of `field`, if present.
+fn source_attr(field: &syn::Field) -> Option<bool> {
+    let mut source = None;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("snafu"))
+    {
+        // Malformed attributes are reported by the Snafu derive itself
+        let _ = attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("source") {
+                return skip_meta_value(&meta);
+            }
+            if !meta.input.peek(syn::token::Paren) {
+                source = Some(true);
+                return Ok(());
+            }
+
+            let content;
+            syn::parenthesized!(content in meta.input);
+            // `source(false)` opts out, `source(from(...))` and `source(true)` opt in
+            source = match content.parse::<syn::LitBool>() {
+                Ok(lit) => Some(lit.value),
+                Err(_) => {
+                    content.parse::<proc_macro2::TokenStream>()?;
+                    Some(true)
+                }
+            };
+            Ok(())
+        });
+    }
+    source
+}
+
+/// Consumes the `= value` or `(...)` following a nested meta we don't care about.
+fn skip_meta_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<proc_macro2::TokenStream>()?;
+    }
+    Ok(())
+}
+
+/// Returns `generics` with a `Self: Display` predicate added when the type has type
+/// parameters, since the generated impls render the error through its (possibly
+/// conditional) `Display` implementation.
+///
+/// Bounding `Self` rather than each parameter keeps the impls available for parameters
+/// that never show up in a display string.
+fn display_bounded(generics: &syn::Generics) -> syn::Generics {
+    let mut generics = generics.clone();
+    if generics.type_params().next().is_some() {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(Self: ::core::fmt::Display));
+    }
+    generics
+}
+
+fn has_tuple_variant(data: &Data) -> bool {
+    match data {
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .any(|variant| matches!(variant.fields, Fields::Unnamed(_))),
+        _ => false,
+    }
+}
+
+fn has_location_field(fields: &Fields) -> bool {
+    fields.iter().any(|field| {
+        field
+            .ident
+            .as_ref()
+            .is_some_and(|ident| ident == "location")
     })
 }
 
 fn generate_virtual_stack_trace_impl(
+    args: &Args,
     name: &syn::Ident,
-    impl_generics: &syn::ImplGenerics,
-    ty_generics: &syn::TypeGenerics,
-    where_clause: Option<&syn::WhereClause>,
+    attrs: &[syn::Attribute],
+    data: &Data,
+    transparent: &[bool],
+    generics: &syn::Generics,
 ) -> syn::Result<proc_macro2::TokenStream> {
+    let krate = args.krate();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let captured_location = captured_location(data, &krate);
+    let nested_stack = nested_stack(data, &krate);
+    let variant = variant_name(data);
+    let static_message = static_message(attrs, data);
+    let captures = captures(data, &krate);
+    let frame_fields = frame_fields(data, &krate)?;
+    let stack = if args.cache {
+        let cache = injected_field(data, CACHE_FIELD);
+        quote! {
+            let cache: Option<&#krate::StackCache> = #cache;
+            match cache {
+                Some(cache) => cache.stack(build),
+                None => build(),
+            }
+        }
+    } else {
+        quote!(build())
+    };
+
+    let max_depth = match &args.max_depth {
+        Some(lit) => quote!(#lit),
+        None => quote!(#krate::DEFAULT_MAX_DEPTH),
+    };
+    let error_label = match &args.error_label {
+        Some(lit) => quote!(#lit),
+        None => quote!("Error"),
+    };
+    let trace_label = match &args.trace_label {
+        Some(lit) => quote!(#lit),
+        None => quote!("Virtual Stack Trace"),
+    };
+    let location_separator = match &args.location_separator {
+        Some(lit) => quote!(#lit),
+        None => quote!(#krate::location_separator()),
+    };
+    let max_message_len = match &args.max_message_len {
+        Some(lit) => quote!(Some(#lit)),
+        None => quote!(#krate::max_message_len()),
+    };
+    let max_frames = match &args.max_frames {
+        Some(lit) => quote!(Some(#lit)),
+        None => quote!(#krate::max_frames()),
+    };
+    let root_first = if args.root_first {
+        quote!(true)
+    } else {
+        quote!(#krate::root_first())
+    };
+    let clean_messages = if args.clean_messages {
+        quote!(true)
+    } else {
+        quote!(#krate::clean_messages())
+    };
+    let compact_leaf = if args.compact_leaf {
+        quote!(true)
+    } else {
+        quote!(#krate::compact_leaf())
+    };
+    let walk = if args.no_source_walk {
+        quote! {
+            // Only the error itself is reported, its sources are never visited
+            let stack = #krate::StackIter::single(self, location);
+        }
+    } else {
+        quote! {
+            use #krate::__private::{ForeignSource as _, LeafSource as _, NestedStack as _};
+
+            // Splice in the frames of a source with its own virtual stack, otherwise
+            // walk the error source chain lazily, bounded in case it contains a cycle
+            let nested: Option<#krate::StackIter<'_>> = #nested_stack;
+            let stack = match nested {
+                Some(nested) => {
+                    #krate::StackIter::with_nested(self, location, nested, #max_depth)
+                }
+                None => #krate::StackIter::new(self, location, #max_depth),
+            };
+        }
+    };
+    let mut dedup = quote!(stack);
+    if args.dedup {
+        dedup = quote!(#dedup.dedup());
+    }
+    if args.dedup_os_errors {
+        dedup = quote!(#dedup.dedup_os_errors());
+    }
+    let transparent = transparent_stack(data, transparent, &krate).map(|transparent| {
+        quote! {
+            use #krate::__private::{ForeignSource as _, LeafSource as _, NestedStack as _};
+
+            // Transparent errors leave out their own frame and start the stack at their
+            // source, splicing in its frames or else walking its source chain
+            let transparent: Option<Option<#krate::StackIter<'_>>> = #transparent;
+            if let Some(nested) = transparent {
+                let stack = match (nested, ::core::error::Error::source(self)) {
+                    (Some(nested), _) => nested,
+                    (None, Some(source)) => #krate::StackIter::new(source, location, #max_depth),
+                    (None, None) => #krate::StackIter::single(self, location),
+                };
+                return #dedup;
+            }
+        }
+    });
+
+    let debug_impl = if args.no_debug {
+        quote!()
+    } else {
+        quote! {
+            impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    if !#krate::is_enabled() {
+                        return write!(f, "{}: {}", #error_label, self);
+                    }
+
+                    let options = #krate::__private::DebugOptions {
+                        error_label: #error_label,
+                        trace_label: #trace_label,
+                        max_message_len: #max_message_len,
+                        max_frames: #max_frames,
+                        root_first: #root_first,
+                        clean_messages: #clean_messages,
+                        compact_leaf: #compact_leaf,
+                        location_separator: #location_separator,
+                        level: #krate::ErrorExt::level(self),
+                    };
+                    #krate::__private::write_debug(f, &options, self)
+                }
+            }
+        }
+    };
+
     Ok(quote! {
-        impl #impl_generics snafu_virtstack::VirtualStackTrace for #name #ty_generics #where_clause {
+        impl #impl_generics #krate::VirtualStackTrace for #name #ty_generics #where_clause {
+            #[track_caller]
+            fn virtual_stack(&self) -> #krate::Stack {
+                #krate::__private::collect_stack(self.virtual_stack_iter())
+            }
+
             #[track_caller]
-            fn virtual_stack(&self) -> Vec<snafu_virtstack::StackFrame> {
-                let mut stack = vec![snafu_virtstack::StackFrame::new(
-                    std::panic::Location::caller(),
-                    self.to_string(),
-                )];
-
-                // Walk the error source chain
-                let mut current_error = self as &dyn std::error::Error;
-                while let Some(source) = current_error.source() {
-                    // Add a simple frame for this source
-                    stack.push(snafu_virtstack::StackFrame::new(
-                        std::panic::Location::caller(),
-                        source.to_string(),
-                    ));
-                    current_error = source;
+            fn virtual_stack_iter(&self) -> #krate::StackIter<'_> {
+                let captured = #krate::VirtualStackTrace::location(self);
+                // Variants without a captured location fall back to the caller
+                let location = match captured {
+                    Some(location) => location,
+                    None => #krate::Location::caller(),
+                };
+
+                let build = || {
+                    #transparent
+                    #walk
+                    let stack = stack.with_module(::core::module_path!());
+                    #captures
+                    #variant
+                    #static_message
+                    #frame_fields
+                    #dedup
+                };
+                #stack
+            }
+
+            fn as_error(&self) -> Option<&(dyn ::core::error::Error + 'static)>
+            where
+                Self: 'static,
+            {
+                Some(self)
+            }
+
+            fn location(&self) -> Option<#krate::Location> {
+                #captured_location
+            }
+
+            fn render(&self) -> #krate::__private::String {
+                #krate::__private::format!("{:?}", self)
+            }
+        }
+
+        #debug_impl
+    })
+}
+
+/// Generates an expression evaluating to the `Option<Location>` captured by `self`.
+fn captured_location(data: &Data, krate: &syn::Path) -> proc_macro2::TokenStream {
+    match data {
+        Data::Enum(data) => {
+            // Map each variant to the location it captured, if any
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                if has_location_field(&variant.fields) {
+                    quote! {
+                        Self::#ident { location, .. } => Some(#krate::Location::from(*location)),
+                    }
+                } else {
+                    quote! {
+                        Self::#ident { .. } => None,
+                    }
+                }
+            });
+
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Struct(data) if has_location_field(&data.fields) => {
+            quote!(Some(#krate::Location::from(self.location)))
+        }
+        _ => quote!(None),
+    }
+}
+
+/// Generates a statement handing the display string of `self` to `stack` when it is a
+/// literal without placeholders, so that the root frame borrows it instead of allocating.
+fn static_message(attrs: &[syn::Attribute], data: &Data) -> proc_macro2::TokenStream {
+    match data {
+        Data::Enum(data) => {
+            let messages: Vec<_> = data
+                .variants
+                .iter()
+                .map(|variant| display_literal(&variant.attrs))
+                .collect();
+            if messages.iter().all(Option::is_none) {
+                return quote!();
+            }
+
+            let arms = data
+                .variants
+                .iter()
+                .zip(messages)
+                .map(|(variant, message)| {
+                    let ident = &variant.ident;
+                    match message {
+                        Some(message) => {
+                            quote!(Self::#ident { .. } => stack.with_static_message(#message),)
+                        }
+                        None => quote!(Self::#ident { .. } => stack,),
+                    }
+                });
+            quote! {
+                let stack = match self {
+                    #(#arms)*
+                };
+            }
+        }
+        _ => match display_literal(attrs) {
+            Some(message) => quote!(let stack = stack.with_static_message(#message);),
+            None => quote!(),
+        },
+    }
+}
+
+/// Generates a statement attaching the `#[frame]` fields of `self` to the metadata of the
+/// root frame of `stack`.
+fn frame_fields(data: &Data, krate: &syn::Path) -> syn::Result<proc_macro2::TokenStream> {
+    let with_meta = |fields: &Fields, value: &dyn Fn(&syn::Ident) -> proc_macro2::TokenStream| {
+        let mut calls = Vec::new();
+        for field in fields {
+            let Some(attr) = field
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("frame"))
+            else {
+                continue;
+            };
+            if !matches!(attr.meta, syn::Meta::Path(_)) {
+                return Err(syn::Error::new_spanned(attr, "#[frame] takes no arguments"));
+            }
+            let Some(ident) = &field.ident else {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "#[frame] requires a named field",
+                ));
+            };
+            let name = ident.to_string();
+            let value = value(ident);
+            calls.push(quote_spanned! {field.ty.span()=>
+                .with_meta(#name, #krate::__private::frame_value(#value))
+            });
+        }
+        Ok(calls)
+    };
+
+    match data {
+        Data::Enum(data) => {
+            let mut marked = false;
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let calls = with_meta(&variant.fields, &|field| quote!(#field))?;
+                marked |= !calls.is_empty();
+                let bindings = variant
+                    .fields
+                    .iter()
+                    .filter(|field| field.attrs.iter().any(|attr| attr.path().is_ident("frame")))
+                    .filter_map(|field| field.ident.as_ref());
+                arms.push(quote!(Self::#ident { #(#bindings,)* .. } => stack #(#calls)*,));
+            }
+            if !marked {
+                return Ok(quote!());
+            }
+
+            Ok(quote! {
+                let stack = match self {
+                    #(#arms)*
+                };
+            })
+        }
+        Data::Struct(data) => {
+            let calls = with_meta(
+                &data.fields,
+                &|field| quote_spanned!(field.span()=> &self.#field),
+            )?;
+            if calls.is_empty() {
+                return Ok(quote!());
+            }
+            Ok(quote!(let stack = stack #(#calls)*;))
+        }
+        Data::Union(_) => Ok(quote!()),
+    }
+}
+
+/// Removes the per-variant attributes of this crate from `data` when the item is emitted
+/// again without generating the impls that would otherwise consume them.
+fn strip_variant_attrs(data: &mut Data) {
+    const ATTRS: [&str; 4] = ["status_code", "level", "external_msg", "transparent"];
+
+    if let Data::Enum(data) = data {
+        for variant in &mut data.variants {
+            variant
+                .attrs
+                .retain(|attr| !ATTRS.iter().any(|name| attr.path().is_ident(name)));
+        }
+    }
+}
+
+/// Removes the `#[frame]` markers from the fields of `data` before the item is emitted again.
+fn strip_frame_attrs(data: &mut Data) {
+    let strip = |fields: &mut Fields| {
+        for field in fields {
+            field.attrs.retain(|attr| !attr.path().is_ident("frame"));
+        }
+    };
+
+    match data {
+        Data::Enum(data) => {
+            for variant in &mut data.variants {
+                strip(&mut variant.fields);
+            }
+        }
+        Data::Struct(data) => strip(&mut data.fields),
+        Data::Union(_) => {}
+    }
+}
+
+/// Returns the format string of a `#[snafu(display("..."))]` attribute without
+/// placeholders, which is then exactly what the Snafu `Display` implementation writes.
+fn display_literal(attrs: &[syn::Attribute]) -> Option<syn::LitStr> {
+    let mut literal = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("snafu")) {
+        // Malformed attributes are reported by the Snafu derive itself
+        let _ = attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("display") {
+                return skip_meta_value(&meta);
+            }
+
+            let content;
+            syn::parenthesized!(content in meta.input);
+            let args = content.parse_terminated(syn::Expr::parse, syn::Token![,])?;
+            literal = match args.first() {
+                Some(syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                })) if args.len() == 1 && !lit.value().contains(['{', '}']) => Some(lit.clone()),
+                _ => None,
+            };
+            Ok(())
+        });
+    }
+    literal
+}
+
+/// Generates a statement naming the root frame of `stack` after the variant of `self`.
+///
+/// Structs have no variant, so their frames are left unnamed.
+///
+/// Like every match generated here, it lists each variant without a wildcard arm: the impls
+/// are expanded in the crate defining the type, where `#[non_exhaustive]` doesn't apply.
+fn variant_name(data: &Data) -> proc_macro2::TokenStream {
+    match data {
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let name = ident.to_string();
+                quote!(Self::#ident { .. } => #name,)
+            });
+
+            quote! {
+                let stack = stack.with_variant(match self {
+                    #(#arms)*
+                });
+            }
+        }
+        _ => quote!(),
+    }
+}
+
+/// Generates an expression evaluating to an `Option` referencing the field `name` injected
+/// into `self`, such as [`CACHE_FIELD`].
+fn injected_field(data: &Data, name: &str) -> proc_macro2::TokenStream {
+    let field = syn::Ident::new(name, proc_macro2::Span::call_site());
+    let has_field = |fields: &Fields| {
+        fields
+            .iter()
+            .any(|field| field.ident.as_ref().is_some_and(|ident| ident == name))
+    };
+
+    match data {
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                if has_field(&variant.fields) {
+                    quote!(Self::#ident { #field, .. } => Some(#field),)
+                } else {
+                    quote!(Self::#ident { .. } => None,)
                 }
+            });
 
-                stack
+            quote! {
+                match self {
+                    #(#arms)*
+                }
             }
         }
+        Data::Struct(data) if has_field(&data.fields) => quote!(Some(&self.#field)),
+        _ => quote!(None),
+    }
+}
+
+/// Removes the `#[name(...)]` attribute from `attrs`, rejecting duplicates.
+fn take_attr(attrs: &mut Vec<syn::Attribute>, name: &str) -> syn::Result<Option<syn::Attribute>> {
+    let mut taken: Option<syn::Attribute> = None;
+    let mut result = Ok(());
+
+    attrs.retain(|attr| {
+        if !attr.path().is_ident(name) {
+            return true;
+        }
+        if taken.is_some() {
+            result = Err(syn::Error::new_spanned(
+                attr,
+                format!("duplicate #[{name}] attribute"),
+            ));
+        }
+        taken = Some(attr.clone());
+        false
+    });
+
+    result.map(|()| taken)
+}
+
+/// Takes the `#[status_code(Code)]` attribute and returns the `StatusCode` expression.
+fn take_status_code(
+    attrs: &mut Vec<syn::Attribute>,
+    krate: &syn::Path,
+) -> syn::Result<proc_macro2::TokenStream> {
+    match take_attr(attrs, "status_code")? {
+        Some(attr) => {
+            let code: syn::Ident = attr.parse_args()?;
+            Ok(quote!(#krate::StatusCode::#code))
+        }
+        None => Ok(quote!(#krate::StatusCode::Internal)),
+    }
+}
+
+/// Takes the `#[level(Level)]` attribute and returns the `Level` expression, if present.
+fn take_level(
+    attrs: &mut Vec<syn::Attribute>,
+    krate: &syn::Path,
+) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    match take_attr(attrs, "level")? {
+        Some(attr) => {
+            let level: syn::Ident = attr.parse_args()?;
+            Ok(Some(quote!(#krate::Level::#level)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Takes the `#[external_msg("...")]` attribute and returns its message, if present.
+fn take_external_msg(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Option<syn::LitStr>> {
+    take_attr(attrs, "external_msg")?
+        .map(|attr| attr.parse_args())
+        .transpose()
+}
 
-        impl #impl_generics std::fmt::Debug for #name #ty_generics #where_clause {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                use snafu_virtstack::VirtualStackTrace;
+/// Generates the `ErrorExt` implementation from the `#[status_code]`, `#[level]` and
+/// `#[external_msg]` attributes.
+fn generate_status_code_impl(
+    input: &mut DeriveInput,
+    krate: &syn::Path,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let to_string = quote!(#krate::__private::ToString::to_string(self));
+    let external_msg = |msg: Option<syn::LitStr>| match msg {
+        Some(msg) => quote!(#krate::__private::String::from(#msg)),
+        None => to_string.clone(),
+    };
 
-                writeln!(f, "Error: {}", self)?;
-                writeln!(f, "Virtual Stack Trace:")?;
+    let (body, level, output_msg) = match &mut input.data {
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            let mut level_arms = Vec::new();
+            let mut leveled = false;
+            let mut msg_arms = Vec::new();
+            let mut overridden = false;
+            for variant in &mut data.variants {
+                let ident = &variant.ident;
+                let code = take_status_code(&mut variant.attrs, krate)?;
+                arms.push(quote!(Self::#ident { .. } => #code,));
 
-                let stack = self.virtual_stack();
-                for (i, frame) in stack.iter().enumerate() {
-                    writeln!(f, "  {}: {}", i, frame)?;
+                let level = take_level(&mut variant.attrs, krate)?;
+                leveled |= level.is_some();
+                let level = level.unwrap_or_else(|| quote!(#krate::Level::Error));
+                level_arms.push(quote!(Self::#ident { .. } => #level,));
+
+                let msg = take_external_msg(&mut variant.attrs)?;
+                overridden |= msg.is_some();
+                let msg = external_msg(msg);
+                msg_arms.push(quote!(Self::#ident { .. } => #msg,));
+            }
+
+            let body = quote! {
+                match self {
+                    #(#arms)*
+                }
+            };
+            let level = leveled.then(|| {
+                quote! {
+                    match self {
+                        #(#level_arms)*
+                    }
+                }
+            });
+            let output_msg = overridden.then(|| {
+                quote! {
+                    match self {
+                        #(#msg_arms)*
+                    }
                 }
+            });
+            (body, level, output_msg)
+        }
+        _ => {
+            let body = take_status_code(&mut input.attrs, krate)?;
+            let level = take_level(&mut input.attrs, krate)?;
+            let output_msg =
+                take_external_msg(&mut input.attrs)?.map(|msg| external_msg(Some(msg)));
+            (body, level, output_msg)
+        }
+    };
 
-                Ok(())
+    // Without levels the provided method already returns `Level::Error`
+    let level = level.map(|level| {
+        quote! {
+            fn level(&self) -> #krate::Level {
+                #level
+            }
+        }
+    });
+
+    // Without overrides the provided method already returns the display string
+    let output_msg = output_msg.map(|output_msg| {
+        quote! {
+            fn output_msg(&self) -> #krate::__private::String {
+                #output_msg
+            }
+        }
+    });
+
+    let name = &input.ident;
+    let generics = display_bounded(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #krate::ErrorExt for #name #ty_generics #where_clause {
+            fn status_code(&self) -> #krate::StatusCode {
+                #body
             }
+
+            #level
+
+            #output_msg
         }
     })
 }
+
+/// Generates an expression evaluating to the `Option<StackIter>` of the source field of
+/// `self`, which is only `Some` when the source implements `VirtualStackTrace`.
+fn nested_stack(data: &Data, krate: &syn::Path) -> proc_macro2::TokenStream {
+    match data {
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                if let Some(source) = source_field(&variant.fields) {
+                    quote! {
+                        Self::#ident { #source: source, .. } => {
+                            (&&#krate::__private::Source(source)).nested_stack()
+                        }
+                    }
+                } else {
+                    quote! {
+                        Self::#ident { .. } => None,
+                    }
+                }
+            });
+
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Struct(data) => match source_field(&data.fields) {
+            Some(source) => {
+                quote!((&&#krate::__private::Source(&self.#source)).nested_stack())
+            }
+            None => quote!(None),
+        },
+        _ => quote!(None),
+    }
+}